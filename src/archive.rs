@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (2025) Christian W. Zuckschwerdt
+
+//! I/Q Viewer -- Capture archives (zip, tar, tar.gz).
+//!
+//! The I/Q entries are listed when the archive is opened, each is extracted to a temporary
+//! folder on demand, when its thumbnail is rendered or it is viewed. Other entries are skipped.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use crate::dirs::is_iq_file;
+
+/// Temp folders of other instances untouched for this long are left over, e.g. from a crash.
+const STALE_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Tests for a zip or (gzipped) tar archive by extension.
+pub fn is_archive(path: impl AsRef<Path>) -> bool {
+    let name = path.as_ref().file_name().unwrap_or_default().to_string_lossy().to_ascii_lowercase();
+    name.ends_with(".zip") || name.ends_with(".tar") || name.ends_with(".tar.gz") || name.ends_with(".tgz")
+}
+
+/// Temporary folder of this app instance, removed by `cleanup()`.
+fn temp_dir() -> PathBuf {
+    env::temp_dir().join(format!("iqviewer-{}", std::process::id()))
+}
+
+/// An I/Q entry of an archive and the place it is extracted to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    archive: PathBuf,
+    name: PathBuf,
+    path: PathBuf,
+}
+
+impl Entry {
+    /// Where the entry is extracted to, the path of the gallery item.
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    /// The canonical path of the archive.
+    pub fn archive(&self) -> &Path {
+        self.archive.as_path()
+    }
+
+    /// The relative path of the entry in the archive.
+    pub fn name(&self) -> &Path {
+        self.name.as_path()
+    }
+
+    /// Extracts the entry unless that was done before, blocks until written.
+    /// Writes to a partial file first, an interrupted extraction is not taken for done.
+    pub fn extract(&self) -> io::Result<()> {
+        if self.path.exists() {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut partial = self.path.clone().into_os_string();
+        partial.push(".part");
+        let partial = PathBuf::from(partial);
+
+        let copied = fs::File::create(&partial).and_then(|mut dst| match archive_kind(&self.archive) {
+            Kind::Zip => extract_zip(&self.archive, &self.name, &mut dst),
+            Kind::Tar => extract_tar(fs::File::open(&self.archive)?, &self.name, &mut dst),
+            Kind::TarGz => extract_tar(
+                flate2::read::GzDecoder::new(fs::File::open(&self.archive)?),
+                &self.name,
+                &mut dst,
+            ),
+        });
+        match copied {
+            Ok(()) => fs::rename(&partial, &self.path),
+            Err(err) => {
+                let _ = fs::remove_file(&partial);
+                Err(err)
+            }
+        }
+    }
+}
+
+enum Kind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+fn archive_kind(path: &Path) -> Kind {
+    let name = path.file_name().unwrap_or_default().to_string_lossy().to_ascii_lowercase();
+    if name.ends_with(".zip") {
+        Kind::Zip
+    } else if name.ends_with(".tar") {
+        Kind::Tar
+    } else {
+        Kind::TarGz
+    }
+}
+
+/// Lists the I/Q entries of the archive without extracting them.
+/// Entries keep their relative path, each archive gets its own folder.
+pub fn list_iq(path: impl AsRef<Path>) -> io::Result<Vec<Entry>> {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    let path = path.as_ref().canonicalize()?;
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    let dir = temp_dir().join(format!("{:016x}", hasher.finish()));
+    fs::create_dir_all(&dir)?;
+    // items are keyed by canonical path, e.g. the temp folder is a symlink on macOS
+    let dir = dir.canonicalize()?;
+
+    let mut names = match archive_kind(&path) {
+        Kind::Zip => list_zip(&path)?,
+        Kind::Tar => list_tar(fs::File::open(&path)?)?,
+        Kind::TarGz => list_tar(flate2::read::GzDecoder::new(fs::File::open(&path)?))?,
+    };
+    names.sort();
+    Ok(names
+        .into_iter()
+        .map(|name| Entry {
+            archive: path.clone(),
+            path: dir.join(&name),
+            name,
+        })
+        .collect())
+}
+
+/// Tests for an I/Q entry with a relative path that stays inside the extraction folder.
+fn is_iq_entry(name: &Path) -> bool {
+    name.components().all(|c| matches!(c, Component::Normal(_))) && is_iq_file(name)
+}
+
+fn list_zip(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(path)?).map_err(io::Error::other)?;
+    let mut names = vec![];
+    for index in 0..archive.len() {
+        let entry = archive.by_index_raw(index).map_err(io::Error::other)?;
+        if let Some(name) = entry.enclosed_name().filter(|name| entry.is_file() && is_iq_entry(name)) {
+            names.push(name);
+        }
+    }
+    Ok(names)
+}
+
+fn list_tar(reader: impl io::Read) -> io::Result<Vec<PathBuf>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut names = vec![];
+    for entry in archive.entries()? {
+        let entry = entry?;
+        let name = entry.path()?;
+        if entry.header().entry_type().is_file() && is_iq_entry(&name) {
+            names.push(name.into_owned());
+        }
+    }
+    Ok(names)
+}
+
+fn extract_zip(path: &Path, name: &Path, dst: &mut fs::File) -> io::Result<()> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(path)?).map_err(io::Error::other)?;
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(io::Error::other)?;
+        if entry.enclosed_name().as_deref() == Some(name) {
+            io::copy(&mut entry, dst)?;
+            return Ok(());
+        }
+    }
+    Err(io::ErrorKind::NotFound.into())
+}
+
+fn extract_tar(reader: impl io::Read, name: &Path, dst: &mut fs::File) -> io::Result<()> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if entry.path()? == name {
+            io::copy(&mut entry, dst)?;
+            return Ok(());
+        }
+    }
+    Err(io::ErrorKind::NotFound.into())
+}
+
+/// Removes all extracted files, call on quit.
+pub fn cleanup() {
+    let dir = temp_dir();
+    if dir.exists() {
+        if let Err(err) = fs::remove_dir_all(&dir) {
+            println!("Archive cleanup error {err:?}");
+        }
+    }
+}
+
+/// Removes the temp folders left over by earlier instances, call on start.
+/// A folder still in use by a running instance is extracted to again as needed.
+pub fn cleanup_stale() {
+    let Ok(entries) = fs::read_dir(env::temp_dir()) else {
+        return;
+    };
+    let own = temp_dir();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_ours = entry.file_name().to_string_lossy().starts_with("iqviewer-");
+        let age = entry.metadata().and_then(|m| m.modified()).ok().and_then(|t| t.elapsed().ok());
+        if is_ours && path != own && age.is_some_and(|age| age > STALE_AGE) {
+            if let Err(err) = fs::remove_dir_all(&path) {
+                println!("Archive cleanup error {err:?}");
+            }
+        }
+    }
+}
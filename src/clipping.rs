@@ -0,0 +1,69 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (2025) Christian W. Zuckschwerdt
+
+//! I/Q Viewer -- Detection of clipped (saturated) samples in a capture.
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::component::{data_range, is_supported};
+use crate::plot_ffi::sample_pair_size;
+
+/// Blocks of consecutive samples checked, spread evenly over longer captures.
+const CHECKED_BLOCKS: u64 = 64;
+const BLOCK_SAMPLES: u64 = 4096;
+
+/// Tests if the I or Q value of the pair at the start of `bytes` is at the full scale of the format.
+fn is_clipped(sample_format: &str, bytes: &[u8]) -> bool {
+    let i16_at = |i: usize| i16::from_le_bytes([bytes[i], bytes[i + 1]]);
+    let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+    match sample_format {
+        "CU8" => bytes[..2].iter().any(|&v| v == u8::MIN || v == u8::MAX),
+        "CS8" => bytes[..2].iter().any(|&v| v as i8 == i8::MIN || v as i8 == i8::MAX),
+        "CU16" => [0, 2].iter().any(|&i| u16_at(i) == u16::MIN || u16_at(i) == u16::MAX),
+        "CS16" => [0, 2].iter().any(|&i| i16_at(i) == i16::MIN || i16_at(i) == i16::MAX),
+        _ => false,
+    }
+}
+
+/// Fraction of samples with the I or Q value at full scale, `None` for unsupported formats.
+/// Float captures have no fixed full scale, clipping is not detected for CF32.
+pub fn clipped_fraction(path: &Path, sample_format: &str) -> io::Result<Option<f32>> {
+    let Some(pair_size) = sample_pair_size(sample_format).filter(|_| is_supported(sample_format)) else {
+        return Ok(None);
+    };
+    if sample_format == "CF32" {
+        return Ok(None);
+    }
+    let pair_size = pair_size as u64;
+    let file = File::open(path)?;
+    // the file is only read, a concurrent truncation is the usual risk of mapping
+    let data = unsafe { Mmap::map(&file)? };
+    let (offset, len) = data_range(path, data.len() as u64)?;
+    let data = &data[offset as usize..(offset + len) as usize];
+    let sample_count = len / pair_size;
+    if sample_count == 0 {
+        return Ok(None);
+    }
+
+    // a few contiguous blocks touch less pages than samples strided over the whole file
+    let blocks: Vec<(u64, u64)> = if sample_count <= CHECKED_BLOCKS * BLOCK_SAMPLES {
+        vec![(0, sample_count)]
+    } else {
+        let stride = (sample_count - BLOCK_SAMPLES) / (CHECKED_BLOCKS - 1);
+        (0..CHECKED_BLOCKS).map(|block| (block * stride, block * stride + BLOCK_SAMPLES)).collect()
+    };
+    let total: u64 = blocks.iter().map(|(start, end)| end - start).sum();
+    let clipped = blocks
+        .into_iter()
+        .flat_map(|(start, end)| start..end)
+        .filter(|&sample| {
+            let offset = (sample * pair_size) as usize;
+            is_clipped(sample_format, &data[offset..offset + pair_size as usize])
+        })
+        .count();
+    Ok(Some(clipped as f32 / total as f32))
+}
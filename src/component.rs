@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (2025) Christian W. Zuckschwerdt
+
+//! I/Q Viewer -- Amplitude of the I or Q component over time.
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::plot_ffi::sample_pair_size;
+use crate::sigmf;
+
+/// Most samples read per pixel, longer spans are sampled evenly.
+const SAMPLES_PER_PIXEL: u64 = 64;
+
+/// Decodes the I (or Q if `q`) value of the pair at the start of `bytes`, scaled to -1..1.
+fn component_value(sample_format: &str, bytes: &[u8], q: bool) -> Option<f32> {
+    let i16_at = |i: usize| i16::from_le_bytes([bytes[i], bytes[i + 1]]);
+    let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+    let f32_at = |i: usize| f32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+    let index = usize::from(q);
+    match sample_format {
+        "CU8" => Some((bytes[index] as f32 - 127.5) / 127.5),
+        "CS8" => Some(bytes[index] as i8 as f32 / 128.0),
+        "CU16" => Some((u16_at(index * 2) as f32 - 32767.5) / 32767.5),
+        "CS16" => Some(i16_at(index * 2) as f32 / 32768.0),
+        "CF32" => Some(f32_at(index * 4).clamp(-1.0, 1.0)),
+        _ => None,
+    }
+}
+
+/// Tests if the component strip can decode the `sample_format`.
+pub fn is_supported(sample_format: &str) -> bool {
+    matches!(sample_format, "CU8" | "CS8" | "CU16" | "CS16" | "CF32")
+}
+
+/// Byte offset and length of the sample data in a file of `len` bytes: the data member
+/// of a `.sigmf` archive, otherwise all of the file.
+pub fn data_range(path: &Path, len: u64) -> io::Result<(u64, u64)> {
+    let (offset, size) = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("sigmf") => sigmf::archive_data_range(path)?,
+        _ => (0, len),
+    };
+    // a truncated file ends the data early
+    let offset = offset.min(len);
+    Ok((offset, size.min(len - offset)))
+}
+
+/// Minimum and maximum of the I (or Q if `q`) component for each of the sample `spans`.
+/// Spans are `(start, end)` sample indices, e.g. one per pixel along the time axis.
+pub fn component_ranges(
+    path: &Path,
+    sample_format: &str,
+    q: bool,
+    spans: &[(u64, u64)],
+) -> io::Result<Vec<(f32, f32)>> {
+    let Some(pair_size) = sample_pair_size(sample_format).filter(|_| is_supported(sample_format)) else {
+        let message = format!("No component view for {sample_format}");
+        return Err(io::Error::new(io::ErrorKind::Unsupported, message));
+    };
+    let pair_size = pair_size as u64;
+    let file = File::open(path)?;
+    // the file is only read, a concurrent truncation is the usual risk of mapping
+    let data = unsafe { Mmap::map(&file)? };
+    let (offset, len) = data_range(path, data.len() as u64)?;
+    let data = &data[offset as usize..(offset + len) as usize];
+    let sample_count = data.len() as u64 / pair_size;
+
+    let ranges = spans
+        .iter()
+        .map(|&(start, end)| {
+            let end = end.max(start + 1).min(sample_count);
+            let step = ((end.saturating_sub(start)) / SAMPLES_PER_PIXEL).max(1);
+            (start..end)
+                .step_by(step as usize)
+                .filter_map(|sample| {
+                    let offset = (sample * pair_size) as usize;
+                    component_value(sample_format, &data[offset..offset + pair_size as usize], q)
+                })
+                .fold(None, |range: Option<(f32, f32)>, value| match range {
+                    Some((min, max)) => Some((min.min(value), max.max(value))),
+                    None => Some((value, value)),
+                })
+                .unwrap_or_default()
+        })
+        .collect();
+    Ok(ranges)
+}
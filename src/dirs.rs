@@ -3,10 +3,16 @@
 
 //! I/Q Viewer -- file and directory helper.
 
+use std::collections::HashSet;
+use std::env;
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 
+use crate::archive::is_archive;
+use crate::options::ViewOptions;
+use crate::sigmf;
+
 #[rustfmt::skip]
 pub const FORMATS: &[&str] = &[
     "cu4",
@@ -26,6 +32,34 @@ pub const FORMATS: &[&str] = &[
     "sigmf",
 ];
 
+/// Platform config folder of the app, e.g. `~/.config/iqviewer`.
+pub fn config_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let base = env::var_os("APPDATA").map(PathBuf::from);
+    #[cfg(target_os = "macos")]
+    let base = env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"));
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+
+    base.map(|dir| dir.join("iqviewer"))
+}
+
+/// Platform cache folder of the app, e.g. `~/.cache/iqviewer`.
+pub fn cache_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let base = env::var_os("LOCALAPPDATA").map(PathBuf::from);
+    #[cfg(target_os = "macos")]
+    let base = env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Caches"));
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let base = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")));
+
+    base.map(|dir| dir.join("iqviewer"))
+}
+
 #[rustfmt::skip]
 pub fn is_iq_file(path: impl AsRef<Path>) -> bool {
     // TODO: should use triq::SampleFormat::from_path(path)
@@ -48,6 +82,177 @@ pub fn is_iq_file(path: impl AsRef<Path>) -> bool {
     })
 }
 
+/// Links the file under a name with the extension of `sample_format` (e.g. "CS16"),
+/// as libtriq detects the format from the extension only. Only the FFI opens this path.
+/// The link is `<cache>/aliases/<hash>/<filename>.<format>`.
+pub fn format_link(path: impl AsRef<Path>, sample_format: &str) -> io::Result<PathBuf> {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    let path = path.as_ref().canonicalize()?;
+    let filename = path.file_name().ok_or(io::ErrorKind::InvalidInput)?;
+    let cache = cache_dir().ok_or(io::ErrorKind::NotFound)?;
+
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    let dir = cache.join("aliases").join(format!("{:016x}", hasher.finish()));
+    fs::create_dir_all(&dir)?;
+
+    let mut link = dir.join(filename).into_os_string();
+    link.push(".");
+    link.push(sample_format.to_ascii_lowercase());
+    let link = PathBuf::from(link);
+
+    if !link.exists() {
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&path, &link)?;
+        // symlinks need privileges and hard links the same volume, copy as a last resort
+        #[cfg(windows)]
+        std::os::windows::fs::symlink_file(&path, &link)
+            .or_else(|_| fs::hard_link(&path, &link))
+            .or_else(|_| fs::copy(&path, &link).map(|_| ()))?;
+    }
+    Ok(link)
+}
+
+/// Guesses the sample format from the first 64 KiB, for files without a known extension.
+/// Tells CF32, CS16, CS8 and CU8 apart, defaults to CU8 (e.g. `rtl_sdr` output).
+pub fn guess_format(path: impl AsRef<Path>) -> &'static str {
+    use std::io::Read;
+
+    let mut bytes = vec![];
+    let read = fs::File::open(path).and_then(|file| file.take(64 * 1024).read_to_end(&mut bytes));
+    if read.is_err() || bytes.len() < 64 {
+        return "CU8";
+    }
+    let len = bytes.len() / 8 * 8;
+    let bytes = &bytes[..len];
+
+    // floats: all finite, of moderate magnitude and not all zero
+    let mut floats = bytes.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+    let plausible = floats.clone().all(|v| v.is_finite() && v.abs() < 100.0);
+    if plausible && floats.any(|v| v.abs() > 1e-9) {
+        return "CF32";
+    }
+
+    // 16 bit: the high bytes are mostly sign extension, the low bytes are not
+    let near_sign = |b: &u8| *b <= 0x0f || *b >= 0xf0;
+    let high = bytes.iter().skip(1).step_by(2).filter(|b| near_sign(b)).count();
+    let low = bytes.iter().step_by(2).filter(|b| near_sign(b)).count();
+    let half = bytes.len() / 2;
+    if high * 10 > half * 9 && low * 10 < half * 6 {
+        return "CS16";
+    }
+
+    // 8 bit: centered around 0 (signed) or 127 (unsigned)
+    let signed = bytes.iter().filter(|&&b| (b as i8).unsigned_abs() < 32).count();
+    let unsigned = bytes.iter().filter(|&&b| b.abs_diff(127) < 32).count();
+    if signed > unsigned { "CS8" } else { "CU8" }
+}
+
+/// The guessed format of a file without a known I/Q extension, see `guess_format()`.
+/// `None` for folders, lists, archives and known files.
+pub fn unknown_format(path: impl AsRef<Path>) -> Option<&'static str> {
+    let path = path.as_ref();
+    if !path.is_file() || is_iq_file(path) || is_iq_list(path) || is_archive(path) {
+        return None;
+    }
+    Some(guess_format(path))
+}
+
+/// Center frequency and sample rate in Hz found in a file name.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct FilenameMeta {
+    pub center_freq: Option<f64>,
+    pub sample_rate: Option<f64>,
+}
+
+/// Parses the center frequency and sample rate from a file name, separated by `_` or `-`,
+/// e.g. `gfile_433.92M_250k.cu8` or `capture_868000000Hz_2048000sps.cs16`.
+/// Values with `Hz` are frequencies, with `sps` rates, otherwise the first value with
+/// a `k`/`M`/`G` suffix (of any case) is the frequency and the second the rate (the rtl_sdr convention).
+pub fn parse_filename_meta(path: impl AsRef<Path>) -> FilenameMeta {
+    let mut meta = FilenameMeta::default();
+    let Some(stem) = path.as_ref().file_stem().and_then(|stem| stem.to_str()) else {
+        return meta;
+    };
+    for token in stem.split(['_', '-']) {
+        if let Some(freq) = strip_suffix_ignore_case(token, "hz").and_then(parse_si) {
+            meta.center_freq.get_or_insert(freq);
+        } else if let Some(rate) = strip_suffix_ignore_case(token, "sps").and_then(parse_si) {
+            meta.sample_rate.get_or_insert(rate);
+        } else if token.ends_with(['k', 'K', 'm', 'M', 'g', 'G']) {
+            // plain numbers are skipped, likely a counter
+            let Some(value) = parse_si(token) else {
+                continue;
+            };
+            if meta.center_freq.is_none() {
+                meta.center_freq = Some(value);
+            } else if meta.sample_rate.is_none() {
+                meta.sample_rate = Some(value);
+            }
+        }
+    }
+    meta
+}
+
+/// Parses a positive number with an optional `k`, `M` or `G` suffix, e.g. `433.92M`.
+/// The suffix ignores case, a lowercase `m` is mega as well, milli makes no sense for a rate.
+fn parse_si(text: &str) -> Option<f64> {
+    let (number, scale) = match text.chars().last()? {
+        'k' | 'K' => (&text[..text.len() - 1], 1e3),
+        'm' | 'M' => (&text[..text.len() - 1], 1e6),
+        'g' | 'G' => (&text[..text.len() - 1], 1e9),
+        _ => (text, 1.0),
+    };
+    let value: f64 = number.parse().ok()?;
+    (value.is_finite() && value > 0.0).then_some(value * scale)
+}
+
+/// Strips an ASCII `suffix` ignoring case, e.g. `Hz`, `hz` and `HZ`.
+fn strip_suffix_ignore_case<'a>(text: &'a str, suffix: &str) -> Option<&'a str> {
+    let split = text.len().checked_sub(suffix.len())?;
+    let (head, tail) = (text.get(..split)?, text.get(split..)?);
+    tail.eq_ignore_ascii_case(suffix).then_some(head)
+}
+
+/// Tests for an I/Q list (manifest) file, or a SigMF collection.
+pub fn is_iq_list(path: impl AsRef<Path>) -> bool {
+    path.as_ref().extension().is_some_and(|ext| ext == "iqlist") || sigmf::is_collection(path)
+}
+
+/// Interprets pasted text as a path to an existing I/Q file or folder.
+/// A `file://` URI is percent-decoded, e.g. `%20` for a space.
+pub fn iq_path_from_str(text: &str) -> Option<PathBuf> {
+    let text = text.trim().trim_matches(|c| c == '"' || c == '\'');
+    let path = match text.strip_prefix("file://") {
+        Some(uri) => PathBuf::from(percent_decode(uri)?),
+        None => PathBuf::from(text),
+    };
+
+    if path.exists() && (path.is_dir() || is_iq_file(&path) || is_iq_list(&path) || is_archive(&path)) {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// Decodes the `%XX` escapes of a URI path, `None` if malformed or not UTF-8.
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
 pub fn read_dir_iq(dir: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
     let entries = fs::read_dir(dir)?
         .map(|res| res.map(|e| e.path()))
@@ -64,3 +269,239 @@ pub fn read_dir_iq(dir: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
 
     Ok(entries)
 }
+
+/// Reads the I/Q files of `dir` and of its subfolders down to `max_depth` levels, zero reads `dir` only.
+/// Each folder is visited once by its canonical path, thus symlink cycles end there.
+/// Unreadable subfolders are skipped, the files are sorted by their path across all folders.
+pub fn read_dir_iq_recursive(dir: impl AsRef<Path>, max_depth: usize) -> io::Result<Vec<PathBuf>> {
+    fn walk(dir: &Path, depth: usize, visited: &mut HashSet<PathBuf>, files: &mut Vec<PathBuf>) -> io::Result<()> {
+        if !visited.insert(dir.canonicalize()?) {
+            return Ok(());
+        }
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                if depth > 0 {
+                    if let Err(err) = walk(&path, depth - 1, visited, files) {
+                        println!("Read error {err:?} in {}", path.display());
+                    }
+                }
+            } else if is_iq_file(&path) {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = vec![];
+    walk(dir.as_ref(), max_depth, &mut HashSet::new(), &mut files)?;
+    files.sort();
+
+    Ok(files)
+}
+
+/// Keeps the `count` most recently modified of `files` in their order, zero keeps all.
+pub fn newest_files(files: Vec<PathBuf>, count: usize) -> Vec<PathBuf> {
+    if count == 0 || files.len() <= count {
+        return files;
+    }
+    let mut by_mtime: Vec<_> = files
+        .iter()
+        .map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .enumerate()
+        .collect();
+    // newest first, unreadable times last
+    by_mtime.sort_by(|a, b| b.1.cmp(&a.1));
+    let mut keep = vec![false; files.len()];
+    for (index, _) in by_mtime.into_iter().take(count) {
+        keep[index] = true;
+    }
+    let mut keep = keep.into_iter();
+    files.into_iter().filter(|_| keep.next().unwrap_or(false)).collect()
+}
+
+/// Reads an I/Q list, one path per line with optional trailing `key=value` options.
+/// Relative paths are resolved to the folder of the list, `#` starts a comment line.
+///
+/// E.g. `capture_01.cu8 fft=1024 gain=12 cmap=viridis`
+///
+/// A SigMF collection lists its member recordings, without options.
+pub fn read_iq_list(list: impl AsRef<Path>) -> io::Result<Vec<(PathBuf, ViewOptions)>> {
+    let list = list.as_ref();
+    if sigmf::is_collection(list) {
+        let members = sigmf::read_collection(list)?;
+        return Ok(members.into_iter().map(|path| (path, ViewOptions::default())).collect());
+    }
+    let base = list.parent().unwrap_or(Path::new(""));
+    let content = fs::read_to_string(list)?;
+
+    let mut entries = vec![];
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        // peel off trailing options, the rest is the path (which may contain spaces)
+        let mut path = line;
+        let mut options = ViewOptions::default();
+        while let Some((head, token)) = path.rsplit_once(char::is_whitespace) {
+            if !options.parse_token(token) {
+                break;
+            }
+            path = head.trim_end();
+        }
+
+        entries.push((base.join(path), options));
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::FftSize;
+
+    /// A fresh temp folder with `files` created empty, at paths relative to it.
+    fn temp_tree(name: &str, files: &[&str]) -> PathBuf {
+        let dir = env::temp_dir().join(format!("iqviewer-test-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for file in files {
+            let path = dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, b"").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn read_dir_iq_recursive_depth() {
+        let dir = temp_tree("depth", &["a.cu8", "notes.txt", "sub/b.cs16", "sub/deep/c.cf32"]);
+        let read = |depth| -> Vec<PathBuf> {
+            read_dir_iq_recursive(&dir, depth)
+                .unwrap()
+                .iter()
+                .map(|path| path.strip_prefix(&dir).unwrap().to_path_buf())
+                .collect()
+        };
+        assert_eq!(read(0), vec![PathBuf::from("a.cu8")]);
+        assert_eq!(read(1), vec![PathBuf::from("a.cu8"), PathBuf::from("sub/b.cs16")]);
+        assert_eq!(
+            read(2),
+            vec![PathBuf::from("a.cu8"), PathBuf::from("sub/b.cs16"), PathBuf::from("sub/deep/c.cf32")]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn read_dir_iq_recursive_symlink_cycle() {
+        let dir = temp_tree("cycle", &["a.cu8", "sub/b.cu8"]);
+        std::os::unix::fs::symlink(&dir, dir.join("sub/loop")).unwrap();
+        let files = read_dir_iq_recursive(&dir, 8).unwrap();
+        assert_eq!(files, vec![dir.join("a.cu8"), dir.join("sub/b.cu8")]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn read_dir_iq_recursive_missing() {
+        assert!(read_dir_iq_recursive(env::temp_dir().join("iqviewer-test-missing"), 1).is_err());
+    }
+
+    #[test]
+    fn read_iq_list_entries() {
+        let dir = temp_tree("iqlist", &["a.cu8", "sub/b c.cs16"]);
+        let list = dir.join("files.iqlist");
+        let absolute = dir.join("a.cu8");
+        let content = format!(
+            "# captures of the day\n\
+            \n\
+            a.cu8\n   \n\
+            sub/b c.cs16 fft=1024\n\
+            \t# indented comment\n\
+            missing.cu8\n\
+            {}\n",
+            absolute.display()
+        );
+        fs::write(&list, content).unwrap();
+
+        let entries = read_iq_list(&list).unwrap();
+        let paths: Vec<&PathBuf> = entries.iter().map(|(path, _)| path).collect();
+        // relative to the folder of the list, missing files are listed for the caller to report
+        assert_eq!(paths, [&dir.join("a.cu8"), &dir.join("sub/b c.cs16"), &dir.join("missing.cu8"), &absolute]);
+        assert_eq!(entries[0].1, ViewOptions::default());
+        assert_eq!(entries[1].1.fftn, Some(FftSize::Size1024));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn read_iq_list_missing() {
+        assert!(read_iq_list(env::temp_dir().join("iqviewer-test-missing.iqlist")).is_err());
+    }
+
+    #[test]
+    fn percent_decode_escapes() {
+        assert_eq!(percent_decode("/data/a%20b.cu8").as_deref(), Some("/data/a b.cu8"));
+        assert_eq!(percent_decode("/data/%C3%BCber%2fall.cu8").as_deref(), Some("/data/über/all.cu8"));
+        assert_eq!(percent_decode("/data/plain.cu8").as_deref(), Some("/data/plain.cu8"));
+        assert_eq!(percent_decode("/data/100%"), None);
+        assert_eq!(percent_decode("/data/%zz"), None);
+        assert_eq!(percent_decode("/data/%FF"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn iq_path_from_file_uri() {
+        let dir = temp_tree("uri", &["a b.cu8", "über.cs16"]);
+        let uri = |name: &str| format!("file://{}/{name}", dir.display());
+        assert_eq!(iq_path_from_str(&uri("a%20b.cu8")), Some(dir.join("a b.cu8")));
+        assert_eq!(iq_path_from_str(&format!("'{}'", uri("%C3%BCber.cs16"))), Some(dir.join("über.cs16")));
+        assert_eq!(iq_path_from_str(&uri("a b.cu8")), Some(dir.join("a b.cu8")));
+        assert_eq!(iq_path_from_str(&uri("missing.cu8")), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn parse_si_suffixes() {
+        assert_eq!(parse_si("250k"), Some(250e3));
+        assert_eq!(parse_si("250K"), Some(250e3));
+        assert_eq!(parse_si("433.92M"), Some(433.92e6));
+        assert_eq!(parse_si("433.92m"), Some(433.92e6));
+        assert_eq!(parse_si("2.4G"), Some(2.4e9));
+        assert_eq!(parse_si("2048000"), Some(2048000.0));
+    }
+
+    #[test]
+    fn parse_si_invalid() {
+        assert_eq!(parse_si(""), None);
+        assert_eq!(parse_si("M"), None);
+        assert_eq!(parse_si("0k"), None);
+        assert_eq!(parse_si("-5M"), None);
+        assert_eq!(parse_si("12T"), None);
+        assert_eq!(parse_si("infM"), None);
+        assert_eq!(parse_si("gfile"), None);
+    }
+
+    #[test]
+    fn parse_filename_meta_examples() {
+        let meta = parse_filename_meta("gfile_433.92M_250k.cu8");
+        assert_eq!(meta, FilenameMeta { center_freq: Some(433.92e6), sample_rate: Some(250e3) });
+        let meta = parse_filename_meta("/some/folder/capture_868000000Hz_2048000sps.cs16");
+        assert_eq!(meta, FilenameMeta { center_freq: Some(868e6), sample_rate: Some(2.048e6) });
+        // units override the order
+        let meta = parse_filename_meta("capture-1024ksps-915MHz.cs8");
+        assert_eq!(meta, FilenameMeta { center_freq: Some(915e6), sample_rate: Some(1.024e6) });
+        let meta = parse_filename_meta("g005_868.3m_1m.cu8");
+        assert_eq!(meta, FilenameMeta { center_freq: Some(868.3e6), sample_rate: Some(1e6) });
+    }
+
+    #[test]
+    fn parse_filename_meta_invalid() {
+        assert_eq!(parse_filename_meta("capture_0042.cu8"), FilenameMeta::default());
+        assert_eq!(parse_filename_meta("noise-floor.cs16"), FilenameMeta::default());
+        assert_eq!(parse_filename_meta("capture_xHz_ysps.cu8"), FilenameMeta::default());
+        assert_eq!(parse_filename_meta(""), FilenameMeta::default());
+        // a lone value is the frequency only
+        assert_eq!(parse_filename_meta("am_1M.cu8"), FilenameMeta { center_freq: Some(1e6), sample_rate: None });
+    }
+}
@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (2025) Christian W. Zuckschwerdt
+
+//! I/Q Viewer -- Export a sample range to a new I/Q file, or a region's levels as CSV.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::component::data_range;
+use crate::plot_ffi::{RegionLevels, sample_pair_size};
+
+/// SigMF datatype of a sample format, `None` for packed formats without one.
+fn sigmf_datatype(sample_format: &str) -> Option<&'static str> {
+    match sample_format {
+        "CU8" => Some("cu8"),
+        "CS8" => Some("ci8"),
+        "CU16" => Some("cu16_le"),
+        "CS16" => Some("ci16_le"),
+        "CU32" => Some("cu32_le"),
+        "CS32" => Some("ci32_le"),
+        "CF32" => Some("cf32_le"),
+        "CF64" => Some("cf64_le"),
+        _ => None,
+    }
+}
+
+/// Copies `count` samples from `start` of the sample data of an I/Q file to `dst`, in the same format.
+/// Samples count from the data offset, e.g. of a SigMF archive, and end with the data.
+/// A `.sigmf-meta` sidecar with the center frequency and sample rate is written next to it,
+/// named by the stem of `dst`, if the format has a SigMF datatype.
+pub fn export_samples(
+    src: &Path,
+    sample_format: &str,
+    start: u64,
+    count: u64,
+    center_freq: f64,
+    sample_rate: f64,
+    dst: &Path,
+) -> io::Result<()> {
+    let pair_size = sample_pair_size(sample_format)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "unknown sample format"))?
+        as u64;
+
+    let mut file = File::open(src)?;
+    let (offset, len) = data_range(src, file.metadata()?.len())?;
+    let skip = start.saturating_mul(pair_size).min(len);
+    let take = count.saturating_mul(pair_size).min(len - skip);
+    file.seek(SeekFrom::Start(offset + skip))?;
+    io::copy(&mut file.take(take), &mut File::create(dst)?)?;
+
+    if let Some(datatype) = sigmf_datatype(sample_format) {
+        let stem = dst.file_stem().unwrap_or_default().to_string_lossy();
+        let filename = dst.file_name().unwrap_or_default().to_string_lossy();
+        let mut meta = serde_json::json!({
+            "global": {
+                "core:datatype": datatype,
+                "core:sample_rate": sample_rate,
+                "core:version": "1.0.0",
+                "core:description": format!(
+                    "Samples {start} to {} of {}",
+                    start + count,
+                    src.file_name().unwrap_or_default().to_string_lossy()
+                ),
+            },
+            "captures": [{ "core:sample_start": 0, "core:frequency": center_freq }],
+            "annotations": [],
+        });
+        // a data file not named `<stem>.sigmf-data` is paired by name
+        if dst.extension().is_none_or(|ext| ext != "sigmf-data") {
+            meta["global"]["core:dataset"] = filename.into();
+        }
+        fs::write(dst.with_file_name(format!("{stem}.sigmf-meta")), meta.to_string())?;
+    }
+    Ok(())
+}
+
+/// CSV matrix of region levels: the header row holds the start sample of each column,
+/// the first column the frequency in Hz of each row. Levels are shifted by `offset` and labeled with `unit`,
+/// the header notes their quantization step.
+pub fn region_csv(region: &RegionLevels, offset: f32, unit: &str) -> String {
+    let mut csv = format!("freq_hz \\ sample ({unit} in {:.2} dB steps)", region.step);
+    for sample in &region.samples {
+        csv.push_str(&format!(",{sample}"));
+    }
+    csv.push('\n');
+    for (freq, levels) in region.freqs.iter().zip(&region.levels) {
+        csv.push_str(&format!("{freq:.0}"));
+        for level in levels {
+            csv.push_str(&format!(",{:.1}", level + offset));
+        }
+        csv.push('\n');
+    }
+    csv
+}
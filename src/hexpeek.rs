@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (2025) Christian W. Zuckschwerdt
+
+//! I/Q Viewer -- Hex peek into the raw bytes of a file.
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::plot_ffi::sample_pair_size;
+
+/// Number of bytes shown from the start and from the end of the file.
+const PEEK_SIZE: u64 = 1024;
+/// Number of bytes per line.
+const LINE_SIZE: usize = 16;
+
+/// Description of the I/Q pair layout for a sample format.
+fn pair_layout(sample_format: &str) -> &'static str {
+    match sample_format {
+        "CU4" => "I: high nibble, Q: low nibble, unsigned",
+        "CS4" => "I: high nibble, Q: low nibble, signed",
+        "CU8" => "I: 1 byte, Q: 1 byte, unsigned",
+        "CS8" => "I: 1 byte, Q: 1 byte, signed",
+        "CU12" => "I/Q: 12 bit each packed in 3 bytes, unsigned",
+        "CS12" => "I/Q: 12 bit each packed in 3 bytes, signed",
+        "CU16" => "I: 2 bytes LE, Q: 2 bytes LE, unsigned",
+        "CS16" => "I: 2 bytes LE, Q: 2 bytes LE, signed",
+        "CU32" => "I: 4 bytes LE, Q: 4 bytes LE, unsigned",
+        "CS32" => "I: 4 bytes LE, Q: 4 bytes LE, signed",
+        "CF32" => "I: 4 bytes LE, Q: 4 bytes LE, float",
+        "CU64" => "I: 8 bytes LE, Q: 8 bytes LE, unsigned",
+        "CS64" => "I: 8 bytes LE, Q: 8 bytes LE, signed",
+        "CF64" => "I: 8 bytes LE, Q: 8 bytes LE, float",
+        _ => "unknown layout",
+    }
+}
+
+/// Decodes the first I/Q pair of `bytes` for the common formats.
+fn decode_pair(sample_format: &str, bytes: &[u8]) -> Option<String> {
+    let i16_at = |i: usize| i16::from_le_bytes([bytes[i], bytes[i + 1]]);
+    let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+    let f32_at = |i: usize| f32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+    match sample_format {
+        "CU8" if bytes.len() >= 2 => Some(format!("{} {}", bytes[0], bytes[1])),
+        "CS8" if bytes.len() >= 2 => Some(format!("{} {}", bytes[0] as i8, bytes[1] as i8)),
+        "CU16" if bytes.len() >= 4 => Some(format!("{} {}", u16_at(0), u16_at(2))),
+        "CS16" if bytes.len() >= 4 => Some(format!("{} {}", i16_at(0), i16_at(2))),
+        "CF32" if bytes.len() >= 8 => Some(format!("{:.4} {:.4}", f32_at(0), f32_at(4))),
+        _ => None,
+    }
+}
+
+/// Formats `bytes` as hex lines, grouped by I/Q pair, with offsets starting at `offset`.
+fn hex_lines(bytes: &[u8], offset: u64, sample_format: &str, pair_size: usize) -> Vec<String> {
+    bytes
+        .chunks(LINE_SIZE)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let start = offset + (index * LINE_SIZE) as u64;
+            let mut line = format!("{start:08x} ");
+            for (i, byte) in chunk.iter().enumerate() {
+                // an extra space between I/Q pairs
+                if (start as usize + i) % pair_size == 0 {
+                    line.push(' ');
+                }
+                line.push_str(&format!("{byte:02x}"));
+            }
+            // annotate the decoded first pair of the line if it is aligned
+            if start as usize % pair_size == 0 {
+                if let Some(pair) = decode_pair(sample_format, chunk) {
+                    line.push_str(&format!("   I/Q {pair}"));
+                }
+            }
+            line
+        })
+        .collect()
+}
+
+/// Hex view of the first and last kilobyte of the file, annotated for the `sample_format`.
+pub fn peek(path: &Path, sample_format: &str) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let pair_size = sample_pair_size(sample_format).unwrap_or(1);
+    let layout = pair_layout(sample_format);
+
+    let mut head = vec![0; PEEK_SIZE.min(size) as usize];
+    file.read_exact(&mut head)?;
+
+    let mut lines = vec![
+        format!("{sample_format}: {pair_size} byte(s) per I/Q pair, {layout}"),
+        format!("{size} bytes"),
+        String::new(),
+    ];
+    lines.extend(hex_lines(&head, 0, sample_format, pair_size));
+
+    // the tail, unless already covered by the head
+    if size > PEEK_SIZE {
+        let tail_start = (size - PEEK_SIZE).max(PEEK_SIZE);
+        // keep lines aligned to the line size
+        let tail_start = tail_start - tail_start % LINE_SIZE as u64;
+        let mut tail = vec![0; (size - tail_start) as usize];
+        file.seek(SeekFrom::Start(tail_start))?;
+        file.read_exact(&mut tail)?;
+        if tail_start > PEEK_SIZE {
+            lines.push("...".into());
+        }
+        lines.extend(hex_lines(&tail, tail_start, sample_format, pair_size));
+    }
+
+    Ok(lines.join("\n"))
+}
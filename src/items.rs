@@ -3,27 +3,239 @@
 
 //! I/Q Viewer -- Item handling.
 
-use std::fs;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io;
 //use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 use std::usize;
 
 use iced::widget::image::Handle;
 
-use crate::dirs::read_dir_iq;
-use crate::plot_ffi::Plot;
+use crate::archive::{self, is_archive};
+use crate::clipping::clipped_fraction;
+use crate::dirs::{is_iq_list, newest_files, parse_filename_meta, read_dir_iq_recursive, read_iq_list};
+use crate::options::{
+    Colormap, Eviction, FftSize, SessionItem, SessionState, SortOrder, ViewOptions, save_aliases, save_reviewed,
+};
+use crate::plot_ffi::{FileInfo, Plot, RawBitmap, SAMPLE_FORMAT};
+use crate::sigmf;
+use crate::thumbcache;
 use crate::watcher;
 
+/// Characters not allowed in file names on any of the supported platforms.
+const INVALID_NAME_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
 /// Basically a Vec<FileItem> but maintains a filter and selection.
 #[derive(Default)]
 pub struct ItemList {
     items: Vec<FileItem>,
     prev_selection: usize,
     selection: usize,
+    /// Moving past an end continues at the other end.
+    wrap_selection: bool,
     filter_map: Vec<usize>,
     filter_text: String,
+    /// Thumbnails with new file info came in since the filter was applied.
+    filter_outdated: bool,
+    sort_order: SortOrder,
+    sort_descending: bool,
+    only_marked: bool,
+    only_delete: bool,
+    /// Show only the items not reviewed yet, to resume a review.
+    only_unreviewed: bool,
     watcher: Option<watcher::FolderWatcher>,
     recent_folders: Vec<PathBuf>,
+    folders: Vec<PathBuf>,
+    deleted: Vec<(PathBuf, SystemTime)>,
+    thumbnail_config: ThumbnailConfig,
+    /// Only every Nth file of a folder is loaded, 1 loads all.
+    folder_stride: usize,
+    /// Some folder was loaded with a stride, not all files are shown.
+    is_strided: bool,
+    /// Only the N most recently modified files of a folder are kept, zero keeps all.
+    folder_newest: usize,
+    /// Levels of subfolders read with a folder, zero reads the folder only.
+    folder_depth: usize,
+    /// Display names by canonical path.
+    aliases: BTreeMap<PathBuf, String>,
+    /// Sample formats by canonical path, for files without a known I/Q extension.
+    formats: HashMap<PathBuf, &'static str>,
+    /// Canonical paths of the reviewed files, kept across runs.
+    reviewed: BTreeSet<PathBuf>,
+    stats: SessionStats,
+    opened: HashSet<PathBuf>,
+}
+
+/// Counts of the triage actions since the start or the last clear.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionStats {
+    /// Distinct files opened in the editor.
+    pub opened: usize,
+    pub marked: usize,
+    pub deleted: usize,
+    pub moved: usize,
+}
+
+/// A file that failed to move or delete, with the cause.
+#[derive(Debug)]
+pub struct FileError {
+    pub path: PathBuf,
+    pub error: io::Error,
+}
+
+impl FileError {
+    /// The cause in short, telling permission, cross-device and not-found errors apart.
+    pub fn reason(&self) -> String {
+        match self.error.kind() {
+            io::ErrorKind::PermissionDenied => "permission denied".into(),
+            io::ErrorKind::CrossesDevices => "target is on another drive".into(),
+            io::ErrorKind::NotFound => "file not found".into(),
+            _ => self.error.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for FileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = self.path.file_name().unwrap_or(self.path.as_os_str());
+        write!(f, "{}: {}", name.to_string_lossy(), self.reason())
+    }
+}
+
+/// How thumbnails are rendered.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct ThumbnailConfig {
+    pub supersample: u32,
+    pub colormap: Colormap,
+    pub fftn: FftSize,
+    /// Give up on a file if rendering takes longer, zero waits forever.
+    pub timeout: Duration,
+}
+
+impl ThumbnailConfig {
+    /// Tag of the options that change the rendered image, for the cache key.
+    fn appearance(&self) -> String {
+        format!("{}x|{}|{}", self.supersample, self.colormap, self.fftn)
+    }
+
+    /// Renders a thumbnail of the file with these options, as `format` if given.
+    pub fn render(&self, path: &Path, format: Option<&'static str>) -> (RawBitmap, FileInfo) {
+        Plot::thumbnail(
+            path,
+            format,
+            self.supersample,
+            self.colormap.to_value() as u32,
+            self.fftn.to_value() as u32,
+        )
+    }
+}
+
+/// Progress of the thumbnail of an item, rendered in the background.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ThumbnailState {
+    Pending,
+    /// Pending after the file changed, rendered without the disk cache.
+    Outdated,
+    /// Taken to render in the background at the instant, see `ItemList::expire_renders()`.
+    Rendering(Instant),
+    Done,
+}
+
+/// A thumbnail to render in the background, of the file as it was when taken.
+pub struct PendingThumbnail {
+    path: PathBuf,
+    format: Option<&'static str>,
+    size: Option<u64>,
+    mtime: Option<SystemTime>,
+    config: ThumbnailConfig,
+    archive_entry: Option<archive::Entry>,
+    /// Render again even if the disk cache has the file.
+    reload: bool,
+}
+
+impl PendingThumbnail {
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    /// Loads the thumbnail from the disk cache or renders it, blocks until done.
+    /// An archive entry is extracted first.
+    pub fn render(&self) -> Thumbnail {
+        // nothing to render in an empty file
+        if self.size == Some(0) {
+            return Thumbnail::placeholder(RenderOutcome::Empty);
+        }
+        let extracted = match &self.archive_entry {
+            Some(entry) => entry.extract().map_err(|err| println!("Archive error {err:?}")).is_ok(),
+            None => true,
+        };
+        let rendered = extracted.then(|| {
+            cached_thumbnail(
+                &self.path,
+                self.format,
+                self.size,
+                self.mtime,
+                self.config,
+                self.reload,
+            )
+        });
+        let Some((bitmap, file_info, clipped)) = rendered else {
+            return Thumbnail::placeholder(RenderOutcome::Failed);
+        };
+        let outcome = match file_info.sample_count {
+            0 => RenderOutcome::Empty,
+            _ => RenderOutcome::Rendered,
+        };
+        let handle = Handle::from_rgba(bitmap.width as u32, bitmap.height as u32, bitmap.pixels);
+        Thumbnail {
+            handle,
+            file_info,
+            outcome,
+            clipped,
+        }
+    }
+}
+
+/// How rendering the thumbnail of an item ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RenderOutcome {
+    Rendered,
+    /// The file holds no complete sample, nothing to render.
+    Empty,
+    /// Rendering timed out or the file can't be decoded.
+    Failed,
+}
+
+/// A thumbnail rendered in the background, with the file info read along.
+#[derive(Debug, Clone)]
+pub struct Thumbnail {
+    handle: Handle,
+    file_info: FileInfo,
+    outcome: RenderOutcome,
+    /// Fraction of clipped samples, checked along with the render.
+    clipped: Option<f32>,
+}
+
+impl Thumbnail {
+    /// The placeholder for a file that failed to render, e.g. when the render thread panicked.
+    pub fn failed() -> Self {
+        Self::placeholder(RenderOutcome::Failed)
+    }
+
+    /// The placeholder for a file that rendered nothing.
+    fn placeholder(outcome: RenderOutcome) -> Self {
+        let (bitmap, file_info) = failed_thumbnail();
+        let handle = Handle::from_rgba(bitmap.width as u32, bitmap.height as u32, bitmap.pixels);
+        Self {
+            handle,
+            file_info,
+            outcome,
+            clipped: None,
+        }
+    }
 }
 
 impl ItemList {
@@ -39,12 +251,207 @@ impl ItemList {
         self.items.len()
     }
 
+    /// Sets how thumbnails of newly loaded items are rendered.
+    pub fn set_thumbnail_config(&mut self, config: ThumbnailConfig) {
+        self.thumbnail_config = config;
+    }
+
+    /// Sets the display names by canonical path, applies to newly loaded items.
+    pub fn set_aliases(&mut self, aliases: BTreeMap<PathBuf, String>) {
+        self.aliases = aliases;
+    }
+
+    /// Opens the file of the `path` as `format` from now on, e.g. for a file without a known extension.
+    /// Items already loaded are rendered again.
+    pub fn set_format(&mut self, path: &Path, format: &'static str) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        for item in self.items.iter_mut().filter(|item| item.path == path) {
+            item.format = Some(format);
+            item.thumbnail_state = ThumbnailState::Outdated;
+        }
+        self.formats.insert(path, format);
+    }
+
+    /// The sample format set for the file of the `path`, `None` if detected from the extension.
+    pub fn format_of(&self, path: &Path) -> Option<&'static str> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.formats.get(&path).copied()
+    }
+
+    /// Sets the canonical paths of the reviewed files, applies to newly loaded items.
+    pub fn set_reviewed_paths(&mut self, reviewed: BTreeSet<PathBuf>) {
+        self.reviewed = reviewed;
+    }
+
+    /// Sets the step between files loaded from folders, 1 loads all.
+    pub fn set_folder_stride(&mut self, stride: usize) {
+        self.folder_stride = stride.max(1);
+    }
+
+    /// Sets the levels of subfolders read with a folder, zero reads the folder only.
+    /// The loaded folders are watched again, with their subfolders if those are read.
+    /// Call `rescan()` to load or drop the files of the subfolders.
+    pub fn set_folder_depth(&mut self, depth: usize) {
+        let recursive = depth > 0;
+        let rewatch = recursive != (self.folder_depth > 0);
+        self.folder_depth = depth;
+        if let (true, Some(watcher)) = (rewatch, self.watcher.as_mut()) {
+            watcher.unwatch_all();
+            for folder in &self.folders {
+                watcher.watch(folder.clone(), recursive);
+            }
+        }
+    }
+
+    /// Reads the I/Q files of a `folder`, and of its subfolders to the folder depth.
+    fn read_folder(&self, folder: &Path) -> io::Result<Vec<PathBuf>> {
+        read_dir_iq_recursive(folder, self.folder_depth)
+    }
+
+    /// Tests if the `path` is in the `folder`, or in one of its subfolders down to the folder depth.
+    fn is_in_folder(&self, path: &Path, folder: &Path) -> bool {
+        path.parent()
+            .and_then(|dir| dir.strip_prefix(folder).ok())
+            .is_some_and(|sub| sub.components().count() <= self.folder_depth)
+    }
+
+    /// Sets the number of most recently modified files kept per folder, zero keeps all.
+    pub fn set_folder_newest(&mut self, count: usize) {
+        self.folder_newest = count;
+        self.trim_folders();
+        self.apply_filter();
+    }
+
+    /// Tests if folders were loaded with a stride, i.e. `load_all()` would add files.
+    pub fn is_strided(&self) -> bool {
+        self.is_strided
+    }
+
+    /// Loads the files skipped by the folder stride.
+    pub fn load_all(&mut self) {
+        let mut found = vec![];
+        for folder in &self.folders {
+            match self.read_folder(folder) {
+                Ok(files) => {
+                    let files = newest_files(files, self.folder_newest);
+                    found.extend(files.iter().filter_map(|path| path.canonicalize().ok()));
+                }
+                Err(err) => {
+                    println!("Read error {err:?}");
+                }
+            }
+        }
+        for path in found {
+            if !self.contains(&path) {
+                self.items.push(self.new_item(path));
+            }
+        }
+        self.is_strided = false;
+        self.apply_filter();
+    }
+
+    /// Tests if thumbnails wait to be rendered.
+    pub fn has_pending(&self) -> bool {
+        self.items
+            .iter()
+            .any(|item| matches!(item.thumbnail_state, ThumbnailState::Pending | ThumbnailState::Outdated))
+    }
+
+    /// Takes up to `count` thumbnails to render, shown items first.
+    pub fn take_pending(&mut self, count: usize) -> Vec<PendingThumbnail> {
+        let order: Vec<usize> = self.filter_map.iter().copied().chain(0..self.items.len()).collect();
+        let mut pending = vec![];
+        for index in order {
+            if pending.len() >= count {
+                break;
+            }
+            let item = &mut self.items[index];
+            if matches!(item.thumbnail_state, ThumbnailState::Pending | ThumbnailState::Outdated) {
+                pending.push(PendingThumbnail {
+                    path: item.path.clone(),
+                    format: item.format,
+                    size: item.size,
+                    mtime: item.mtime,
+                    config: item.thumbnail_config,
+                    archive_entry: item.archive_entry.clone(),
+                    reload: item.thumbnail_state == ThumbnailState::Outdated,
+                });
+                item.thumbnail_state = ThumbnailState::Rendering(Instant::now());
+            }
+        }
+        pending
+    }
+
+    /// Sets a thumbnail rendered in the background on the items of the `path`.
+    /// Results for items refreshed or reloaded since, or given up on, are dropped.
+    pub fn set_thumbnail(&mut self, path: &Path, thumbnail: Thumbnail) {
+        let uses_file_info = self.filter_uses_file_info();
+        for item in self.items.iter_mut() {
+            if item.path == path && matches!(item.thumbnail_state, ThumbnailState::Rendering(_)) {
+                item.set_thumbnail(thumbnail.clone());
+                self.filter_outdated |= uses_file_info;
+            }
+        }
+        self.apply_outdated_filter();
+    }
+
+    /// Gives up on thumbnails rendering longer than the timeout of their config, shows them as failed.
+    /// A stuck FFI call can't be cancelled, a late result is dropped by `set_thumbnail()`.
+    pub fn expire_renders(&mut self) {
+        let uses_file_info = self.filter_uses_file_info();
+        for item in self.items.iter_mut() {
+            let ThumbnailState::Rendering(start) = item.thumbnail_state else {
+                continue;
+            };
+            let timeout = item.thumbnail_config.timeout;
+            if !timeout.is_zero() && start.elapsed() >= timeout {
+                println!("Thumbnail timeout, skipping {:?}", item.path);
+                item.set_thumbnail(Thumbnail::failed());
+                self.filter_outdated |= uses_file_info;
+            }
+        }
+        self.apply_outdated_filter();
+    }
+
+    /// Tests if the order or the filter depend on the file info read along with the thumbnails.
+    fn filter_uses_file_info(&self) -> bool {
+        let sorted_by_info = matches!(
+            self.sort_order,
+            SortOrder::SampleCount | SortOrder::SampleRate | SortOrder::Frequency | SortOrder::BandScan
+        );
+        // the filter text matches the sample format, frequency and rate
+        sorted_by_info || !self.filter_text.trim().is_empty()
+    }
+
+    /// Applies the filter once for the thumbnails rendered in a batch, when none is left to render.
+    /// Sorting as each thumbnail comes in would move the items while scrolling.
+    fn apply_outdated_filter(&mut self) {
+        let unfinished = self.items.iter().any(|item| item.thumbnail_state != ThumbnailState::Done);
+        if self.filter_outdated && !unfinished {
+            self.apply_filter();
+        }
+    }
+
+    fn new_item(&self, path: PathBuf) -> FileItem {
+        let format = self.formats.get(&path).copied();
+        let mut item = FileItem::new(path, format, self.thumbnail_config);
+        if let Some(alias) = self.aliases.get(&item.path) {
+            item.set_alias(Some(alias.clone()));
+        }
+        item.has_reviewed = self.reviewed.contains(&item.path);
+        item
+    }
+
     pub fn clear(&mut self) {
         self.items.clear();
         // also validates selection
         self.set_filter("");
         // unwatch all if we have a watcher, nothing to do otherwise
         self.recent_folders.drain(..);
+        self.folders.clear();
+        self.is_strided = false;
+        self.stats = SessionStats::default();
+        self.opened.clear();
         self.watcher.as_mut().map(|w| w.unwatch_all());
     }
 
@@ -76,24 +483,72 @@ impl ItemList {
     }
 
     pub fn push(&mut self, path: PathBuf) {
-        if path.is_file() {
-            self.items.push(FileItem::new(
-                path.canonicalize().expect("Canonicalize path"),
-            ));
+        if is_iq_list(&path) {
+            match read_iq_list(&path) {
+                Ok(entries) => {
+                    let collection = sigmf::is_collection(&path).then(|| path.clone());
+                    for (path, options) in entries {
+                        // report missing files but keep loading
+                        if let Ok(path) = path.canonicalize() {
+                            // a member's `.sigmf-data` is opened with the format from its metadata
+                            if let Some(format) = sigmf::data_format(&path) {
+                                self.formats.entry(path.clone()).or_insert(format);
+                            }
+                            let mut item = self.new_item(path);
+                            item.options = options;
+                            item.collection = collection.clone();
+                            self.items.push(item);
+                        } else {
+                            println!("List entry not found {path:?}");
+                        }
+                    }
+                }
+                Err(err) => {
+                    println!("Read error {err:?}");
+                }
+            }
+        } else if is_archive(&path) {
+            match archive::list_iq(&path) {
+                Ok(entries) => {
+                    // extracted on demand, see `FileItem::extract()`
+                    for entry in entries {
+                        let mut item = self.new_item(entry.path().to_path_buf());
+                        item.read_error = None;
+                        item.archive_entry = Some(entry);
+                        self.items.push(item);
+                    }
+                }
+                Err(err) => {
+                    println!("Archive error {err:?}");
+                }
+            }
+        } else if path.is_file() {
+            self.items.push(self.new_item(path.canonicalize().expect("Canonicalize path")));
         } else {
-            match read_dir_iq(&path) {
+            match self.read_folder(&path) {
                 Ok(files) => {
-                    for path in files {
-                        self.items.push(FileItem::new(
-                            path.canonicalize().expect("Canonicalize path"),
-                        ));
+                    let files = newest_files(files, self.folder_newest);
+                    let stride = self.folder_stride.max(1);
+                    self.is_strided |= stride > 1 && files.len() > 1;
+                    for path in files.into_iter().step_by(stride) {
+                        let path = path.canonicalize().expect("Canonicalize path");
+                        // the same folder given twice merges into the gallery
+                        if !self.contains(&path) {
+                            self.items.push(self.new_item(path));
+                        }
+                    }
+
+                    // remember folders for rescans
+                    let path = path.canonicalize().unwrap_or(path);
+                    if !self.folders.contains(&path) {
+                        self.folders.push(path.clone());
                     }
 
                     // stash recent folders and try to apply
                     self.recent_folders.push(path);
                     if let Some(watcher) = self.watcher.as_mut() {
                         for path in self.recent_folders.drain(..) {
-                            watcher.watch(path);
+                            watcher.watch(path, self.folder_depth > 0);
                         }
                     }
                 }
@@ -102,6 +557,104 @@ impl ItemList {
                 }
             }
         }
+        self.trim_folders();
+        self.apply_filter();
+        thumbcache::evict();
+    }
+
+    /// Rescan all loaded folders, adds new files, refreshes changed and drops vanished ones.
+    pub fn rescan(&mut self) {
+        let mut found = vec![];
+        for folder in &self.folders {
+            match self.read_folder(folder) {
+                Ok(files) => {
+                    // keep to the stride, unless all files were loaded
+                    let stride = if self.is_strided { self.folder_stride.max(1) } else { 1 };
+                    let files = newest_files(files, self.folder_newest);
+                    found.extend(files.iter().step_by(stride).filter_map(|path| path.canonicalize().ok()));
+                }
+                Err(err) => {
+                    println!("Read error {err:?}");
+                }
+            }
+        }
+
+        // files of subfolders beyond the folder depth are dropped too, unless flagged
+        let vanished: Vec<bool> = self
+            .items
+            .iter()
+            .map(|item| {
+                let in_folder = self.folders.iter().any(|folder| self.is_in_folder(&item.path, folder));
+                let below_depth = !in_folder
+                    && !item.has_mark
+                    && !item.has_delete
+                    && self.folders.iter().any(|folder| item.path.starts_with(folder));
+                (!item.path.exists() && in_folder) || below_depth
+            })
+            .collect();
+        let mut vanished = vanished.into_iter();
+        self.items.retain(|_| !vanished.next().unwrap_or(false));
+
+        for path in found {
+            match self.items.iter_mut().find(|item| item.path == path) {
+                Some(item) => {
+                    if item.is_modified() {
+                        item.refresh();
+                    }
+                }
+                None => self.items.push(self.new_item(path)),
+            }
+        }
+        self.trim_folders();
+        self.apply_filter();
+    }
+
+    /// Drops the items of loaded folders beyond the most recently modified `folder_newest`,
+    /// items marked to move or delete are not counted and kept.
+    fn trim_folders(&mut self) {
+        if self.folder_newest == 0 {
+            return;
+        }
+        let mut keep = vec![true; self.items.len()];
+        for folder in &self.folders {
+            let mut order: Vec<usize> = (0..self.items.len())
+                .filter(|&index| !self.items[index].has_mark && !self.items[index].has_delete)
+                .filter(|&index| self.is_in_folder(&self.items[index].path, folder))
+                .collect();
+            // newest first
+            order.sort_by(|&a, &b| self.items[b].mtime.cmp(&self.items[a].mtime));
+            for &index in order.iter().skip(self.folder_newest) {
+                keep[index] = false;
+            }
+        }
+        let mut keep = keep.into_iter();
+        self.items.retain(|_| keep.next().unwrap_or(true));
+    }
+
+    /// Drops items beyond `max_items` (zero is unlimited) according to the eviction `policy`.
+    /// Drops items past `max_items` in the `policy` order. Marked, delete-flagged and selected
+    /// items and the file `open` in the editor are pinned and never dropped.
+    pub fn evict(&mut self, max_items: usize, policy: Eviction, open: Option<&Path>) {
+        if max_items == 0 || self.items.len() <= max_items {
+            return;
+        }
+        let count = self.items.len() - max_items;
+        let selected = self.filter_map.get(self.selection).copied();
+        let mut order: Vec<usize> = (0..self.items.len())
+            .filter(|&index| !self.items[index].has_mark && !self.items[index].has_delete)
+            .filter(|&index| Some(index) != selected)
+            .filter(|&index| Some(self.items[index].path.as_path()) != open)
+            .collect();
+        if policy == Eviction::OldestModified {
+            order.sort_by_key(|&index| self.items[index].mtime);
+        }
+        let mut keep = vec![true; self.items.len()];
+        for &index in order.iter().take(count) {
+            keep[index] = false;
+        }
+        let mut keep = keep.into_iter();
+        self.items.retain(|_| keep.next().unwrap_or(true));
+        // also validates selection
         self.apply_filter();
     }
 
@@ -121,6 +674,16 @@ impl ItemList {
         self.apply_filter();
     }
 
+    /// Tests if the path is in the list, regardless of the filter.
+    pub fn contains(&self, path: &Path) -> bool {
+        self.items.iter().any(|item| item.path == path)
+    }
+
+    /// Finds an item by path, regardless of the filter.
+    pub fn find(&self, path: &Path) -> Option<&FileItem> {
+        self.items.iter().find(|item| item.path == path)
+    }
+
     pub fn get(&self, index: usize) -> Option<&FileItem> {
         self.filter_map.get(index).and_then(|&i| self.items.get(i))
     }
@@ -134,16 +697,55 @@ impl ItemList {
         self.apply_filter();
     }
 
+    pub fn only_marked(&self) -> bool {
+        self.only_marked
+    }
+
+    pub fn only_delete(&self) -> bool {
+        self.only_delete
+    }
+
+    /// Toggle showing only items marked for move.
+    pub fn toggle_only_marked(&mut self) {
+        self.only_marked = !self.only_marked;
+        self.apply_filter();
+    }
+
+    /// Toggle showing only items marked for delete.
+    pub fn toggle_only_delete(&mut self) {
+        self.only_delete = !self.only_delete;
+        self.apply_filter();
+    }
+
+    pub fn only_unreviewed(&self) -> bool {
+        self.only_unreviewed
+    }
+
+    /// Toggle showing only items not reviewed yet.
+    pub fn toggle_only_unreviewed(&mut self) {
+        self.only_unreviewed = !self.only_unreviewed;
+        self.apply_filter();
+    }
+
     pub fn count_watches(&self) -> usize {
         self.watcher.as_ref().map(|w| w.len()).unwrap_or_default()
     }
 
+    /// Folders currently watched for new files.
+    pub fn watches(&self) -> &[PathBuf] {
+        self.watcher.as_ref().map(|w| w.as_slice()).unwrap_or_default()
+    }
+
     pub fn count_marked(&self) -> usize {
         self.items
             .iter()
             .fold(0, |acc, t| if t.has_mark { acc + 1 } else { acc })
     }
 
+    pub fn count_reviewed(&self) -> usize {
+        self.items.iter().filter(|item| item.has_reviewed).count()
+    }
+
     pub fn count_to_delete(&self) -> usize {
         self.items
             .iter()
@@ -158,17 +760,102 @@ impl ItemList {
         self.selection
     }
 
+    /// All items with their flags and format, regardless of the filter, for the session recovery.
+    pub fn session_state(&self) -> SessionState {
+        let items = self.items.iter().map(|item| {
+            let (path, entry) = match &item.archive_entry {
+                Some(entry) => (entry.archive().to_path_buf(), Some(entry.name().to_path_buf())),
+                None => (item.path.clone(), None),
+            };
+            SessionItem {
+                path,
+                entry,
+                format: self.formats.get(&item.path).map(|format| format.to_string()),
+                mark: item.has_mark,
+                delete: item.has_delete,
+            }
+        });
+        SessionState {
+            items: items.collect(),
+            selection: self.selection,
+        }
+    }
+
+    /// Reloads the items of a recovered session, files and archive members gone since are skipped.
+    pub fn restore_session(&mut self, session: SessionState) {
+        // archives are listed once for all of their members
+        let mut listed: HashMap<PathBuf, Vec<archive::Entry>> = HashMap::new();
+        for session_item in session.items {
+            if !session_item.path.is_file() {
+                continue;
+            }
+            let entry = match &session_item.entry {
+                Some(name) => {
+                    let entries = listed.entry(session_item.path.clone()).or_insert_with(|| {
+                        archive::list_iq(&session_item.path).unwrap_or_else(|err| {
+                            println!("Archive error {err:?}");
+                            vec![]
+                        })
+                    });
+                    match entries.iter().find(|entry| entry.name() == name) {
+                        Some(entry) => Some(entry.clone()),
+                        None => continue,
+                    }
+                }
+                None => None,
+            };
+            let path = entry.as_ref().map_or(session_item.path, |entry| entry.path().to_path_buf());
+            if self.contains(&path) {
+                continue;
+            }
+            let format = session_item.format.as_deref();
+            if let Some(format) = SAMPLE_FORMAT.iter().copied().find(|known| Some(*known) == format) {
+                self.formats.insert(path.clone(), format);
+            }
+            let mut item = self.new_item(path);
+            if let Some(entry) = entry {
+                item.read_error = None;
+                item.archive_entry = Some(entry);
+            }
+            item.has_mark = session_item.mark;
+            item.has_delete = session_item.delete;
+            self.items.push(item);
+        }
+        self.apply_filter();
+        self.set_selection(session.selection);
+    }
+
     pub fn set_selection(&mut self, index: usize) {
         self.selection = index.min(self.len().saturating_sub(1));
         self.prev_selection = self.selection;
     }
 
+    /// Orders the shown items by the `sort_order`, reversed if `descending`, keeps the selected item.
+    pub fn set_sort(&mut self, sort_order: SortOrder, descending: bool) {
+        self.sort_order = sort_order;
+        self.sort_descending = descending;
+        self.apply_filter();
+    }
+
+    pub fn set_wrap_selection(&mut self, wrap: bool) {
+        self.wrap_selection = wrap;
+    }
+
     pub fn inc_selection(&mut self, offset: usize) {
-        self.set_selection(self.selection.saturating_add(offset));
+        if self.wrap_selection && !self.is_empty() {
+            self.set_selection((self.selection + offset % self.len()) % self.len());
+        } else {
+            self.set_selection(self.selection.saturating_add(offset));
+        }
     }
 
     pub fn dec_selection(&mut self, offset: usize) {
-        self.set_selection(self.selection.saturating_sub(offset));
+        if self.wrap_selection && !self.is_empty() {
+            let len = self.len();
+            self.set_selection((self.selection + len - offset % len) % len);
+        } else {
+            self.set_selection(self.selection.saturating_sub(offset));
+        }
     }
 
     pub fn selected_remove(&mut self) {
@@ -180,12 +867,129 @@ impl ItemList {
         }
     }
 
+    /// Counts the file as opened, once per session.
+    pub fn note_opened(&mut self, path: &Path) {
+        if self.opened.insert(path.to_path_buf()) {
+            self.stats.opened += 1;
+        }
+    }
+
+    pub fn session_stats(&self) -> SessionStats {
+        self.stats
+    }
+
     pub fn selected_toggle_mark(&mut self) {
-        self.selected_mut().map(FileItem::toggle_mark);
+        match self.selected_mut().map(|item| {
+            item.toggle_mark();
+            item.has_mark
+        }) {
+            Some(true) => self.stats.marked += 1,
+            Some(false) => self.stats.marked = self.stats.marked.saturating_sub(1),
+            None => {}
+        }
+        if self.only_marked {
+            self.apply_filter();
+        }
     }
 
     pub fn selected_toggle_delete(&mut self) {
         self.selected_mut().map(FileItem::toggle_delete);
+        if self.only_delete {
+            self.apply_filter();
+        }
+    }
+
+    /// Marks the items of the `path` as reviewed, they stay shown until the filter is applied again.
+    pub fn set_reviewed(&mut self, path: &Path) {
+        for item in self.items.iter_mut().filter(|item| item.path == path) {
+            item.has_reviewed = true;
+        }
+        if self.reviewed.insert(path.to_path_buf()) {
+            save_reviewed(&self.reviewed);
+        }
+    }
+
+    pub fn selected_toggle_reviewed(&mut self) {
+        let Some(item) = self.selected_mut() else {
+            return;
+        };
+        item.toggle_reviewed();
+        let (path, reviewed) = (item.path.clone(), item.has_reviewed);
+        if reviewed {
+            self.reviewed.insert(path);
+        } else {
+            self.reviewed.remove(&path);
+        }
+        save_reviewed(&self.reviewed);
+        if self.only_unreviewed {
+            self.apply_filter();
+        }
+    }
+
+    /// Renames the selected file within its folder, returns the new canonical path.
+    pub fn selected_rename(&mut self, name: &str) -> Result<PathBuf, String> {
+        let name = name.trim();
+        if name.is_empty() || name == "." || name == ".." {
+            return Err("Name is empty".into());
+        }
+        if let Some(c) = name.chars().find(|c| INVALID_NAME_CHARS.contains(c) || c.is_control()) {
+            return Err(format!("Name contains invalid character {c:?}"));
+        }
+        let Some(item) = self.selected_mut() else {
+            return Err("No file selected".into());
+        };
+        let Some(dir) = item.path.parent() else {
+            return Err("File has no folder".into());
+        };
+        let dst = dir.join(name);
+        if dst == item.path {
+            return Ok(dst);
+        }
+        if dst.exists() {
+            return Err(format!("\"{name}\" already exists"));
+        }
+        if let Err(err) = fs::rename(&item.path, &dst) {
+            println!("File rename error: {:?}", err);
+            return Err(err.to_string());
+        }
+        let old_path = item.path.clone();
+        item.rename_to(dst.canonicalize().unwrap_or(dst));
+        let path = item.path.clone();
+        // the alias follows the file
+        if let Some(alias) = self.aliases.remove(&old_path) {
+            self.aliases.insert(path.clone(), alias);
+            save_aliases(&self.aliases);
+        }
+        // and the format override
+        if let Some(format) = self.formats.remove(&old_path) {
+            self.formats.insert(path.clone(), format);
+        }
+        // so does the reviewed flag
+        if self.reviewed.remove(&old_path) {
+            self.reviewed.insert(path.clone());
+            save_reviewed(&self.reviewed);
+        }
+        // the filter matches on the path
+        self.apply_filter();
+        Ok(path)
+    }
+
+    /// Sets or clears (if empty) the display name of the selected file, persisted across sessions.
+    pub fn selected_set_alias(&mut self, alias: &str) {
+        let alias = alias.trim();
+        let alias = (!alias.is_empty()).then(|| alias.to_string());
+        let Some(item) = self.selected_mut() else {
+            return;
+        };
+        item.set_alias(alias.clone());
+        let path = item.path.clone();
+        match alias {
+            Some(alias) => self.aliases.insert(path, alias),
+            None => self.aliases.remove(&path),
+        };
+        save_aliases(&self.aliases);
+        // the filter also matches on the alias
+        self.apply_filter();
     }
 
     pub fn selected(&self) -> Option<&FileItem> {
@@ -202,6 +1006,7 @@ impl ItemList {
 
     /// Rebuild filter_map, try to maintain the selection.
     fn apply_filter(&mut self) {
+        self.filter_outdated = false;
         let filter = self.filter_text.split_ascii_whitespace();
         // get previous selection index
         let prev_index = self.filter_map.get(self.prev_selection).copied().unwrap_or_default();
@@ -210,7 +1015,11 @@ impl ItemList {
         self.filter_map.clear();
         for (i, item) in self.items.iter().enumerate() {
             // test filter condition
-            if item.matches_all(filter.clone()) {
+            if item.matches_all(filter.clone())
+                && (!self.only_marked || item.has_mark)
+                && (!self.only_delete || item.has_delete)
+                && (!self.only_unreviewed || !item.has_reviewed)
+            {
                 self.filter_map.push(i);
                 // move selection along until we reach the previous index
                 if i < prev_index {
@@ -218,10 +1027,46 @@ impl ItemList {
                 }
             }
         }
+        let items = &self.items;
+        let descending = self.sort_descending;
+        let known = |value: f64| Some(value).filter(|v| *v > 0.0 && v.is_finite());
+        match self.sort_order {
+            SortOrder::Loaded if descending => self.filter_map.reverse(),
+            SortOrder::Loaded => {}
+            SortOrder::Name => self.filter_map.sort_by(|&a, &b| {
+                let name = |i: usize| items[i].path.file_name();
+                cmp_known(name(a), name(b), Ord::cmp, descending)
+            }),
+            SortOrder::Size => self
+                .filter_map
+                .sort_by(|&a, &b| cmp_known(items[a].size, items[b].size, Ord::cmp, descending)),
+            SortOrder::Modified => self
+                .filter_map
+                .sort_by(|&a, &b| cmp_known(items[a].mtime, items[b].mtime, Ord::cmp, descending)),
+            SortOrder::SampleCount => self.filter_map.sort_by(|&a, &b| {
+                let count = |i: usize| Some(items[i].sample_count).filter(|&count| count > 0);
+                cmp_known(count(a), count(b), Ord::cmp, descending)
+            }),
+            SortOrder::SampleRate => self.filter_map.sort_by(|&a, &b| {
+                let rate = |i: usize| known(items[i].sample_rate);
+                cmp_known(rate(a), rate(b), f64::total_cmp, descending)
+            }),
+            SortOrder::Frequency | SortOrder::BandScan => self.filter_map.sort_by(|&a, &b| {
+                let freq = |i: usize| known(items[i].center_freq);
+                cmp_known(freq(a), freq(b), f64::total_cmp, descending)
+            }),
+        }
+        // keep the previously selected item if still shown
+        if self.sort_order != SortOrder::Loaded || descending {
+            selection = self.filter_map.iter().position(|&i| i == prev_index).unwrap_or_default();
+        }
         self.set_selection(selection);
     }
 
-    pub fn move_marked_to(&mut self, dst: PathBuf) {
+    /// Moves the marked files to the `dst` folder, returns the files that failed.
+    pub fn move_marked_to(&mut self, dst: PathBuf) -> Vec<FileError> {
+        let mut moved = 0;
+        let mut errors = Vec::new();
         self.items.retain(|item| {
             if item.has_mark {
                 // NOTE: only works if the rename points to the same drive, otherwise needs fs::copy and fs::remove_file.
@@ -229,34 +1074,53 @@ impl ItemList {
                     let dst_file = dst.join(filename);
                     if let Err(err) = fs::rename(&item, &dst_file) {
                         println!("File move error: {:?}", err);
+                        errors.push(FileError { path: item.path.clone(), error: err });
                         true // errored thus retain
                     } else {
+                        moved += 1;
                         false // remove
                     }
                 } else {
+                    let error = io::ErrorKind::InvalidInput.into();
+                    errors.push(FileError { path: item.path.clone(), error });
                     true // errored thus retain
                 }
             } else {
                 true // retain
             }
         });
+        self.stats.moved += moved;
         self.apply_filter();
+        errors
     }
 
-    pub fn delete_marked(&mut self) {
+    /// Deletes the files flagged for deletion, returns the files that failed.
+    pub fn delete_marked(&mut self) -> Vec<FileError> {
+        let count = self.deleted.len();
+        let deleted = &mut self.deleted;
+        let mut errors = Vec::new();
         self.items.retain(|item| {
             if item.has_delete {
                 if let Err(err) = fs::remove_file(&item) {
                     println!("File delete error: {:?}", err);
+                    errors.push(FileError { path: item.path.clone(), error: err });
                     true // errored thus retain
                 } else {
+                    deleted.push((item.path.clone(), SystemTime::now()));
                     false // remove
                 }
             } else {
                 true // retain
             }
         });
+        self.stats.deleted += self.deleted.len() - count;
         self.apply_filter();
+        errors
+    }
+
+    /// Files deleted in this session with the time of deletion.
+    pub fn deleted(&self) -> &[(PathBuf, SystemTime)] {
+        &self.deleted
     }
 
     pub fn watcher_event(&mut self, event: watcher::WatcherEvent) {
@@ -267,7 +1131,7 @@ impl ItemList {
                 // apply recent folders, likely from startup args
                 if let Some(watcher) = self.watcher.as_mut() {
                     for path in self.recent_folders.drain(..) {
-                        watcher.watch(path);
+                        watcher.watch(path, self.folder_depth > 0);
                     }
                 }
             }
@@ -281,6 +1145,15 @@ impl ItemList {
             }
 
             watcher::WatcherEvent::Create(paths) => {
+                // skip files we already know of, e.g. after a rename, and files beyond the folder depth
+                let paths: Vec<_> = paths
+                    .into_iter()
+                    .filter(|path| !path.canonicalize().is_ok_and(|path| self.contains(&path)))
+                    .filter(|path| {
+                        let path = path.canonicalize().unwrap_or_else(|_| path.clone());
+                        self.folders.iter().any(|folder| self.is_in_folder(&path, folder))
+                    })
+                    .collect();
                 self.extend(paths);
             }
 
@@ -297,7 +1170,10 @@ impl ItemList {
 
 pub struct FileItem {
     path: PathBuf,
+    /// Sample format to open the file as, `None` detects it from the extension.
+    format: Option<&'static str>,
     size: Option<u64>,
+    mtime: Option<SystemTime>,
     sample_format: &'static str,
     sample_count: u64,
     center_freq: f64,
@@ -305,7 +1181,22 @@ pub struct FileItem {
     handle: Handle,
     has_mark: bool,
     has_delete: bool,
+    /// Inspected in a review, apart from the move and delete marks.
+    has_reviewed: bool,
     metadata: String,
+    options: ViewOptions,
+    thumbnail_config: ThumbnailConfig,
+    outcome: RenderOutcome,
+    thumbnail_state: ThumbnailState,
+    alias: Option<String>,
+    /// Fraction of clipped samples, checked along with the thumbnail render.
+    clipped: Option<f32>,
+    /// The SigMF collection the item was loaded from.
+    collection: Option<PathBuf>,
+    /// The archive entry the file is extracted from, on demand.
+    archive_entry: Option<archive::Entry>,
+    /// Why the file can't be opened for reading, e.g. missing permissions.
+    read_error: Option<io::ErrorKind>,
 }
 
 impl AsRef<Path> for FileItem {
@@ -315,21 +1206,35 @@ impl AsRef<Path> for FileItem {
 }
 
 impl FileItem {
-    pub fn new(path: PathBuf) -> Self {
-        let size = if let Ok(metadata) = fs::metadata(&path) {
-            Some(metadata.len())
+    pub fn new(path: PathBuf, format: Option<&'static str>, thumbnail_config: ThumbnailConfig) -> Self {
+        let (size, mtime) = if let Ok(metadata) = fs::metadata(&path) {
+            (Some(metadata.len()), metadata.modified().ok())
         } else {
-            None
+            (None, None)
         };
 
-        let (bitmap, file_info) = Plot::thumbnail(&path);
+        // a cache miss is rendered in the background, see `ItemList::take_pending()`
+        let cached = cache_key(&path, format, size, mtime, thumbnail_config).and_then(|key| thumbcache::load(&key));
+        let (bitmap, file_info, clipped, thumbnail_state) = match cached {
+            Some((bitmap, file_info, clipped)) => (bitmap, file_info, clipped, ThumbnailState::Done),
+            None => {
+                let (bitmap, file_info) = pending_thumbnail();
+                (bitmap, file_info, None, ThumbnailState::Pending)
+            }
+        };
+        let outcome = match thumbnail_state {
+            ThumbnailState::Done if file_info.sample_count == 0 => RenderOutcome::Empty,
+            _ => RenderOutcome::Rendered,
+        };
+        let file_info = with_filename_meta(file_info, &path);
         let handle = Handle::from_rgba(bitmap.width as u32, bitmap.height as u32, bitmap.pixels);
+        let read_error = read_error(&path);
 
-        let metadata = format!("{} {} {:.0}M {:.0}k", path.to_string_lossy(), file_info.sample_format, file_info.center_freq / 1_000_000.0, file_info.sample_rate / 1_000.0).to_ascii_lowercase();
-
-        Self {
+        let mut item = Self {
             path,
+            format,
             size,
+            mtime,
             sample_format: file_info.sample_format,
             sample_count: file_info.sample_count,
             center_freq: file_info.center_freq,
@@ -337,32 +1242,92 @@ impl FileItem {
             handle,
             has_mark: false,
             has_delete: false,
-            metadata,
-        }
+            has_reviewed: false,
+            metadata: String::new(),
+            options: ViewOptions::default(),
+            thumbnail_config,
+            outcome,
+            thumbnail_state,
+            alias: None,
+            clipped,
+            collection: None,
+            archive_entry: None,
+            read_error,
+        };
+        item.update_metadata();
+        item
     }
 
     pub fn refresh(&mut self) {
-        self.size = if let Ok(metadata) = fs::metadata(&self.path) {
-            Some(metadata.len())
+        (self.size, self.mtime) = if let Ok(metadata) = fs::metadata(&self.path) {
+            (Some(metadata.len()), metadata.modified().ok())
         } else {
-            None
+            (None, None)
         };
 
-        let (bitmap, file_info) = Plot::thumbnail(&self.path);
-        self.handle = Handle::from_rgba(bitmap.width as u32, bitmap.height as u32, bitmap.pixels);
+        // keeps the old thumbnail until the new one is rendered
+        self.thumbnail_state = ThumbnailState::Outdated;
+        self.read_error = read_error(&self.path);
+    }
 
+    /// Takes over a thumbnail rendered in the background and the file info read along.
+    fn set_thumbnail(&mut self, thumbnail: Thumbnail) {
+        self.outcome = thumbnail.outcome;
+        self.handle = thumbnail.handle;
+        self.thumbnail_state = ThumbnailState::Done;
+
+        let file_info = with_filename_meta(thumbnail.file_info, &self.path);
         self.sample_format = file_info.sample_format;
         self.sample_count = file_info.sample_count;
         self.center_freq = file_info.center_freq;
         self.sample_rate = file_info.sample_rate;
+        self.clipped = thumbnail.clipped;
+        self.update_metadata();
+    }
+
+    /// Takes over a new path after the file was renamed, keeps the thumbnail.
+    fn rename_to(&mut self, path: PathBuf) {
+        self.path = path;
+        self.update_metadata();
+    }
+
+    fn set_alias(&mut self, alias: Option<String>) {
+        self.alias = alias;
+        self.update_metadata();
+    }
+
+    /// The text the filter matches on: path, alias, format, frequency and rate.
+    fn update_metadata(&mut self) {
+        self.metadata = format!(
+            "{} {} {} {:.0}M {:.0}k",
+            self.path.to_string_lossy(),
+            self.alias.as_deref().unwrap_or_default(),
+            self.sample_format,
+            self.center_freq / 1_000_000.0,
+            self.sample_rate / 1_000.0
+        )
+        .to_ascii_lowercase();
+    }
 
-        self.metadata = format!("{} {} {:.0}M {:.0}k", self.path.to_string_lossy(), file_info.sample_format, file_info.center_freq / 1_000_000.0, file_info.sample_rate / 1_000.0).to_ascii_lowercase();
+    /// Tests if size or modification time on disk differ from the last read.
+    pub fn is_modified(&self) -> bool {
+        match fs::metadata(&self.path) {
+            Ok(metadata) => {
+                Some(metadata.len()) != self.size || metadata.modified().ok() != self.mtime
+            }
+            Err(_) => false,
+        }
     }
 
     pub fn path(&self) -> &Path {
         self.path.as_path()
     }
 
+    /// The sample format the file is opened as, `None` if detected from the extension.
+    pub fn format(&self) -> Option<&'static str> {
+        self.format
+    }
+
     pub fn size(&self) -> Option<u64> {
         self.size
     }
@@ -387,6 +1352,48 @@ impl FileItem {
         &self.handle
     }
 
+    pub fn options(&self) -> ViewOptions {
+        self.options
+    }
+
+    /// Why the file can't be opened for reading, if it can't.
+    pub fn read_error(&self) -> Option<io::ErrorKind> {
+        self.read_error
+    }
+
+    /// The SigMF collection the item was loaded from, if any.
+    pub fn collection(&self) -> Option<&Path> {
+        self.collection.as_deref()
+    }
+
+    /// Extracts the file from its archive unless done before, a no-op for other files.
+    pub fn extract(&self) -> io::Result<()> {
+        match &self.archive_entry {
+            Some(entry) => entry.extract(),
+            None => Ok(()),
+        }
+    }
+
+    /// Tests if rendering the thumbnail timed out or the file can't be decoded.
+    pub fn has_failed(&self) -> bool {
+        self.outcome == RenderOutcome::Failed
+    }
+
+    /// Tests if the thumbnail is not rendered yet, the file info is from the file name only.
+    pub fn is_pending(&self) -> bool {
+        self.thumbnail_state != ThumbnailState::Done
+    }
+
+    /// Tests if the file holds no complete sample, a failed or pending render is not counted.
+    pub fn is_empty(&self) -> bool {
+        self.size == Some(0) || (!self.is_pending() && self.outcome == RenderOutcome::Empty)
+    }
+
+    /// Fraction of samples at full scale, `None` if the format can't be checked or before the render.
+    pub fn clipped_fraction(&self) -> Option<f32> {
+        self.clipped
+    }
+
     pub fn has_mark(&self) -> bool {
         self.has_mark
     }
@@ -399,6 +1406,14 @@ impl FileItem {
         self.has_mark = !self.has_mark;
     }
 
+    pub fn has_reviewed(&self) -> bool {
+        self.has_reviewed
+    }
+
+    pub fn toggle_reviewed(&mut self) {
+        self.has_reviewed = !self.has_reviewed;
+    }
+
     pub fn toggle_delete(&mut self) {
         self.has_delete = !self.has_delete;
     }
@@ -410,8 +1425,196 @@ impl FileItem {
             .unwrap_or_default()
     }
 
+    pub fn alias(&self) -> Option<&str> {
+        self.alias.as_deref()
+    }
+
+    /// The alias if set, otherwise the file name.
+    pub fn display_name(&self) -> std::borrow::Cow<'_, str> {
+        match self.alias.as_deref() {
+            Some(alias) => alias.into(),
+            None => self.filename(),
+        }
+    }
+
     /// Tests if all filter conditions match
     fn matches_all<'a>(&self, filter: impl IntoIterator<Item = &'a str>) -> bool {
         filter.into_iter().all(|filter| self.metadata.contains(filter))
     }
 }
+
+/// Disk cache key of the thumbnail, `None` if the file has no size and modification time to key on.
+fn cache_key(
+    path: &Path,
+    format: Option<&str>,
+    size: Option<u64>,
+    mtime: Option<SystemTime>,
+    config: ThumbnailConfig,
+) -> Option<String> {
+    let appearance = match format {
+        Some(format) => format!("{}|{format}", config.appearance()),
+        None => config.appearance(),
+    };
+    Some(thumbcache::key(path, size?, mtime?, &appearance))
+}
+
+/// Loads the thumbnail from the disk cache, renders and stores it on a miss, along with the
+/// fraction of clipped samples. With `reload` the cache is not looked up, the render replaces
+/// the entry.
+fn cached_thumbnail(
+    path: &Path,
+    format: Option<&'static str>,
+    size: Option<u64>,
+    mtime: Option<SystemTime>,
+    config: ThumbnailConfig,
+    reload: bool,
+) -> (RawBitmap, FileInfo, Option<f32>) {
+    let key = cache_key(path, format, size, mtime, config);
+    if !reload {
+        if let Some(cached) = key.as_deref().and_then(thumbcache::load) {
+            return cached;
+        }
+    }
+
+    let (bitmap, file_info) = config.render(path, format);
+    let clipped = clipped_fraction(path, file_info.sample_format).unwrap_or_else(|err| {
+        println!("Clipping check error {err:?}");
+        None
+    });
+    if let Some(key) = key {
+        thumbcache::store(&key, &bitmap, &file_info, clipped);
+    }
+    (bitmap, file_info, clipped)
+}
+
+/// Fills in the center frequency and sample rate from the file name, where the file has none.
+fn with_filename_meta(mut file_info: FileInfo, path: &Path) -> FileInfo {
+    let parsed = parse_filename_meta(path);
+    if file_info.center_freq <= 0.0 {
+        file_info.center_freq = parsed.center_freq.unwrap_or(file_info.center_freq);
+    }
+    if file_info.sample_rate <= 0.0 {
+        file_info.sample_rate = parsed.sample_rate.unwrap_or(file_info.sample_rate);
+    }
+    file_info
+}
+
+/// Placeholder for a file until the thumbnail is rendered.
+fn pending_thumbnail() -> (RawBitmap, FileInfo) {
+    let pixels = [48, 48, 48, 255].repeat(16 * 16);
+    let bitmap = RawBitmap {
+        pixels,
+        width: 16,
+        height: 16,
+    };
+    let file_info = FileInfo {
+        sample_format: "n/a",
+        sample_count: 0,
+        center_freq: 0.0,
+        sample_rate: 0.0,
+    };
+    (bitmap, file_info)
+}
+
+/// Placeholder for a file that failed to render.
+fn failed_thumbnail() -> (RawBitmap, FileInfo) {
+    let pixels = [96, 32, 32, 255].repeat(16 * 16);
+    let bitmap = RawBitmap {
+        pixels,
+        width: 16,
+        height: 16,
+    };
+    let file_info = FileInfo {
+        sample_format: "n/a",
+        sample_count: 0,
+        center_freq: 0.0,
+        sample_rate: 0.0,
+    };
+    (bitmap, file_info)
+}
+
+/// Compares known values by `cmp`, reversed if `descending`, unknown values last in either order.
+fn cmp_known<T>(a: Option<T>, b: Option<T>, cmp: impl Fn(&T, &T) -> Ordering, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if descending => cmp(&b, &a),
+        (Some(a), Some(b)) => cmp(&a, &b),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    }
+}
+
+/// Why the file at `path` can't be opened for reading, `None` if it can.
+fn read_error(path: &Path) -> Option<io::ErrorKind> {
+    File::open(path).err().map(|err| err.kind())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cmp_known_orders() {
+        let asc = |a, b| cmp_known(a, b, |a: &u64, b: &u64| a.cmp(b), false);
+        let desc = |a, b| cmp_known(a, b, |a: &u64, b: &u64| a.cmp(b), true);
+        assert_eq!(asc(Some(1), Some(2)), Ordering::Less);
+        assert_eq!(desc(Some(1), Some(2)), Ordering::Greater);
+        assert_eq!(asc(Some(2), Some(2)), Ordering::Equal);
+    }
+
+    #[test]
+    fn cmp_known_unknown_last() {
+        for descending in [false, true] {
+            let cmp = |a, b| cmp_known(a, b, |a: &u64, b: &u64| a.cmp(b), descending);
+            assert_eq!(cmp(Some(1), None), Ordering::Less);
+            assert_eq!(cmp(None, Some(1)), Ordering::Greater);
+            assert_eq!(cmp(None, None), Ordering::Equal);
+        }
+    }
+
+    #[test]
+    fn cmp_known_sorts() {
+        let mut values = vec![Some(3.0), None, Some(1.0), Some(2.0)];
+        values.sort_by(|a, b| cmp_known(*a, *b, |a: &f64, b: &f64| a.total_cmp(b), true));
+        assert_eq!(values, vec![Some(3.0), Some(2.0), Some(1.0), None]);
+    }
+
+    fn test_list(count: usize) -> ItemList {
+        let mut list = ItemList::default();
+        for index in 0..count {
+            let item = list.new_item(PathBuf::from(format!("/nonexistent/{index}.cu8")));
+            list.items.push(item);
+        }
+        list.apply_filter();
+        list
+    }
+
+    #[test]
+    fn evict_drops_first_added() {
+        let mut list = test_list(5);
+        list.evict(3, Eviction::FirstAdded, None);
+        let names: Vec<_> = list.items.iter().map(|item| item.path.file_name().unwrap()).collect();
+        assert_eq!(names, ["2.cu8", "3.cu8", "4.cu8"]);
+    }
+
+    #[test]
+    fn evict_keeps_pinned() {
+        let mut list = test_list(6);
+        list.items[0].has_mark = true;
+        list.items[1].has_delete = true;
+        let open = list.items[2].path.clone();
+        list.evict(4, Eviction::FirstAdded, Some(&open));
+        let paths: Vec<_> = list.items.iter().map(|item| item.path.clone()).collect();
+        assert_eq!(paths.len(), 4);
+        assert!(list.items[0].has_mark);
+        assert!(list.items[1].has_delete);
+        assert!(paths.contains(&open));
+    }
+
+    #[test]
+    fn evict_keeps_selected() {
+        let mut list = test_list(4);
+        let selected = list.selected().map(|item| item.path.clone());
+        list.evict(2, Eviction::FirstAdded, None);
+        assert_eq!(list.items.len(), 2);
+        assert_eq!(list.selected().map(|item| item.path.clone()), selected);
+    }
+}
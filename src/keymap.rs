@@ -0,0 +1,525 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (2025) Christian W. Zuckschwerdt
+
+//! I/Q Viewer -- Key bindings, the built-in defaults remapped by a keymap file.
+//!
+//! The keymap file `keymap.toml` in the config folder binds actions to one or more keys:
+//!
+//! ```toml
+//! toggle_mark = ["f", "m"]
+//! open_dir_dialog = "Shift+o"
+//! paste_clipboard = "Cmd+v"
+//! ```
+//!
+//! Modifiers are `Shift`, `Ctrl`, `Alt`, `Logo` and `Cmd` (Ctrl, or ⌘ on macOS).
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use iced::keyboard::{Key, Modifiers, key::Named};
+
+use crate::dirs::config_dir;
+
+/// The actions that can be bound to keys, see `Viewer::on_key_press()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    SelectPrev,
+    SelectNext,
+    SelectUp,
+    SelectDown,
+    SelectHome,
+    SelectEnd,
+    CloseEditor,
+    ClearMarkers,
+    Space,
+    RemoveSelected,
+    StartRename,
+    StartAlias,
+    FocusNext,
+    FocusPrev,
+    FocusActivate,
+    ConfirmDelete,
+    ConfirmMove,
+    OpenDirDialog,
+    OpenFileDialog,
+    PasteClipboard,
+    CopyRegionCsv,
+    SaveRegionCsv,
+    ClearGallery,
+    Quit,
+    ToggleMark,
+    ToggleReviewed,
+    ToggleOnlyUnreviewed,
+    ToggleDelete,
+    FocusFilter,
+    ShowSessionStats,
+    ThumbnailSizeToggle,
+    ShowDeleted,
+    ShowOverview,
+    ShowHexPeek,
+    ShowCompare,
+    VerifyAll,
+    CopyTuningCommand,
+    ToggleSampleGrid,
+    TogglePowerHistogram,
+    IncrementZoom,
+    DecrementZoom,
+    ResetZoom,
+    /// Zoom preset by index, 0 to 8 on the keys 1 to 9.
+    ZoomPreset(usize),
+    ResetPan,
+    ExportRegion,
+    FitWidth,
+    FitHeight,
+    ToggleSplit,
+    CycleLayout,
+    ToggleColumnNormalize,
+    ToggleComparePane,
+    MatchCompareTime,
+    PrevWindow,
+    NextWindow,
+    ToggleEditorChrome,
+    ShowHelp,
+    ShowSettings,
+}
+
+/// Names of the actions in the keymap file.
+#[rustfmt::skip]
+const ACTIONS: &[(&str, Action)] = &[
+    ("select_prev", Action::SelectPrev),
+    ("select_next", Action::SelectNext),
+    ("select_up", Action::SelectUp),
+    ("select_down", Action::SelectDown),
+    ("select_home", Action::SelectHome),
+    ("select_end", Action::SelectEnd),
+    ("close_editor", Action::CloseEditor),
+    ("clear_markers", Action::ClearMarkers),
+    ("space", Action::Space),
+    ("remove_selected", Action::RemoveSelected),
+    ("start_rename", Action::StartRename),
+    ("start_alias", Action::StartAlias),
+    ("focus_next", Action::FocusNext),
+    ("focus_prev", Action::FocusPrev),
+    ("focus_activate", Action::FocusActivate),
+    ("confirm_delete", Action::ConfirmDelete),
+    ("confirm_move", Action::ConfirmMove),
+    ("open_dir_dialog", Action::OpenDirDialog),
+    ("open_file_dialog", Action::OpenFileDialog),
+    ("paste_clipboard", Action::PasteClipboard),
+    ("copy_region_csv", Action::CopyRegionCsv),
+    ("save_region_csv", Action::SaveRegionCsv),
+    ("clear_gallery", Action::ClearGallery),
+    ("quit", Action::Quit),
+    ("toggle_mark", Action::ToggleMark),
+    ("toggle_reviewed", Action::ToggleReviewed),
+    ("toggle_only_unreviewed", Action::ToggleOnlyUnreviewed),
+    ("toggle_delete", Action::ToggleDelete),
+    ("focus_filter", Action::FocusFilter),
+    ("show_session_stats", Action::ShowSessionStats),
+    ("thumbnail_size_toggle", Action::ThumbnailSizeToggle),
+    ("show_deleted", Action::ShowDeleted),
+    ("show_overview", Action::ShowOverview),
+    ("show_hex_peek", Action::ShowHexPeek),
+    ("show_compare", Action::ShowCompare),
+    ("verify_all", Action::VerifyAll),
+    ("copy_tuning_command", Action::CopyTuningCommand),
+    ("toggle_sample_grid", Action::ToggleSampleGrid),
+    ("toggle_power_histogram", Action::TogglePowerHistogram),
+    ("increment_zoom", Action::IncrementZoom),
+    ("decrement_zoom", Action::DecrementZoom),
+    ("reset_zoom", Action::ResetZoom),
+    ("zoom_preset_1", Action::ZoomPreset(0)),
+    ("zoom_preset_2", Action::ZoomPreset(1)),
+    ("zoom_preset_3", Action::ZoomPreset(2)),
+    ("zoom_preset_4", Action::ZoomPreset(3)),
+    ("zoom_preset_5", Action::ZoomPreset(4)),
+    ("zoom_preset_6", Action::ZoomPreset(5)),
+    ("zoom_preset_7", Action::ZoomPreset(6)),
+    ("zoom_preset_8", Action::ZoomPreset(7)),
+    ("zoom_preset_9", Action::ZoomPreset(8)),
+    ("reset_pan", Action::ResetPan),
+    ("export_region", Action::ExportRegion),
+    ("fit_width", Action::FitWidth),
+    ("fit_height", Action::FitHeight),
+    ("toggle_split", Action::ToggleSplit),
+    ("cycle_layout", Action::CycleLayout),
+    ("toggle_column_normalize", Action::ToggleColumnNormalize),
+    ("toggle_compare_pane", Action::ToggleComparePane),
+    ("match_compare_time", Action::MatchCompareTime),
+    ("prev_window", Action::PrevWindow),
+    ("next_window", Action::NextWindow),
+    ("toggle_editor_chrome", Action::ToggleEditorChrome),
+    ("show_help", Action::ShowHelp),
+    ("show_settings", Action::ShowSettings),
+];
+
+/// The built-in bindings, in the syntax of the keymap file.
+#[rustfmt::skip]
+const DEFAULT_BINDINGS: &[(&str, Action)] = &[
+    ("ArrowLeft", Action::SelectPrev),
+    ("ArrowRight", Action::SelectNext),
+    ("ArrowUp", Action::SelectUp),
+    ("ArrowDown", Action::SelectDown),
+    ("Home", Action::SelectHome),
+    ("End", Action::SelectEnd),
+    ("Escape", Action::CloseEditor),
+    ("Shift+Escape", Action::ClearMarkers),
+    ("Space", Action::Space),
+    ("Delete", Action::RemoveSelected),
+    ("F2", Action::StartRename),
+    ("Shift+F2", Action::StartAlias),
+    ("Tab", Action::FocusNext),
+    ("Shift+Tab", Action::FocusPrev),
+    ("Enter", Action::FocusActivate),
+    ("Shift+d", Action::ConfirmDelete),
+    ("Shift+m", Action::ConfirmMove),
+    ("Shift+o", Action::OpenDirDialog),
+    ("o", Action::OpenFileDialog),
+    ("Cmd+v", Action::PasteClipboard),
+    ("Cmd+c", Action::CopyRegionCsv),
+    ("Cmd+e", Action::SaveRegionCsv),
+    ("x", Action::ClearGallery),
+    ("q", Action::Quit),
+    ("f", Action::ToggleMark),
+    ("m", Action::ToggleMark),
+    ("i", Action::ToggleReviewed),
+    ("Shift+i", Action::ToggleOnlyUnreviewed),
+    ("d", Action::ToggleDelete),
+    ("s", Action::FocusFilter),
+    ("Shift+s", Action::ShowSessionStats),
+    ("l", Action::ThumbnailSizeToggle),
+    ("Shift+l", Action::ShowDeleted),
+    ("c", Action::ShowOverview),
+    ("Shift+h", Action::ShowHexPeek),
+    ("Shift+a", Action::ShowCompare),
+    ("Shift+v", Action::VerifyAll),
+    ("t", Action::CopyTuningCommand),
+    ("g", Action::ToggleSampleGrid),
+    ("p", Action::TogglePowerHistogram),
+    ("+", Action::IncrementZoom),
+    ("-", Action::DecrementZoom),
+    ("0", Action::ResetZoom),
+    ("1", Action::ZoomPreset(0)),
+    ("2", Action::ZoomPreset(1)),
+    ("3", Action::ZoomPreset(2)),
+    ("4", Action::ZoomPreset(3)),
+    ("5", Action::ZoomPreset(4)),
+    ("6", Action::ZoomPreset(5)),
+    ("7", Action::ZoomPreset(6)),
+    ("8", Action::ZoomPreset(7)),
+    ("9", Action::ZoomPreset(8)),
+    ("r", Action::ResetPan),
+    ("e", Action::ExportRegion),
+    // also while holding Shift to measure the region
+    ("Shift+e", Action::ExportRegion),
+    ("w", Action::FitWidth),
+    ("Shift+w", Action::FitHeight),
+    ("z", Action::ToggleSplit),
+    ("b", Action::CycleLayout),
+    ("n", Action::ToggleColumnNormalize),
+    ("k", Action::ToggleComparePane),
+    ("Shift+k", Action::MatchCompareTime),
+    ("[", Action::PrevWindow),
+    ("]", Action::NextWindow),
+    ("u", Action::ToggleEditorChrome),
+    ("h", Action::ShowHelp),
+    (",", Action::ShowSettings),
+];
+
+/// Named keys that can be bound, by their name in the keymap file.
+#[rustfmt::skip]
+const NAMED_KEYS: &[(&str, Named)] = &[
+    ("ArrowLeft", Named::ArrowLeft),
+    ("ArrowRight", Named::ArrowRight),
+    ("ArrowUp", Named::ArrowUp),
+    ("ArrowDown", Named::ArrowDown),
+    ("Home", Named::Home),
+    ("End", Named::End),
+    ("PageUp", Named::PageUp),
+    ("PageDown", Named::PageDown),
+    ("Escape", Named::Escape),
+    ("Space", Named::Space),
+    ("Enter", Named::Enter),
+    ("Tab", Named::Tab),
+    ("Backspace", Named::Backspace),
+    ("Delete", Named::Delete),
+    ("Insert", Named::Insert),
+    ("F1", Named::F1),
+    ("F2", Named::F2),
+    ("F3", Named::F3),
+    ("F4", Named::F4),
+    ("F5", Named::F5),
+    ("F6", Named::F6),
+    ("F7", Named::F7),
+    ("F8", Named::F8),
+    ("F9", Named::F9),
+    ("F10", Named::F10),
+    ("F11", Named::F11),
+    ("F12", Named::F12),
+];
+
+/// Modifier prefixes, matched without regard to case.
+const MODIFIERS: &[(&str, Modifiers)] = &[
+    ("shift", Modifiers::SHIFT),
+    ("ctrl", Modifiers::CTRL),
+    ("alt", Modifiers::ALT),
+    ("logo", Modifiers::LOGO),
+    ("cmd", Modifiers::COMMAND),
+];
+
+/// A key with the exact modifiers held.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Binding {
+    key: Key,
+    modifiers: Modifiers,
+}
+
+impl Binding {
+    /// Parses e.g. `"Shift+o"`, `"Cmd+v"`, `"F2"` or `"+"`, `None` for an unknown key or modifier.
+    fn parse(text: &str) -> Option<Self> {
+        let mut rest = text.trim();
+        let mut modifiers = Modifiers::empty();
+        // the key itself may be a "+", thus peel off modifiers from the front
+        while let Some((prefix, tail)) = rest.split_once('+').filter(|(_, tail)| !tail.is_empty()) {
+            let (_, modifier) = MODIFIERS.iter().find(|(name, _)| name.eq_ignore_ascii_case(prefix.trim()))?;
+            modifiers |= *modifier;
+            rest = tail;
+        }
+        let key = match NAMED_KEYS.iter().find(|(name, _)| name.eq_ignore_ascii_case(rest)) {
+            Some((_, named)) => Key::Named(*named),
+            None if rest.chars().count() == 1 => Key::Character(rest.to_lowercase().into()),
+            None => return None,
+        };
+        Some(Self { key, modifiers })
+    }
+}
+
+/// Bindings of keys to actions, see the module docs for the file format.
+pub struct Keymap {
+    bindings: Vec<(Binding, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = DEFAULT_BINDINGS
+            .iter()
+            .map(|&(text, action)| (Binding::parse(text).expect("Valid default binding"), action))
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// Remaps the actions listed in the `table` to their keys, an unknown action or key logs a
+    /// warning and the action keeps its default bindings then. Conflicts are resolved once all
+    /// remaps are applied: a remapped key replaces the default binding of another action, and a
+    /// key remapped to two actions stays with the first in the `ACTIONS` order, each with a warning.
+    /// The table order is alphabetical and not what the user wrote, thus it is not used.
+    fn from_table(table: &toml::Table) -> Self {
+        let default = Self::default();
+
+        for name in table.keys().filter(|name| !ACTIONS.iter().any(|(known, _)| known == name)) {
+            println!("Keymap warning: unknown action {name:?}");
+        }
+
+        let mut remapped: Vec<(Binding, Action)> = vec![];
+        let mut remapped_actions = vec![];
+        for &(name, action) in ACTIONS {
+            let Some(value) = table.get(name) else {
+                continue;
+            };
+            let texts = match value {
+                toml::Value::String(text) => vec![Some(text.as_str())],
+                toml::Value::Array(values) => values.iter().map(toml::Value::as_str).collect(),
+                _ => vec![None],
+            };
+            let bindings: Option<Vec<Binding>> = texts.into_iter().map(|text| Binding::parse(text?)).collect();
+            match bindings {
+                Some(bindings) => {
+                    remapped.extend(bindings.into_iter().map(|binding| (binding, action)));
+                    remapped_actions.push(action);
+                }
+                None => println!("Keymap warning: invalid key for {name:?}, keeping the default"),
+            }
+        }
+
+        // the actions not remapped keep their default bindings, unless a remap took the key
+        let defaults = default.bindings.into_iter().filter(|(_, action)| !remapped_actions.contains(action));
+        let mut bindings = remapped;
+        for (binding, action) in defaults {
+            match bindings.iter().find(|(bound, _)| *bound == binding) {
+                Some((_, other)) => println!("Keymap warning: {binding:?} of {action:?} is remapped to {other:?}"),
+                None => bindings.push((binding, action)),
+            }
+        }
+
+        // a key remapped twice stays with the first action
+        let mut resolved: Vec<(Binding, Action)> = Vec::with_capacity(bindings.len());
+        for (binding, action) in bindings {
+            match resolved.iter().find(|(bound, _)| *bound == binding) {
+                Some((_, other)) if *other != action => {
+                    println!("Keymap warning: {binding:?} is bound to {other:?} and {action:?}, keeping {other:?}");
+                }
+                Some(_) => {}
+                None => resolved.push((binding, action)),
+            }
+        }
+        Self { bindings: resolved }
+    }
+
+    /// The action bound to the `key` with exactly the `modifiers` held.
+    pub fn action(&self, key: &Key, modifiers: Modifiers) -> Option<Action> {
+        let key = match key {
+            Key::Character(c) => Key::Character(c.to_lowercase().into()),
+            key => key.clone(),
+        };
+        self.bindings
+            .iter()
+            .find(|(binding, _)| binding.key == key && binding.modifiers == modifiers)
+            .map(|(_, action)| *action)
+    }
+}
+
+fn keymap_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("keymap.toml"))
+}
+
+/// Loads the keymap, falls back to the defaults if missing or malformed.
+fn load_keymap() -> Keymap {
+    let Some(content) = keymap_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Keymap::default();
+    };
+    match content.parse::<toml::Table>() {
+        Ok(table) => Keymap::from_table(&table),
+        Err(err) => {
+            println!("Keymap error {err:?}");
+            Keymap::default()
+        }
+    }
+}
+
+/// The action bound to a key press, the keymap is loaded on first use.
+pub fn action(key: &Key, modifiers: Modifiers) -> Option<Action> {
+    static KEYMAP: OnceLock<Keymap> = OnceLock::new();
+    KEYMAP.get_or_init(load_keymap).action(key, modifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(key: Key, modifiers: Modifiers) -> Binding {
+        Binding { key, modifiers }
+    }
+
+    fn char_key(c: &str) -> Key {
+        Key::Character(c.into())
+    }
+
+    fn parse_keymap(text: &str) -> Keymap {
+        Keymap::from_table(&text.parse::<toml::Table>().unwrap())
+    }
+
+    #[test]
+    fn binding_parse() {
+        assert_eq!(Binding::parse("o"), Some(binding(char_key("o"), Modifiers::empty())));
+        assert_eq!(Binding::parse("O"), Some(binding(char_key("o"), Modifiers::empty())));
+        assert_eq!(Binding::parse("Shift+o"), Some(binding(char_key("o"), Modifiers::SHIFT)));
+        assert_eq!(Binding::parse("cmd+v"), Some(binding(char_key("v"), Modifiers::COMMAND)));
+        assert_eq!(
+            Binding::parse("Ctrl+Alt+x"),
+            Some(binding(char_key("x"), Modifiers::CTRL | Modifiers::ALT))
+        );
+        assert_eq!(Binding::parse("F2"), Some(binding(Key::Named(Named::F2), Modifiers::empty())));
+        assert_eq!(Binding::parse(" escape "), Some(binding(Key::Named(Named::Escape), Modifiers::empty())));
+        assert_eq!(Binding::parse("+"), Some(binding(char_key("+"), Modifiers::empty())));
+        assert_eq!(Binding::parse("Shift++"), Some(binding(char_key("+"), Modifiers::SHIFT)));
+    }
+
+    #[test]
+    fn binding_parse_invalid() {
+        assert_eq!(Binding::parse(""), None);
+        assert_eq!(Binding::parse("Foo"), None);
+        assert_eq!(Binding::parse("Hyper+x"), None);
+        assert_eq!(Binding::parse("Shift+Foo"), None);
+    }
+
+    #[test]
+    fn default_bindings_unique() {
+        let default = Keymap::default();
+        for (index, (binding, _)) in default.bindings.iter().enumerate() {
+            assert!(!default.bindings[..index].iter().any(|(bound, _)| bound == binding), "{binding:?}");
+        }
+    }
+
+    #[test]
+    fn from_table_remaps() {
+        let keymap = parse_keymap(r#"toggle_mark = ["j", "Shift+j"]"#);
+        assert_eq!(keymap.action(&char_key("j"), Modifiers::empty()), Some(Action::ToggleMark));
+        assert_eq!(keymap.action(&char_key("J"), Modifiers::SHIFT), Some(Action::ToggleMark));
+        // the defaults of a remapped action are dropped
+        assert_eq!(keymap.action(&char_key("f"), Modifiers::empty()), None);
+        assert_eq!(keymap.action(&char_key("m"), Modifiers::empty()), None);
+        // the other actions keep theirs
+        assert_eq!(keymap.action(&char_key("d"), Modifiers::empty()), Some(Action::ToggleDelete));
+    }
+
+    #[test]
+    fn from_table_keeps_default_on_errors() {
+        let keymap = parse_keymap(
+            r#"
+            no_such_action = "j"
+            toggle_mark = ["j", "Foo+j"]
+            "#,
+        );
+        assert_eq!(keymap.action(&char_key("j"), Modifiers::empty()), None);
+        assert_eq!(keymap.action(&char_key("f"), Modifiers::empty()), Some(Action::ToggleMark));
+    }
+
+    #[test]
+    fn from_table_remap_takes_default_key() {
+        // swapping the keys of two actions works in either table order
+        let keymap = parse_keymap(
+            r#"
+            toggle_delete = "f"
+            toggle_mark = "d"
+            "#,
+        );
+        assert_eq!(keymap.action(&char_key("f"), Modifiers::empty()), Some(Action::ToggleDelete));
+        assert_eq!(keymap.action(&char_key("d"), Modifiers::empty()), Some(Action::ToggleMark));
+
+        // a default key taken by a remap is not bound twice
+        let keymap = parse_keymap(r#"toggle_mark = "d""#);
+        assert_eq!(keymap.action(&char_key("d"), Modifiers::empty()), Some(Action::ToggleMark));
+        let bound = keymap.bindings.iter().filter(|(binding, _)| binding.key == char_key("d")).count();
+        assert_eq!(bound, 1);
+    }
+
+    #[test]
+    fn from_table_duplicate_remap() {
+        let keymap = parse_keymap(
+            r#"
+            quit = "j"
+            toggle_mark = "j"
+            "#,
+        );
+        assert_eq!(keymap.action(&char_key("j"), Modifiers::empty()), Some(Action::Quit));
+        let bound = keymap.bindings.iter().filter(|(binding, _)| binding.key == char_key("j")).count();
+        assert_eq!(bound, 1);
+    }
+
+    #[test]
+    fn from_table_duplicate_remap_in_actions_order() {
+        // alphabetical "toggle_delete" comes first, but "toggle_mark" is listed first in `ACTIONS`
+        let keymap = parse_keymap(
+            r#"
+            toggle_delete = "j"
+            toggle_mark = "j"
+            "#,
+        );
+        assert_eq!(keymap.action(&char_key("j"), Modifiers::empty()), Some(Action::ToggleMark));
+        // the loser of the conflict is still remapped, its defaults are gone
+        assert_eq!(keymap.action(&char_key("d"), Modifiers::empty()), None);
+    }
+}
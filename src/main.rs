@@ -7,40 +7,77 @@
 #![windows_subsystem = "windows"]
 
 use rfd::{FileDialog, MessageButtons, MessageDialog, MessageDialogResult, MessageLevel};
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::env;
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use std::usize;
 
 use iced::mouse::ScrollDelta;
 use iced::widget::scrollable::RelativeOffset;
 use iced::widget::{
-    button, column, container, focus_next, grid, horizontal_space, image, pick_list, row, scrollable, slider, text, text_input, Column, Container, Stack
+    button, column, container, focus_next, focus_previous, grid, horizontal_space, image, pick_list, responsive, row, scrollable, slider, text, text_input, tooltip, Column, Container, Space, Stack
 };
 use iced::{
-    Alignment, Center, Element, Event, Length, Point, Subscription, Task, Theme, event, keyboard,
-    mouse, window,
+    Alignment, Center, ContentFit, Element, Event, Length, Point, Subscription, Task, Theme, event, keyboard,
+    mouse, window, Size,
 };
 
+mod archive;
+mod clipping;
+mod component;
 mod dirs;
+mod export;
+mod hexpeek;
 mod icons;
 mod items;
+mod keymap;
+mod mask;
 mod mouse_area;
 mod options;
 mod plot_ffi;
 mod plotarea;
+mod render;
+mod sigmf;
+mod thumbcache;
+mod verify;
 mod watcher;
+mod windowing;
 
 use items::*;
+use keymap::Action;
 use mouse_area::*;
 use options::*;
 use plot_ffi::*;
 use plotarea::*;
 
 pub fn main() -> iced::Result {
-    iced::application(Viewer::default, Viewer::update, Viewer::view)
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "--render") {
+        if let Err(err) = render::run_cli(&args[1..]) {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if args.first().is_some_and(|arg| arg == "--verify") {
+        match verify::run_cli(&args[1..]) {
+            Ok(true) => return Ok(()),
+            Ok(false) => std::process::exit(1),
+            Err(err) => {
+                eprintln!("{err}");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    iced::application(Viewer::new, Viewer::update, Viewer::view)
         .subscription(Viewer::subscription)
-        .title(Viewer::TITLE)
+        .title(Viewer::title)
         .theme(Viewer::theme)
+        .scale_factor(Viewer::scale_factor)
         .settings(Viewer::settings())
         .window(Viewer::window_settings())
         .font(icons::FONT)
@@ -48,14 +85,46 @@ pub fn main() -> iced::Result {
 }
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
-const GRID_SPACING: f32 = 10.0;
 const GRID_TEXT_HEIGHT: f32 = 40.0;
+/// Height of the divider between the gallery and the editor in the split view.
+const SPLIT_DIVIDER_HEIGHT: f32 = 6.0;
+const OVERVIEW_TILE_SIZE: u32 = 40;
+/// Seconds the spectral leakage of a newly picked FFT window is shown.
+const WINDOW_NOTICE_SECS: u64 = 4;
+/// Failed files listed in the error dialog of a move or delete.
+const FILE_ERRORS_SHOWN: usize = 10;
+/// Seconds after the last change of the spectrogram options until they are saved, picks in between are saved once.
+const VIEW_OPTIONS_SAVE_SECS: u64 = 2;
+/// Interval in which unsaved spectrogram options are checked for the save delay.
+const VIEW_OPTIONS_POLL_MILLIS: u64 = 250;
+/// Interval in which pending thumbnails are handed to the background rendering.
+const THUMBNAIL_POLL_MILLIS: u64 = 50;
+/// Interval of the session autosave for the crash recovery.
+const SESSION_AUTOSAVE_SECS: u64 = 10;
+/// Most markers set at once, the table lists them all.
+const MARKERS_MAX: usize = 8;
+/// Search radius in px of the marker peak snap, and the level rise (of 255) that makes a clear peak.
+const PEAK_SNAP_RADIUS: u32 = 8;
+const PEAK_SNAP_RISE: u32 = 16;
+/// Keyboard focusable editor toolbar controls: FFT N, windowing, gain, range, colormap, orientation,
+/// time direction, component, enhancement, DC notch, reference levels.
+const TOOLBAR_CONTROLS: usize = 11;
+/// Power histogram bins and their span below 0 dBFS.
+const POWER_HISTOGRAM_BINS: usize = 256;
+const POWER_HISTOGRAM_SPAN: f32 = 150.0;
 
 //#[derive(Default)]
 struct Viewer {
     screen: Screen,
     zoom_editor: bool,
+    editor_chrome_visible: bool,
     show_help: bool,
+    show_settings: bool,
+    show_deleted: bool,
+    show_overview: bool,
+    show_session_stats: bool,
+    hex_peek: Option<String>,
+    verify_report: Option<Vec<(PathBuf, verify::Verdict)>>,
     cells_per_row: usize,
     thumbnail_size: u32,
     hover_count: usize,
@@ -65,14 +134,98 @@ struct Viewer {
     opts_range: Option<DbRange>,
     opts_colormap: Option<Colormap>,
     opts_orientation: Option<Orientation>,
+    /// When the spectrogram options were last picked, unsaved since.
+    view_options_changed: Option<Instant>,
+    /// Settings changed but not saved yet, e.g. the recent files, see `flush_settings()`.
+    settings_unsaved: bool,
+    opts_component: Option<Component>,
+    opts_enhance: Option<Enhance>,
+    /// The enhancement to return to when per-column normalization is toggled off.
+    enhance_before_normalize: Option<Enhance>,
+    exact_gain: Option<f32>,
+    exact_range: Option<f32>,
+    /// Gain and range in dB frozen for every file opened, for a fair comparison.
+    reference_levels: Option<(f32, f32)>,
+    gain_text: String,
+    range_text: String,
+    dbm_offset_text: String,
+    zoom_presets_text: String,
+    settings: Settings,
+    gallery_width: f32,
     cwd: Option<PathBuf>,
     thumbnails: ItemList,
     in_click: bool,
     clicked_sample: u64,
     plot: Option<Plot>,
+    /// Second pane with its own zoom and pan, keeps its file while the editor moves on.
+    compare_pane: Option<Plot>,
+    /// Sample grabbed when panning the compare pane.
+    compare_pane_drag: Option<u64>,
+    /// Watched file changes collected until the throttle time passed.
+    watch_batch: watcher::EventBatch,
     is_shift_pressed: bool,
     cursor: Point,
-    marker: PlotMarker,
+    /// Markers in the order set, the last one is the marker of the export and CSV regions.
+    markers: Vec<PlotMarker>,
+    /// Press position and temporary marker of a middle button drag measurement.
+    measure: Option<(Point, PlotMarker)>,
+    timeline: Vec<f32>,
+    /// Peak level in dB per column of the timeline, by the path of the file.
+    timeline_levels: Option<(PathBuf, Vec<f32>)>,
+    /// Hardware and antenna fields from the SigMF metadata of the open plot.
+    provenance: Vec<(&'static str, String)>,
+    detections: Vec<mask::Detection>,
+    /// Detection mask of the view, keyed by path, zoom, origin, layout and mask colors.
+    mask_overlay: RefCell<Option<(MaskKey, image::Handle)>>,
+    geolocation: Option<sigmf::Geolocation>,
+    /// Orientations picked manually in this session, by path.
+    manual_orientation: HashMap<PathBuf, Orientation>,
+    show_power_histogram: bool,
+    /// Component amplitude strip of the view, keyed by path, zoom, origin, layout and component.
+    component_strip: RefCell<Option<(ComponentKey, image::Handle)>>,
+    /// Power histogram of the view, keyed by path, zoom, origin and FFT size.
+    power_histogram: RefCell<Option<(HistogramKey, Vec<u32>)>>,
+    /// Grayscale rendering of the view for the level under the cursor, keyed like the power histogram and by the layout.
+    view_levels: RefCell<Option<(LevelsKey, LevelRender)>>,
+    /// The cursor hovers the plot, for the level readout.
+    cursor_in_plot: bool,
+    /// Thumbnails rendering in the background, at most one per core.
+    thumbnails_rendering: usize,
+    /// Occupied bandwidth of the view, keyed like the power histogram and by the estimate setting.
+    bandwidth: RefCell<Option<(BandwidthKey, f64)>>,
+    /// Dragging the upper (gain) or the lower (range) edge in the power histogram.
+    histogram_drag: Option<bool>,
+    /// Dragging the divider of the split view.
+    split_drag: bool,
+    /// Dragging the time scrollbar, with the grab offset from the thumb center as fraction.
+    scrollbar_drag: Option<f32>,
+    /// Sample range between marker and cursor, waiting for the save dialog.
+    pending_export: Option<(u64, u64)>,
+    /// Region levels as CSV, waiting for the save dialog.
+    pending_csv: Option<String>,
+    /// Spectral leakage of the FFT window just picked, shown briefly in the infobar.
+    window_notice: Option<String>,
+    /// Time direction picked but not applied yet, saved to the settings on apply.
+    pending_time_direction: Option<TimeDirection>,
+    /// Editor toolbar control with the keyboard focus.
+    toolbar_focus: Option<usize>,
+    /// A text input has the keyboard focus: set when one is focused or typed into,
+    /// cleared on submit and Escape.
+    text_focused: bool,
+    /// View options were changed but not applied to the plot yet.
+    options_pending: bool,
+    /// Session state last written for the recovery.
+    saved_session: SessionState,
+    /// Session left by an unclean exit, waiting for the restore prompt.
+    recovered_session: Option<SessionState>,
+    busy_count: usize,
+    rename: Option<String>,
+    rename_error: Option<String>,
+    alias_edit: Option<String>,
+    /// Files without a known I/Q extension, asked for their sample format in turn.
+    format_prompt: Vec<PathBuf>,
+    /// Thumbnail options A and B with their renders of the selected file.
+    compare: Option<[(ThumbnailConfig, image::Handle); 2]>,
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
@@ -82,65 +235,265 @@ enum Screen {
     Editor,
 }
 
+/// The screen combined with the split, as cycled through with `b`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScreenLayout {
+    Gallery,
+    Split,
+    Editor,
+}
+
+/// Key of the detection mask cache.
+#[derive(Debug, Clone, PartialEq)]
+struct MaskKey {
+    path: PathBuf,
+    zoom: u32,
+    origin: u32,
+    size: (u32, u32),
+    plot_rect: (u32, u32, u32, u32),
+    direction: u8,
+    colormap: MaskColormap,
+}
+
+/// Key of the component strip cache.
+#[derive(Debug, Clone, PartialEq)]
+struct ComponentKey {
+    path: PathBuf,
+    zoom: u32,
+    origin: u32,
+    size: (u32, u32),
+    plot_rect: (u32, u32, u32, u32),
+    direction: u8,
+    component: Component,
+}
+
+/// Key of the power histogram cache.
+#[derive(Debug, Clone, PartialEq)]
+struct HistogramKey {
+    path: PathBuf,
+    zoom: u32,
+    origin: u32,
+    fft_size: u32,
+}
+
+/// Key of the cursor level rendering cache.
+#[derive(Debug, Clone, PartialEq)]
+struct LevelsKey {
+    path: PathBuf,
+    zoom: u32,
+    origin: u32,
+    fft_size: u32,
+    size: (u32, u32),
+    direction: u8,
+}
+
+/// Key of the occupied bandwidth cache.
+#[derive(Debug, Clone, PartialEq)]
+struct BandwidthKey {
+    path: PathBuf,
+    zoom: u32,
+    origin: u32,
+    fft_size: u32,
+    estimate: BandwidthEstimate,
+}
+
 impl Default for Viewer {
     fn default() -> Self {
+        // explicitly given files are opened regardless of extension,
+        // the same for files handed over by a file manager "open with"
+        let args: Vec<PathBuf> = env::args()
+            .skip(1)
+            // older macOS launch services add a process serial number
+            .filter(|arg| !arg.starts_with("-psn_"))
+            .map(PathBuf::from)
+            .collect();
+        let no_args = args.is_empty();
+        archive::cleanup_stale();
+        let settings = load_settings();
         let mut thumbnails = ItemList::default();
-        thumbnails.extend(env::args().skip(1).map(|arg| PathBuf::from(arg)));
+        thumbnails.set_thumbnail_config(thumbnail_config(&settings));
+        thumbnails.set_aliases(load_aliases());
+        thumbnails.set_reviewed_paths(load_reviewed());
+        thumbnails.set_folder_stride(settings.folder_stride.to_value());
+        thumbnails.set_folder_newest(settings.folder_newest.to_value());
+        thumbnails.set_folder_depth(settings.folder_depth.to_value());
+        thumbnails.set_wrap_selection(settings.selection_ends == SelectionEnds::Wrap);
+        thumbnails.set_sort(settings.sort_order, settings.sort_descending);
+        for arg in &args {
+            if let Some(format) = dirs::unknown_format(arg) {
+                thumbnails.set_format(arg, format);
+            }
+        }
+        thumbnails.extend(args);
         // Note: watches will be added on watcher::Ready
 
-        Self {
+        // a session file left over means the last run did not quit cleanly, asked once the window is up
+        let recovered_session = load_session().filter(|session| !session.items.is_empty());
+        if recovered_session.is_none() {
+            clear_session();
+        }
+        let saved_session = thumbnails.session_state();
+
+        let mut viewer = Self {
             screen: Screen::default(),
             zoom_editor: false,
+            editor_chrome_visible: true,
             show_help: false,
+            show_settings: false,
+            show_deleted: false,
+            show_overview: false,
+            show_session_stats: false,
+            hex_peek: None,
+            verify_report: None,
             cells_per_row: 1,
             thumbnail_size: 256,
             hover_count: 0,
-            opts_fftn: Some(FftSize::default()), // FFT window width
-            opts_windowf: Some(WindowFunctions::default()), // FFT windowing function
-            opts_gain: Some(DbGain::default()),  // Overall gain (signal amplification)
-            opts_range: Some(DbRange::default()), // Gain range (cut-off to black)
-            opts_colormap: Some(Colormap::default()), // Color map
-            opts_orientation: Some(Orientation::default()), // Display orientation
+            // restored from the last session, missing ones are the defaults
+            opts_fftn: settings.view_options.fftn.or(Some(FftSize::default())), // FFT window width
+            opts_windowf: settings.view_options.windowf.or(Some(WindowFunctions::default())), // FFT windowing function
+            opts_gain: match settings.view_gain {
+                Some(gain) => DbGain::VARIANTS.iter().copied().find(|g| g.to_value() == gain),
+                None => settings.view_options.gain.or(Some(DbGain::default())), // Overall gain (signal amplification)
+            },
+            opts_range: match settings.view_range {
+                Some(range) => DbRange::VARIANTS.iter().copied().find(|r| r.to_value() == range),
+                None => settings.view_options.range.or(Some(DbRange::default())), // Gain range (cut-off to black)
+            },
+            opts_colormap: settings.view_options.colormap.or(Some(Colormap::default())), // Color map
+            opts_orientation: settings.view_options.orientation.or(Some(Orientation::default())), // Display orientation
+            view_options_changed: None,
+            settings_unsaved: false,
+            opts_component: Some(Component::default()),
+            opts_enhance: Some(Enhance::default()),
+            enhance_before_normalize: None,
+            exact_gain: settings.view_gain,
+            exact_range: settings.view_range,
+            reference_levels: None,
+            gain_text: settings.view_gain.map(format_db).unwrap_or_default(),
+            range_text: settings.view_range.map(format_db).unwrap_or_default(),
+            dbm_offset_text: format_db(settings.dbm_offset),
+            zoom_presets_text: format_zoom_presets(&settings.zoom_presets),
+            settings,
+            gallery_width: 0.0,
             cwd: None,
             thumbnails,
             in_click: false,
             clicked_sample: 0,
             plot: None,
+            compare_pane: None,
+            compare_pane_drag: None,
+            watch_batch: watcher::EventBatch::default(),
             is_shift_pressed: false,
             cursor: Point::default(),
-            marker: PlotMarker::default(),
+            markers: Vec::new(),
+            measure: None,
+            timeline: Vec::new(),
+            timeline_levels: None,
+            provenance: Vec::new(),
+            detections: Vec::new(),
+            mask_overlay: RefCell::new(None),
+            geolocation: None,
+            manual_orientation: HashMap::new(),
+            show_power_histogram: false,
+            power_histogram: RefCell::new(None),
+            view_levels: RefCell::new(None),
+            cursor_in_plot: false,
+            thumbnails_rendering: 0,
+            component_strip: RefCell::new(None),
+            bandwidth: RefCell::new(None),
+            histogram_drag: None,
+            split_drag: false,
+            scrollbar_drag: None,
+            pending_export: None,
+            pending_csv: None,
+            window_notice: None,
+            pending_time_direction: None,
+            toolbar_focus: None,
+            text_focused: false,
+            options_pending: false,
+            saved_session,
+            recovered_session,
+            busy_count: 0,
+            rename: None,
+            rename_error: None,
+            alias_edit: None,
+            format_prompt: Vec::new(),
+            compare: None,
+        };
+        // CLI arguments and a session to restore take precedence over the most recent file
+        if no_args && viewer.recovered_session.is_none() {
+            viewer.open_recent();
         }
+        viewer
     }
 }
 
 #[derive(Debug, Clone)]
 enum Message {
     ShowHelp,
+    ShowSettings,
+    ShowDeleted,
+    ShowOverview,
+    ShowSessionStats,
+    ShowHexPeek,
+    ShowCompare,
+    PickCompareColormap(usize, Colormap),
+    PickCompareFftn(usize, FftSize),
+    ApplyCompare(usize),
+    VerifyAll,
+    OverviewPress(usize),
     Quit,
     CloseEditor,
     ToggleGallery,
+    SpacePressed,
     ToggleSplit,
+    CycleLayout,
+    ToggleEditorChrome,
     FilterChanged(String),
+    ToggleOnlyMarked,
+    ToggleOnlyDelete,
     FocusFilter,
     FocusNext,
+    FocusPrev,
+    FocusActivate,
     ThumbnailSize(f32),
     ThumbnailSizeToggle,
     GalleryScrolled(scrollable::Viewport),
     RemoveSelected,
     ClearGallery,
     OpenThumbnail(usize),
+    OpenSelected,
+    StartRename,
+    OpenAsFormat(&'static str),
+    RenameChanged(String),
+    RenameSubmit,
+    StartAlias,
+    AliasChanged(String),
+    AliasSubmit,
+    Busy(Box<Message>),
+    BusyRun(Box<Message>),
     OpenDirDialog,
     OpenFileDialog,
     FilesSelected(Option<Vec<PathBuf>>),
     FileHovered,
     FilesHoveredLeft,
     FileDropped(PathBuf),
+    PasteClipboard,
+    ClipboardPasted(Option<String>),
     Watcher(watcher::WatcherEvent),
+    FlushWatcher,
+    RescanFolders,
+    LoadAll,
     ToggleMark,
+    ToggleReviewed,
+    ToggleOnlyUnreviewed,
     ToggleDelete,
     ConfirmMove,
     ConfirmDelete,
     MoveFiles(Option<PathBuf>),
+    MoveFilesConfirmed(PathBuf, MessageDialogResult),
+    PickConfirmDelete(ConfirmThreshold),
+    PickConfirmMove(ConfirmThreshold),
     DeleteFiles(MessageDialogResult),
     SelectPrev,
     SelectNext,
@@ -151,19 +504,124 @@ enum Message {
     IncrementZoom,
     DecrementZoom,
     ResetZoom,
+    ZoomPreset(usize),
+    SetZoom(u32),
+    ResetPan,
+    ExportRegion,
+    ExportTo(Option<PathBuf>),
+    CopyRegionCsv,
+    SaveRegionCsv,
+    SaveRegionCsvTo(Option<PathBuf>),
+    FitWidth,
+    FitHeight,
     PickFftn(FftSize),
     PickWindowf(WindowFunctions),
+    CycleWindowf(isize),
+    ClearWindowNotice,
+    SaveViewOptions,
     PickGain(DbGain),
+    GainInput(String),
+    GainSubmit,
+    SetGainExact(f32),
+    RangeInput(String),
+    RangeSubmit,
+    SetRangeExact(f32),
     PickRange(DbRange),
     PickColormap(Colormap),
     PickOrientation(Orientation),
+    PickTimeDirection(TimeDirection),
+    PickComponent(Component),
+    PickEnhance(Enhance),
+    ToggleColumnNormalize,
+    ApplyOptions,
+    AutosaveSession,
+    RestoreSession(MessageDialogResult),
+    PickEmptyGallery(EmptyGallery),
+    PickRefresh(RefreshInterval),
+    PickStartup(Startup),
+    PickSpaceAction(SpaceAction),
+    PickReviewOnOpen(ReviewOnOpen),
+    PickGalleryLimit(GalleryLimit),
+    PickEviction(Eviction),
+    PickGuideFilter(GuideFilter),
+    PickGuideColor(GuideColor),
+    PickSupersample(Supersample),
+    PickRenderTimeout(RenderTimeout),
+    PickInitialView(InitialView),
+    PickMaskColormap(MaskColormap),
+    PickMaskOpacity(MaskOpacity),
+    PickNumberFormat(NumberFormat),
+    PickSortOrder(SortOrder),
+    ToggleSortDescending,
+    PickWatchThrottle(WatchThrottle),
+    PickClippingWarning(ClippingWarning),
+    PickLevelUnit(LevelUnit),
+    PickThumbnailBackground(ThumbnailBackground),
+    PickOptionApply(OptionApply),
+    PickTimeScrollbar(TimeScrollbar),
+    PickMarkerSnap(MarkerSnap),
+    PickMarkAdvance(MarkAdvance),
+    PickUiScale(UiScale),
+    PickFolderNewest(FolderNewest),
+    PickFolderDepth(FolderDepth),
+    PickBandwidthEstimate(BandwidthEstimate),
+    PickNarrowOrientation(Orientation),
+    PickWideOrientation(Orientation),
+    PickRateSplit(RateSplit),
+    PickSessionLog(SessionLog),
+    PickGalleryOpen(GalleryOpen),
+    PickFolderStride(FolderStride),
+    PickSelectionEnds(SelectionEnds),
+    PickSelectAction(SelectAction),
+    ToggleDcNotch,
+    ToggleReferenceLevels,
+    ShowOnMap,
+    ToggleSampleGrid,
+    TuningTemplate(String),
+    TuningTemplateSubmit,
+    DbmOffsetInput(String),
+    ZoomPresetsInput(String),
+    ZoomPresetsSubmit,
+    DbmOffsetSubmit,
+    ExternalTool(String),
+    ExternalToolSubmit,
+    CopyTuningCommand,
+    GridSpacing(f32),
+    ThumbnailPadding(f32),
+    /// A settings slider was released, its value is saved.
+    SettingsSliderRelease,
+    ClearThumbnailCache,
     PlotLeftPress(Point),
     PlotMove(Point),
+    PlotExit,
+    RenderThumbnails,
+    ThumbnailReady(PathBuf, Thumbnail),
     PlotLeftRelease(Point),
     PlotMiddlePress(Point),
+    PlotMiddleRelease(Point),
     PlotRightPress(Point),
     PlotDoubleClicked,
+    ClearMarkers,
     PlotScroll(Point, ScrollDelta),
+    ToggleComparePane,
+    MatchCompareTime,
+    ComparePanePress(Point),
+    ComparePaneMove(Point),
+    ComparePaneRelease(Point),
+    ComparePaneScroll(Point, ScrollDelta),
+    TimelinePress(Point),
+    TogglePowerHistogram,
+    HistogramPress(Point),
+    HistogramMove(Point),
+    HistogramRelease(Point),
+    SplitPress(Point),
+    SplitMove(f32),
+    SplitRelease(Point),
+    ScrollbarPress(Point),
+    ScrollbarMove(Point),
+    ScrollbarRelease(Point),
+    TimelineThreshold(f32),
+    TimelineThresholdRelease,
     ShiftPressed,
     ShiftReleased,
 }
@@ -171,10 +629,38 @@ enum Message {
 impl Viewer {
     const TITLE: &'static str = "I/Q Viewer";
 
+    /// The viewer with the startup prompts shown in the app, e.g. to restore a session.
+    fn new() -> (Self, Task<Message>) {
+        let viewer = Self::default();
+        let task = match &viewer.recovered_session {
+            Some(session) => Task::perform(restore_session_dialog(session.items.len()), Message::RestoreSession),
+            None => Task::none(),
+        };
+        (viewer, task)
+    }
+
+    /// The open plot in the editor, otherwise the selected file, followed by the app name.
+    fn title(&self) -> String {
+        let path = match (self.screen, self.plot.as_ref()) {
+            (Screen::Editor, Some(plot)) => Some(plot.path()),
+            _ => self.thumbnails.selected().map(FileItem::path),
+        };
+        match path.and_then(Path::file_name) {
+            Some(filename) => format!("{} — {}", filename.to_string_lossy(), Self::TITLE),
+            None => Self::TITLE.to_string(),
+        }
+    }
+
     fn theme(&self) -> Theme {
         Theme::CatppuccinFrappe
     }
 
+    /// The monitor scale factor is applied by the windowing, also when moving between displays,
+    /// this scales the whole interface on top of it.
+    fn scale_factor(&self) -> f64 {
+        self.settings.ui_scale.to_value()
+    }
+
     fn settings() -> iced::Settings {
         iced::Settings {
             id: Some("org.triq.iqviewer".to_string()),
@@ -193,6 +679,8 @@ impl Viewer {
         };
         window::Settings {
             icon: Some(icon),
+            // closing quits like `q`, which also clears the recovery session
+            exit_on_close_request: false,
             min_size: Some((400.0, 400.0).into()),
             platform_specific,
             ..Default::default()
@@ -200,6 +688,34 @@ impl Viewer {
     }
 
     fn subscription(&self) -> Subscription<Message> {
+        let rescan = match self.settings.refresh.to_value() {
+            0 => Subscription::none(),
+            secs => iced::time::every(Duration::from_secs(secs)).map(|_| Message::RescanFolders),
+        };
+        let window_notice = match self.window_notice {
+            Some(_) => iced::time::every(Duration::from_secs(WINDOW_NOTICE_SECS)).map(|_| Message::ClearWindowNotice),
+            None => Subscription::none(),
+        };
+        let save_view_options = if self.view_options_changed.is_some() {
+            iced::time::every(Duration::from_millis(VIEW_OPTIONS_POLL_MILLIS)).map(|_| Message::SaveViewOptions)
+        } else {
+            Subscription::none()
+        };
+        // also polls while rendering, to give up on renders past the timeout
+        let render_thumbnails = if self.thumbnails_rendering > 0
+            || (self.thumbnails_rendering < thumbnail_workers() && self.thumbnails.has_pending())
+        {
+            iced::time::every(Duration::from_millis(THUMBNAIL_POLL_MILLIS)).map(|_| Message::RenderThumbnails)
+        } else {
+            Subscription::none()
+        };
+        let flush_watcher = match self.settings.watch_throttle.to_value() {
+            millis if millis > 0 && !self.watch_batch.is_empty() => {
+                iced::time::every(Duration::from_millis(millis)).map(|_| Message::FlushWatcher)
+            }
+            _ => Subscription::none(),
+        };
+
         Subscription::batch([
             keyboard::on_key_press(Self::on_key_press),
             keyboard::on_key_release(Self::on_key_release),
@@ -210,48 +726,83 @@ impl Viewer {
                 _ => None,
             }),
             Subscription::run(watcher::watcher_subscription).map(Message::Watcher),
+            rescan,
+            render_thumbnails,
+            flush_watcher,
+            window_notice,
+            save_view_options,
+            iced::time::every(Duration::from_secs(SESSION_AUTOSAVE_SECS)).map(|_| Message::AutosaveSession),
+            window::close_requests().map(|_| Message::Quit),
         ])
     }
 
     fn on_key_press(key: keyboard::Key, modifiers: keyboard::Modifiers) -> Option<Message> {
-        use keyboard::Key::{Character, Named};
-        use keyboard::key::Named as Key;
-
-        const NONE: keyboard::Modifiers = keyboard::Modifiers::from_bits(0).unwrap();
-        const SHIFT: keyboard::Modifiers = keyboard::Modifiers::SHIFT;
+        if key == keyboard::Key::Named(keyboard::key::Named::Shift) {
+            return Some(Message::ShiftPressed);
+        }
+        keymap::action(&key, modifiers).map(Self::action_message)
+    }
 
-        match (key.as_ref(), modifiers) {
-            (Named(Key::Shift), _) => Some(Message::ShiftPressed),
-            (Named(Key::ArrowLeft), NONE) => Some(Message::SelectPrev),
-            (Named(Key::ArrowRight), NONE) => Some(Message::SelectNext),
-            (Named(Key::ArrowUp), NONE) => Some(Message::SelectUp),
-            (Named(Key::ArrowDown), NONE) => Some(Message::SelectDown),
-            //(Named(Key::PageUp), NONE) => Some(Message::SelectPageUp),
-            //(Named(Key::PageDown), NONE) => Some(Message::SelectPageDown),
-            (Named(Key::Home), NONE) => Some(Message::SelectHome),
-            (Named(Key::End), NONE) => Some(Message::SelectEnd),
-            (Named(Key::Escape), NONE) => Some(Message::CloseEditor),
-            (Named(Key::Space), NONE) => Some(Message::ToggleGallery),
-            (Named(Key::Delete), NONE) => Some(Message::RemoveSelected),
-            (Named(Key::Tab), NONE) => Some(Message::FocusNext),
-            (Named(Key::Enter), NONE) => Some(Message::FocusNext),
-            (Character("d"), SHIFT) => Some(Message::ConfirmDelete),
-            (Character("m"), SHIFT) => Some(Message::ConfirmMove),
-            (Character("o"), SHIFT) => Some(Message::OpenDirDialog),
-            (Character("o"), NONE) => Some(Message::OpenFileDialog),
-            (Character("x"), NONE) => Some(Message::ClearGallery),
-            (Character("q"), NONE) => Some(Message::Quit),
-            (Character("f"), NONE) => Some(Message::ToggleMark),
-            (Character("m"), NONE) => Some(Message::ToggleMark),
-            (Character("d"), NONE) => Some(Message::ToggleDelete),
-            (Character("s"), NONE) => Some(Message::FocusFilter),
-            (Character("l"), NONE) => Some(Message::ThumbnailSizeToggle),
-            (Character("+"), NONE) => Some(Message::IncrementZoom),
-            (Character("-"), NONE) => Some(Message::DecrementZoom),
-            (Character("0"), NONE) => Some(Message::ResetZoom),
-            (Character("z"), NONE) => Some(Message::ToggleSplit),
-            (Character("h"), NONE) => Some(Message::ShowHelp),
-            _ => None,
+    /// The message of a key bound action, see the `keymap` module for the bindings.
+    fn action_message(action: Action) -> Message {
+        match action {
+            Action::SelectPrev => Message::SelectPrev,
+            Action::SelectNext => Message::SelectNext,
+            Action::SelectUp => Message::SelectUp,
+            Action::SelectDown => Message::SelectDown,
+            Action::SelectHome => Message::SelectHome,
+            Action::SelectEnd => Message::SelectEnd,
+            Action::CloseEditor => Message::CloseEditor,
+            Action::ClearMarkers => Message::ClearMarkers,
+            Action::Space => Message::SpacePressed,
+            Action::RemoveSelected => Message::RemoveSelected,
+            Action::StartRename => Message::StartRename,
+            Action::StartAlias => Message::StartAlias,
+            Action::FocusNext => Message::FocusNext,
+            Action::FocusPrev => Message::FocusPrev,
+            Action::FocusActivate => Message::FocusActivate,
+            Action::ConfirmDelete => Message::ConfirmDelete,
+            Action::ConfirmMove => Message::ConfirmMove,
+            Action::OpenDirDialog => Message::OpenDirDialog,
+            Action::OpenFileDialog => Message::OpenFileDialog,
+            Action::PasteClipboard => Message::PasteClipboard,
+            Action::CopyRegionCsv => Message::CopyRegionCsv,
+            Action::SaveRegionCsv => Message::SaveRegionCsv,
+            Action::ClearGallery => Message::ClearGallery,
+            Action::Quit => Message::Quit,
+            Action::ToggleMark => Message::ToggleMark,
+            Action::ToggleReviewed => Message::ToggleReviewed,
+            Action::ToggleOnlyUnreviewed => Message::ToggleOnlyUnreviewed,
+            Action::ToggleDelete => Message::ToggleDelete,
+            Action::FocusFilter => Message::FocusFilter,
+            Action::ShowSessionStats => Message::ShowSessionStats,
+            Action::ThumbnailSizeToggle => Message::ThumbnailSizeToggle,
+            Action::ShowDeleted => Message::ShowDeleted,
+            Action::ShowOverview => Message::ShowOverview,
+            Action::ShowHexPeek => Message::ShowHexPeek,
+            Action::ShowCompare => Message::ShowCompare,
+            Action::VerifyAll => Message::VerifyAll,
+            Action::CopyTuningCommand => Message::CopyTuningCommand,
+            Action::ToggleSampleGrid => Message::ToggleSampleGrid,
+            Action::TogglePowerHistogram => Message::TogglePowerHistogram,
+            Action::IncrementZoom => Message::IncrementZoom,
+            Action::DecrementZoom => Message::DecrementZoom,
+            Action::ResetZoom => Message::ResetZoom,
+            Action::ZoomPreset(index) => Message::ZoomPreset(index),
+            Action::ResetPan => Message::ResetPan,
+            Action::ExportRegion => Message::ExportRegion,
+            Action::FitWidth => Message::FitWidth,
+            Action::FitHeight => Message::FitHeight,
+            Action::ToggleSplit => Message::ToggleSplit,
+            Action::CycleLayout => Message::CycleLayout,
+            Action::ToggleColumnNormalize => Message::ToggleColumnNormalize,
+            Action::ToggleComparePane => Message::ToggleComparePane,
+            Action::MatchCompareTime => Message::MatchCompareTime,
+            Action::PrevWindow => Message::CycleWindowf(-1),
+            Action::NextWindow => Message::CycleWindowf(1),
+            Action::ToggleEditorChrome => Message::ToggleEditorChrome,
+            Action::ShowHelp => Message::ShowHelp,
+            Action::ShowSettings => Message::ShowSettings,
         }
     }
 
@@ -277,6 +828,9 @@ impl Viewer {
         FileDialog::new()
             .set_title("Open I/Q data files")
             .add_filter("I/Q Sample", dirs::FORMATS)
+            .add_filter("I/Q List", &["iqlist", "sigmf-collection"])
+            .add_filter("Archive", &["zip", "tar", "gz", "tgz"])
+            .add_filter("All files", &["*"])
             .pick_files()
     }
 
@@ -288,6 +842,23 @@ impl Viewer {
             .pick_folder()
     }
 
+    async fn csv_dialog(filename: String) -> Option<PathBuf> {
+        // TODO: AsyncFileDialog::new() ?
+        FileDialog::new()
+            .set_title("Export region levels")
+            .add_filter("CSV", &["csv"])
+            .set_file_name(filename)
+            .save_file()
+    }
+
+    async fn export_dialog(filename: String) -> Option<PathBuf> {
+        // TODO: AsyncFileDialog::new() ?
+        FileDialog::new()
+            .set_title("Export samples")
+            .set_file_name(filename)
+            .save_file()
+    }
+
     async fn confirm_delete_dialog(count: usize) -> MessageDialogResult {
         // TODO: AsyncMessageDialog::new() ?
         let description = format!("Do you want to delete {} files?", count);
@@ -299,6 +870,33 @@ impl Viewer {
             .show()
     }
 
+    async fn confirm_move_dialog(count: usize, path: PathBuf) -> MessageDialogResult {
+        // TODO: AsyncMessageDialog::new() ?
+        let description = format!("Do you want to move {} files to {}?", count, path.display());
+        MessageDialog::new()
+            .set_buttons(MessageButtons::OkCancel)
+            .set_description(description)
+            .set_level(MessageLevel::Info)
+            .set_title("Move files?")
+            .show()
+    }
+
+    /// Cells per row of the fluid grid, must match the spacing used in `view_thumbnails`.
+    fn update_cells_per_row(&mut self) {
+        let spacing = self.grid_spacing();
+        let max_width = self.thumbnail_size as f32;
+        // width = n * (cell + spacing) - spacing, given n > 0
+        self.cells_per_row = ((self.gallery_width + spacing) / (max_width + spacing)).ceil() as usize;
+    }
+
+    /// Spacing of the gallery grid, the band scan tiles are contiguous.
+    fn grid_spacing(&self) -> f32 {
+        match self.settings.sort_order {
+            SortOrder::BandScan => 0.0,
+            _ => self.settings.grid_spacing,
+        }
+    }
+
     /// Quick hack to get cells_per_row for a grid.
     fn thumbnails_scroll_position(&self) -> f32 {
         // get row postion
@@ -314,34 +912,639 @@ impl Viewer {
 
     fn open_plot(&mut self, path: impl AsRef<Path>) {
         let path = path.as_ref();
+        let listed = self.thumbnails.find(path).map(FileItem::options);
+        // Per-file options, e.g. from an I/Q list, are taken over
+        if let Some(options) = listed {
+            self.opts_fftn = options.fftn.or(self.opts_fftn);
+            self.opts_windowf = options.windowf.or(self.opts_windowf);
+            if options.gain.is_some() {
+                self.opts_gain = options.gain;
+                self.exact_gain = None;
+            }
+            if options.range.is_some() {
+                self.opts_range = options.range;
+                self.exact_range = None;
+            }
+            self.opts_colormap = options.colormap.or(self.opts_colormap);
+            self.opts_orientation = options.orientation.or(self.opts_orientation);
+        }
+        // Reference levels override the list options
+        if let Some((gain, range)) = self.reference_levels {
+            self.set_exact_levels(gain, range);
+        }
+        if let Err(err) = self.thumbnails.find(path).map_or(Ok(()), FileItem::extract) {
+            println!("Archive error {err:?}");
+        }
+        let format = self.thumbnails.format_of(path);
         if self.plot.is_none() {
-            let plot = Plot::with_path(path);
+            let plot = Plot::with_path(path, format);
             self.plot = Some(plot);
         } else {
-            self.plot.as_mut().unwrap().open(path);
+            self.plot.as_mut().unwrap().open(path, format);
+        }
+        // A manual pick, then the list option, then the default by sample rate
+        if let Some(orientation) = self.manual_orientation.get(path) {
+            self.opts_orientation = Some(*orientation);
+        } else if let (None, Some(plot)) = (listed.and_then(|o| o.orientation), self.plot.as_ref()) {
+            let split = self.settings.rate_split.to_value();
+            if split > 0.0 {
+                self.opts_orientation = Some(if plot.sample_rate() >= split {
+                    self.settings.wide_orientation
+                } else {
+                    self.settings.narrow_orientation
+                });
+            }
+        }
+        // the file may have changed since it was last opened
+        self.timeline_levels = None;
+        self.apply_view_options();
+        self.apply_initial_view();
+        self.provenance = sigmf::provenance(path);
+        if let Some(collection) = self.thumbnails.find(path).and_then(FileItem::collection) {
+            self.provenance.extend(sigmf::collection_fields(collection));
+        }
+        self.detections = mask::load(path);
+        self.mask_overlay.replace(None);
+        self.geolocation = sigmf::geolocation(path);
+        self.thumbnails.note_opened(path);
+        if self.settings.review_on_open == ReviewOnOpen::Mark {
+            self.thumbnails.set_reviewed(path);
+        }
+        self.remember_recent(path);
+    }
+
+    /// Sets the zoom and pan of a newly opened plot, by the initial view setting.
+    /// Needs the timeline of the plot for the loudest region.
+    fn apply_initial_view(&self) {
+        let Some(plot) = self.plot.as_ref() else {
+            return;
+        };
+        match self.settings.initial_view {
+            InitialView::Overview => {}
+            InitialView::FitPeak => {
+                if let Some((start, end)) = loudest_span(&self.timeline, self.settings.timeline_threshold) {
+                    let columns = self.timeline.len() as f64;
+                    let sample_at = |column: usize| (plot.sample_count() as f64 * column as f64 / columns) as u64;
+                    plot.zoom_to_span(sample_at(start), sample_at(end));
+                }
+            }
+            InitialView::ZoomPreset => {
+                if let Some(&zoom) = self.settings.zoom_presets.first() {
+                    plot.set_zoom(zoom.max(1));
+                    plot.set_origin(0);
+                }
+            }
+        }
+    }
+
+    /// Takes over a picked spectrogram option into the settings, saved once the picks settle.
+    /// Options from an I/Q list only apply to the listed file and are not taken over.
+    fn pick_view_option(&mut self, pick: impl FnOnce(&mut Settings)) {
+        pick(&mut self.settings);
+        self.view_options_changed = Some(Instant::now());
+    }
+
+    /// Saves the picked spectrogram options with the settings, to restore them on the next start.
+    fn save_view_options(&mut self) {
+        save_settings(&self.settings);
+        self.view_options_changed = None;
+        self.settings_unsaved = false;
+    }
+
+    /// The current screen and split, the editor without a plot shows the gallery.
+    fn screen_layout(&self) -> ScreenLayout {
+        match (self.screen, self.zoom_editor) {
+            (Screen::Gallery, _) => ScreenLayout::Gallery,
+            (Screen::Editor, _) if self.plot.is_none() => ScreenLayout::Gallery,
+            (Screen::Editor, false) => ScreenLayout::Split,
+            (Screen::Editor, true) => ScreenLayout::Editor,
+        }
+    }
+
+    /// Applies all view options to the open plot, including pending ones.
+    fn apply_view_options(&mut self) {
+        // a deferred orientation or time direction change keeps the view position
+        if self.options_pending {
+            self.carry_layout_direction();
+        }
+        if let Some(direction) = self.pending_time_direction.take() {
+            self.settings.time_direction = direction;
+            save_settings(&self.settings);
         }
-        // Apply all settings
         if let Some(plot) = self.plot.as_ref() {
             plot.set_cmap(self.opts_colormap.unwrap_or_default().to_value() as u32);
             plot.set_fft_size(self.opts_fftn.unwrap_or_default().to_value() as u32);
             plot.set_fft_window(self.opts_windowf.unwrap_or_default().to_value() as u8);
-            plot.set_db_gain(self.opts_gain.unwrap_or_default().to_value());
-            plot.set_db_range(self.opts_range.unwrap_or_default().to_value());
+            plot.set_db_gain(self.db_gain());
+            plot.set_db_range(self.db_range());
             plot.set_cmap(self.opts_colormap.unwrap_or_default().to_value() as u32);
-            plot.set_layout_direction(self.opts_orientation.unwrap_or_default().to_value() as u8);
+            plot.set_layout_direction(self.layout_direction());
+            plot.set_dc_notch(self.settings.dc_notch);
+            plot.set_enhance(self.opts_enhance.unwrap_or_default().to_value() as u8);
+        }
+        self.options_pending = false;
+        self.update_timeline();
+    }
+
+    /// Sets the layout direction of the plot from the options, if it changed.
+    /// The marker is kept as sample and frequency, the cursor is a pixel position:
+    /// carry it over to the same sample and frequency in the new layout.
+    fn carry_layout_direction(&mut self) {
+        let direction = self.layout_direction();
+        let Some(plot) = self.plot.as_ref().filter(|plot| plot.layout_direction() != direction) else {
+            return;
+        };
+        let (x, y) = plot_pos(self.cursor);
+        let (sample, freq) = (plot.sample_at_pos(x, y), plot.freq_at_pos(x, y));
+        plot.set_layout_direction(direction);
+        if self.cursor != Point::ORIGIN {
+            let (x, y) = plot.pos_of(sample, freq);
+            self.cursor = Point::new(x as f32, y as f32);
+        }
+    }
+
+    /// The libtriq layout for the orientation with the preferred time direction.
+    fn layout_direction(&self) -> u8 {
+        let orientation = self.opts_orientation.unwrap_or_default();
+        orientation.with_direction(self.time_direction()).to_value() as u8
+    }
+
+    /// The time direction picked, pending or else from the settings.
+    fn time_direction(&self) -> TimeDirection {
+        self.pending_time_direction.unwrap_or(self.settings.time_direction)
+    }
+
+    /// Tests if option changes wait for an apply, notes them as pending then.
+    fn defer_options(&mut self) -> bool {
+        let defer = self.settings.option_apply == OptionApply::Commit && self.plot.is_some();
+        self.options_pending |= defer;
+        defer
+    }
+
+    /// Takes over an FFT size the plot picked itself, e.g. to fit the frequency extent.
+    fn sync_fft_size(&mut self) {
+        let Some(fft_size) = self.plot.as_ref().map(Plot::fft_size) else {
+            return;
+        };
+        if self.opts_fftn.unwrap_or_default().to_value() as u32 != fft_size {
+            let fftn = FftSize::VARIANTS.iter().copied().find(|val| val.to_value() as u32 == fft_size);
+            self.opts_fftn = fftn;
+            self.pick_view_option(|settings| settings.view_options.fftn = fftn);
+        }
+    }
+
+    /// Opens the selected file in the editor if set to preview on select.
+    fn preview_selected(&mut self) -> Task<Message> {
+        let Some(path) = self.thumbnails.selected().map(FileItem::path) else {
+            return Task::none();
+        };
+        let is_open = self.screen == Screen::Editor && self.plot.as_ref().map(Plot::path) == Some(path);
+        if self.settings.select_action == SelectAction::Preview && !is_open {
+            self.run_busy(Message::OpenSelected)
+        } else {
+            Task::none()
+        }
+    }
+
+    /// Moves on to the next file after flagging, if enabled in the settings.
+    /// Opens it if the editor is shown.
+    fn advance_after_flag(&mut self) -> Task<Message> {
+        if self.settings.mark_advance != MarkAdvance::Next {
+            return Task::none();
+        }
+        if self.screen == Screen::Editor {
+            self.open_next()
+        } else {
+            self.update(Message::SelectNext)
+        }
+    }
+
+    /// Selects and opens the next file in the editor, keeps the gallery scrolled to it.
+    fn open_next(&mut self) -> Task<Message> {
+        self.thumbnails.inc_selection(1);
+        if let Some(path) = self.thumbnails.selected().map(|t| t.path().to_path_buf()) {
+            self.open_plot(path);
+        }
+        let y = self.thumbnails_scroll_position();
+        scrollable::snap_to("gallery", RelativeOffset { x: 0.0, y })
+    }
+
+    /// Keep the gallery within the configured limit.
+    fn evict_gallery(&mut self) {
+        let max_items = self.settings.gallery_limit.to_value();
+        let open = self.plot.as_ref().map(|plot| plot.path());
+        self.thumbnails.evict(max_items, self.settings.eviction, open);
+    }
+
+    /// Run a blocking message with a busy cursor shown.
+    /// The work is deferred by one update so the cursor can change first.
+    fn run_busy(&mut self, message: Message) -> Task<Message> {
+        self.busy_count += 1;
+        Task::done(Message::BusyRun(Box::new(message)))
+    }
+
+    /// Record a viewed file or folder in the persisted recently viewed list.
+    fn remember_recent(&mut self, path: &Path) {
+        // stepping through the files of a folder would rewrite the settings for each,
+        // saved with the next session autosave instead
+        let before = self.settings.recent.first().cloned();
+        self.settings.add_recent(path);
+        if self.settings.recent.first() != before.as_ref() {
+            self.settings_unsaved = true;
+        }
+    }
+
+    /// Saves the settings changed without a save yet.
+    fn flush_settings(&mut self) {
+        if self.settings_unsaved {
+            save_settings(&self.settings);
+            self.settings_unsaved = false;
+        }
+    }
+
+    /// Reopen the most recent file or folder, if enabled in the settings.
+    fn open_recent(&mut self) {
+        if self.settings.startup == Startup::Empty {
+            return;
+        }
+        let Some(path) = self.settings.recent.iter().find(|path| path.exists()).cloned() else {
+            return;
+        };
+        if path.is_file() && !dirs::is_iq_list(&path) && !archive::is_archive(&path) {
+            self.thumbnails.push(path.clone());
+            self.open_plot(&path);
+            if self.settings.startup == Startup::RecentEditor {
+                self.screen = Screen::Editor;
+            }
+        } else {
+            self.cwd = Some(path.clone());
+            self.thumbnails.push(path);
+        }
+    }
+
+    /// Gain in dB, an exact value takes precedence over the preset.
+    fn db_gain(&self) -> f32 {
+        self.exact_gain.unwrap_or_else(|| self.opts_gain.unwrap_or_default().to_value())
+    }
+
+    /// Range in dB, an exact value takes precedence over the preset.
+    fn db_range(&self) -> f32 {
+        self.exact_range.unwrap_or_else(|| self.opts_range.unwrap_or_default().to_value())
+    }
+
+    /// Sets the exact gain and range in dB, and the matching presets if any.
+    fn set_exact_levels(&mut self, gain: f32, range: f32) {
+        self.opts_gain = DbGain::VARIANTS.iter().copied().find(|g| g.to_value() == gain);
+        self.exact_gain = Some(gain);
+        self.gain_text = format_db(gain);
+        self.opts_range = DbRange::VARIANTS.iter().copied().find(|r| r.to_value() == range);
+        self.exact_range = Some(range);
+        self.range_text = format_db(range);
+    }
+
+    /// The last set marker, the default (sample 0) if there is none.
+    fn marker(&self) -> PlotMarker {
+        self.markers.last().copied().unwrap_or_default()
+    }
+
+    /// Levels of the region from the marker to the cursor as CSV, in dBm if calibrated.
+    fn region_csv(&self) -> Option<String> {
+        let Some(plot) = self.plot.as_ref().filter(|_| self.marker().sample != 0) else {
+            println!("Region export needs a marker (Shift+Click) at one corner of the region");
+            return None;
+        };
+        let (x, y) = plot_pos(self.cursor);
+        let region = plot.region_levels(
+            (self.marker().sample, self.marker().freq),
+            (plot.sample_at_pos(x, y), plot.freq_at_pos(x, y)),
+            Colormap::Grayscale.to_value() as u32,
+            POWER_HISTOGRAM_SPAN,
+        );
+        if region.samples.is_empty() || region.freqs.is_empty() {
+            return None;
+        }
+        Some(match self.dbm_offset() {
+            Some(offset) => export::region_csv(&region, offset, "dBm"),
+            None => export::region_csv(&region, 0.0, "dBFS"),
+        })
+    }
+
+    /// Calibration offset from dBFS to dBm, if levels are shown in dBm.
+    fn dbm_offset(&self) -> Option<f32> {
+        (self.settings.level_unit == LevelUnit::Dbm).then_some(self.settings.dbm_offset)
+    }
+
+    /// Power level under the cursor in dBFS, `None` if the cursor is not over the plotted samples.
+    /// Read from a grayscale rendering of the view, quantized to the steps of `LevelRender::step()`.
+    fn cursor_level(&self, plot: &Plot) -> Option<f32> {
+        let (x, y) = plot_pos(self.cursor);
+        if !self.cursor_in_plot || !plot.in_plot_rect(x, y) || plot.sample_at_pos(x, y) >= plot.sample_count() {
+            return None;
+        }
+        let key = LevelsKey {
+            path: plot.path().to_path_buf(),
+            zoom: plot.zoom(),
+            origin: plot.origin(),
+            fft_size: plot.fft_size(),
+            size: (plot.width(), plot.height()),
+            direction: plot.layout_direction(),
+        };
+        let mut cache = self.view_levels.borrow_mut();
+        if cache.as_ref().is_none_or(|(cached, _)| *cached != key) {
+            let levels = plot.render_view_levels(Colormap::Grayscale.to_value() as u32, POWER_HISTOGRAM_SPAN);
+            *cache = Some((key, levels));
+        }
+        let (x, y) = (x as usize, y as usize);
+        cache
+            .as_ref()
+            .map(|(_, levels)| levels)
+            .filter(|levels| x < levels.width && y < levels.height)
+            .map(|levels| levels.level(x, y))
+    }
+
+    /// Power level in dBFS at a position in the power histogram, which spans the plot width.
+    fn histogram_level(&self, position: Point) -> f32 {
+        let width = self.plot.as_ref().map_or(1, Plot::width).max(1) as f32;
+        let fraction = (position.x / width).clamp(0.0, 1.0);
+        -POWER_HISTOGRAM_SPAN * (1.0 - fraction)
+    }
+
+    /// The power histogram of the current view, recomputed if the view changed.
+    fn power_histogram_handle(&self, plot: &Plot) -> image::Handle {
+        let key = HistogramKey {
+            path: plot.path().to_path_buf(),
+            zoom: plot.zoom(),
+            origin: plot.origin(),
+            fft_size: plot.fft_size(),
+        };
+        let mut cache = self.power_histogram.borrow_mut();
+        if cache.as_ref().is_none_or(|(cached, _)| *cached != key) {
+            let histogram = plot.power_histogram(
+                POWER_HISTOGRAM_BINS,
+                Colormap::Grayscale.to_value() as u32,
+                POWER_HISTOGRAM_SPAN,
+            );
+            *cache = Some((key, histogram));
+        }
+        let histogram = cache.as_ref().map(|(_, histogram)| histogram.as_slice()).unwrap_or_default();
+
+        // the displayed window as fractions of the span
+        let low = (-self.db_range() - self.db_gain()) / POWER_HISTOGRAM_SPAN + 1.0;
+        let high = -self.db_gain() / POWER_HISTOGRAM_SPAN + 1.0;
+        power_histogram_handle(histogram, (low, high))
+    }
+
+    /// Amplitude strip of the I or Q component of the view, `None` for the spectrogram.
+    fn component_handle(&self, plot: &Plot) -> Option<Result<image::Handle, String>> {
+        let component = self.opts_component.unwrap_or_default();
+        if component == Component::Both {
+            return None;
+        }
+        let sample_format = SAMPLE_FORMAT[plot.sample_format() as usize];
+        if !component::is_supported(sample_format) {
+            return Some(Err(format!("No component view for {sample_format}")));
+        }
+        let direction = plot.layout_direction();
+        let key = ComponentKey {
+            path: plot.path().to_path_buf(),
+            zoom: plot.zoom(),
+            origin: plot.origin(),
+            size: (plot.width(), plot.height()),
+            plot_rect: plot.plot_rect(),
+            direction,
+            component,
+        };
+        let mut cache = self.component_strip.borrow_mut();
+        if cache.as_ref().is_none_or(|(cached, _)| *cached != key) {
+            let q = component == Component::Imaginary;
+            let ranges = component::component_ranges(plot.path(), sample_format, q, &plot.time_spans())
+                .map_err(|err| err.to_string());
+            let ranges = match ranges {
+                Ok(ranges) => ranges,
+                Err(err) => return Some(Err(err)),
+            };
+            let handle = component_handle(&ranges, key.plot_rect, key.size, direction != 0);
+            *cache = Some((key, handle));
+        }
+        cache.as_ref().map(|(_, handle)| Ok(handle.clone()))
+    }
+
+    /// Detection mask of the view, `None` if there are no detections or the mask is hidden.
+    fn mask_handle(&self, plot: &Plot) -> Option<image::Handle> {
+        if self.detections.is_empty() || self.settings.mask_opacity.to_value().is_none() {
+            return None;
+        }
+        let key = MaskKey {
+            path: plot.path().to_path_buf(),
+            zoom: plot.zoom(),
+            origin: plot.origin(),
+            size: (plot.width(), plot.height()),
+            plot_rect: plot.plot_rect(),
+            direction: plot.layout_direction(),
+            colormap: self.settings.mask_colormap,
+        };
+        let mut cache = self.mask_overlay.borrow_mut();
+        if cache.as_ref().is_none_or(|(cached, _)| *cached != key) {
+            let handle = mask_handle(plot, &self.detections, self.settings.mask_colormap.to_ramp());
+            *cache = Some((key, handle));
+        }
+        cache.as_ref().map(|(_, handle)| handle.clone())
+    }
+
+    /// Occupied bandwidth estimate of the view, if enabled in the settings.
+    fn occupied_bandwidth(&self, plot: &Plot) -> Option<f64> {
+        let estimate = self.settings.bandwidth_estimate;
+        if estimate == BandwidthEstimate::Off {
+            return None;
+        }
+        let key = BandwidthKey {
+            path: plot.path().to_path_buf(),
+            zoom: plot.zoom(),
+            origin: plot.origin(),
+            fft_size: plot.fft_size(),
+            estimate,
+        };
+        let mut cache = self.bandwidth.borrow_mut();
+        if cache.as_ref().is_none_or(|(cached, _)| *cached != key) {
+            let bandwidth = plot.occupied_bandwidth(
+                estimate.to_value(),
+                Colormap::Grayscale.to_value() as u32,
+                POWER_HISTOGRAM_SPAN,
+            );
+            *cache = Some((key, bandwidth));
+        }
+        cache.as_ref().map(|(_, bandwidth)| *bandwidth)
+    }
+
+    /// Recompute the signal presence timeline of the open plot.
+    fn update_timeline(&mut self) {
+        let Some(plot) = self.plot.as_ref() else {
+            self.timeline = Vec::new();
+            return;
+        };
+        // the levels in dB are rendered once per file, the gain and range only rescale them
+        if self.timeline_levels.as_ref().is_none_or(|(path, _)| path != plot.path()) {
+            let levels = Plot::level_profile(
+                plot.path(),
+                plot.format(),
+                1024,
+                Colormap::Grayscale.to_value() as u32,
+                POWER_HISTOGRAM_SPAN,
+            );
+            self.timeline_levels = Some((plot.path().to_path_buf(), levels));
+        }
+        let (low, range) = (-self.db_range() - self.db_gain(), self.db_range());
+        let levels = self.timeline_levels.as_ref().map(|(_, levels)| levels.as_slice()).unwrap_or_default();
+        self.timeline = levels.iter().map(|level| ((level - low) / range).clamp(0.0, 1.0)).collect();
+    }
+
+    /// Leave the editor once the last item is gone, unless set to keep the plot.
+    fn check_empty_gallery(&mut self) {
+        if self.thumbnails.unfiltered_len() == 0
+            && self.settings.empty_gallery == EmptyGallery::ReturnToGallery
+        {
+            self.screen = Screen::Gallery;
+            self.plot = None;
         }
     }
 
     fn update(&mut self, message: Message) -> Task<Message> {
+        // the toolbar keys stay with the text inputs while one is focused
+        match message {
+            Message::FocusFilter | Message::FilterChanged(_) | Message::GainInput(_) | Message::RangeInput(_) => {
+                self.text_focused = true;
+            }
+            Message::GainSubmit | Message::RangeSubmit | Message::CloseEditor => self.text_focused = false,
+            _ => {}
+        }
         match message {
-            Message::Quit => return window::get_latest().and_then(window::close),
+            Message::Quit => {
+                if self.view_options_changed.is_some() {
+                    self.save_view_options();
+                }
+                self.flush_settings();
+                clear_session();
+                archive::cleanup();
+                if self.settings.session_log == SessionLog::Append {
+                    append_session_log(self.thumbnails.session_stats());
+                }
+                return window::get_latest().and_then(window::close);
+            }
             Message::ShowHelp => {
                 self.show_help = !self.show_help;
             }
+            Message::ShowSettings => {
+                self.show_settings = !self.show_settings;
+            }
+            Message::ShowDeleted => {
+                self.show_deleted = !self.show_deleted;
+            }
+            Message::ShowOverview => {
+                self.show_overview = !self.show_overview;
+            }
+            Message::ShowSessionStats => {
+                self.show_session_stats = !self.show_session_stats;
+            }
+            Message::VerifyAll => {
+                if self.verify_report.is_some() {
+                    self.verify_report = None;
+                } else {
+                    for item in self.thumbnails.iter() {
+                        if let Err(err) = item.extract() {
+                            println!("Archive error {err:?}");
+                        }
+                    }
+                    let files = self.thumbnails.iter().map(|item| (item.path(), item.format()));
+                    self.verify_report = Some(verify::verify_all(files));
+                }
+            }
+            Message::ShowHexPeek => {
+                if self.hex_peek.is_some() {
+                    self.hex_peek = None;
+                } else if let Some(thumbnail) = self.thumbnails.selected() {
+                    self.hex_peek = Some(
+                        hexpeek::peek(thumbnail.path(), thumbnail.sample_format())
+                            .unwrap_or_else(|err| format!("Read error {err:?}")),
+                    );
+                }
+            }
+            Message::ShowCompare => {
+                if self.compare.is_some() {
+                    self.compare = None;
+                } else if let Some((path, format)) = self.thumbnails.selected().map(|t| (t.path().to_path_buf(), t.format())) {
+                    // start with the current options on both sides
+                    let config = thumbnail_config(&self.settings);
+                    let handle = compare_handle(&path, format, &config);
+                    self.compare = Some([(config, handle.clone()), (config, handle)]);
+                }
+            }
+            Message::PickCompareColormap(side, val) => {
+                let selected = self.thumbnails.selected().map(|t| (t.path().to_path_buf(), t.format()));
+                if let (Some(compare), Some((path, format))) = (self.compare.as_mut(), selected) {
+                    let (config, handle) = &mut compare[side];
+                    config.colormap = val;
+                    *handle = compare_handle(&path, format, config);
+                }
+            }
+            Message::PickCompareFftn(side, val) => {
+                let selected = self.thumbnails.selected().map(|t| (t.path().to_path_buf(), t.format()));
+                if let (Some(compare), Some((path, format))) = (self.compare.as_mut(), selected) {
+                    let (config, handle) = &mut compare[side];
+                    config.fftn = val;
+                    *handle = compare_handle(&path, format, config);
+                }
+            }
+            Message::ApplyCompare(side) => {
+                if let Some(compare) = self.compare.take() {
+                    let config = compare[side].0;
+                    self.settings.thumbnail_colormap = config.colormap;
+                    self.settings.thumbnail_fftn = config.fftn;
+                    save_settings(&self.settings);
+                    self.thumbnails.set_thumbnail_config(thumbnail_config(&self.settings));
+                }
+            }
+            Message::OverviewPress(index) => {
+                self.show_overview = false;
+                self.thumbnails.set_selection(index);
+                return self.run_busy(Message::OpenSelected);
+            }
             Message::CloseEditor => {
-                if self.show_help {
+                if self.rename.is_some() {
+                    // Cancel rename if active
+                    self.rename = None;
+                } else if self.alias_edit.is_some() {
+                    // Cancel alias edit if active
+                    self.alias_edit = None;
+                } else if !self.format_prompt.is_empty() {
+                    // Skip the file of the format prompt
+                    self.format_prompt.remove(0);
+                } else if self.show_help {
                     // Close help if it's open
                     self.show_help = !self.show_help;
+                } else if self.show_settings {
+                    // Close settings if open
+                    self.show_settings = false;
+                } else if self.show_deleted {
+                    // Close deleted files log if open
+                    self.show_deleted = false;
+                } else if self.show_overview {
+                    // Close overview if open
+                    self.show_overview = false;
+                } else if self.show_session_stats {
+                    // Close session statistics if open
+                    self.show_session_stats = false;
+                } else if self.hex_peek.is_some() {
+                    // Close hex peek if open
+                    self.hex_peek = None;
+                } else if self.verify_report.is_some() {
+                    // Close verify report if open
+                    self.verify_report = None;
+                } else if self.compare.is_some() {
+                    // Close thumbnail compare if open
+                    self.compare = None;
+                } else if self.toolbar_focus.is_some() {
+                    // Release the toolbar keyboard focus
+                    self.toolbar_focus = None;
                 } else {
                     // Otherwise close Editor, return to gallery
                     self.screen = Screen::Gallery
@@ -363,30 +1566,53 @@ impl Viewer {
                     }
                 }
             }
+            Message::SpacePressed => match (self.screen, self.settings.space_action) {
+                (Screen::Editor, SpaceAction::NextFile) => return self.open_next(),
+                (Screen::Editor, SpaceAction::Nothing) => {}
+                _ => return self.update(Message::ToggleGallery),
+            },
             Message::ToggleSplit => {
                 self.zoom_editor = !self.zoom_editor;
             }
+            Message::CycleLayout => {
+                let next = match self.screen_layout() {
+                    ScreenLayout::Gallery => ScreenLayout::Split,
+                    ScreenLayout::Split => ScreenLayout::Editor,
+                    ScreenLayout::Editor => ScreenLayout::Gallery,
+                };
+                // coming from the gallery the editor shows the selected file
+                if next == ScreenLayout::Split {
+                    let Some(path) = self.thumbnails.selected().map(|t| t.path().to_path_buf()) else {
+                        return Task::none();
+                    };
+                    if self.plot.as_ref().map(Plot::path) != Some(path.as_path()) {
+                        self.open_plot(path);
+                    }
+                }
+                self.screen = if next == ScreenLayout::Gallery { Screen::Gallery } else { Screen::Editor };
+                self.zoom_editor = next == ScreenLayout::Editor;
+            }
+            Message::ToggleEditorChrome => {
+                self.editor_chrome_visible = !self.editor_chrome_visible;
+            }
             Message::GalleryScrolled(viewport) => {
                 // TODO: save/restore offset
                 // println!("relative_offset {:?}", viewport.relative_offset().y);
-                let scrollable_available_width = viewport.content_bounds().width;
-                let max_width = self.thumbnail_size;
-                // width = n * (cell + spacing) - spacing, given n > 0
-                self.cells_per_row = ((scrollable_available_width + GRID_SPACING)
-                    / (max_width as f32 + GRID_SPACING))
-                    .ceil() as usize;
+                self.gallery_width = viewport.content_bounds().width;
+                self.update_cells_per_row();
 
                 // TODO: calculate row count
-                // let thumbnail_available_width = scrollable_available_width - GRID_SPACING * (self.cells_per_row + 1) as f32;
+                // let thumbnail_available_width = scrollable_available_width - grid_spacing * (self.cells_per_row + 1) as f32;
                 // let thumbnail_width = thumbnail_available_width / self.cells_per_row as f32;
                 // let thumbnail_scale = thumbnail_width / self.thumbnail_size as f32;
                 // let thumbnail_height = (self.thumbnail_size as f32 + GRID_TEXT_HEIGHT) * thumbnail_scale;
                 // let scrollable_visible_height = viewport.bounds().height;
-                // let visible_rows = scrollable_visible_height / (thumbnail_height + GRID_SPACING);
+                // let visible_rows = scrollable_visible_height / (thumbnail_height + grid_spacing);
                 // println!("rows {visible_rows} width {thumbnail_width} scale {thumbnail_scale}  viewport.bounds() {:?}", viewport.bounds());
             }
             Message::ThumbnailSize(size) => {
                 self.thumbnail_size = size as u32;
+                self.update_cells_per_row();
             }
             Message::ThumbnailSizeToggle => {
                 if self.thumbnail_size > 128 {
@@ -396,72 +1622,239 @@ impl Viewer {
                 } else {
                     self.thumbnail_size = 256;
                 }
+                self.update_cells_per_row();
             }
             Message::FocusFilter => return Task::batch([text_input::focus("filter"), text_input::select_all("filter")]),
+            Message::FocusNext if self.toolbar_focusable() => {
+                self.toolbar_focus = match self.toolbar_focus {
+                    None => Some(0),
+                    Some(index) if index + 1 < TOOLBAR_CONTROLS => Some(index + 1),
+                    Some(_) => {
+                        // past the last control on to the text inputs, Escape comes back
+                        self.toolbar_focus = None;
+                        self.text_focused = true;
+                        return focus_next();
+                    }
+                };
+            }
             Message::FocusNext => return focus_next(),
+            Message::FocusPrev if self.toolbar_focusable() => {
+                self.toolbar_focus = match self.toolbar_focus {
+                    None => Some(TOOLBAR_CONTROLS - 1),
+                    Some(0) => {
+                        self.toolbar_focus = None;
+                        self.text_focused = true;
+                        return focus_previous();
+                    }
+                    Some(index) => Some(index - 1),
+                };
+            }
+            Message::FocusPrev => return focus_previous(),
+            Message::FocusActivate if self.toolbar_focusable() && self.toolbar_focus.is_some() => {
+                return self.step_toolbar(1);
+            }
+            Message::FocusActivate if self.toolbar_focusable() && self.options_pending => self.apply_view_options(),
+            Message::FocusActivate => return focus_next(),
             Message::FilterChanged(content) => {
                 self.thumbnails.set_filter(&content);
             }
+            Message::ToggleOnlyMarked => {
+                self.thumbnails.toggle_only_marked();
+            }
+            Message::ToggleOnlyDelete => {
+                self.thumbnails.toggle_only_delete();
+            }
             Message::ClearGallery => {
                 self.screen = Screen::Gallery;
                 self.thumbnails.clear();
             }
             Message::RemoveSelected => {
                 self.thumbnails.selected_remove();
+                self.check_empty_gallery();
             }
             Message::ToggleMark => {
+                let flagged = self.thumbnails.selected().is_some_and(FileItem::has_mark);
                 self.thumbnails.selected_toggle_mark();
+                if !flagged {
+                    return self.advance_after_flag();
+                }
+            }
+            Message::ToggleReviewed => {
+                self.thumbnails.selected_toggle_reviewed();
+            }
+            Message::ToggleOnlyUnreviewed => {
+                self.thumbnails.toggle_only_unreviewed();
             }
             Message::ToggleDelete => {
+                let flagged = self.thumbnails.selected().is_some_and(FileItem::has_delete);
                 self.thumbnails.selected_toggle_delete();
+                if !flagged {
+                    return self.advance_after_flag();
+                }
             }
             Message::ConfirmMove => {
                 if self.thumbnails.count_marked() > 0 {
-                    return Task::perform(Self::save_dir_dialog(), Message::MoveFiles);
+                    return Task::perform(Self::save_dir_dialog(), |path| {
+                        Message::Busy(Box::new(Message::MoveFiles(path)))
+                    });
                 }
             }
             Message::ConfirmDelete => {
-                if self.thumbnails.count_to_delete() > 0 {
+                let count = self.thumbnails.count_to_delete();
+                // without the dialog it runs busy all the same
+                if count > 0 && !self.settings.confirm_delete.needs_confirm(count) {
+                    return self.update(Message::Busy(Box::new(Message::DeleteFiles(MessageDialogResult::Ok))));
+                }
+                if count > 0 {
                     return Task::perform(
                         Self::confirm_delete_dialog(self.thumbnails.count_to_delete()),
-                        Message::DeleteFiles,
+                        |result| Message::Busy(Box::new(Message::DeleteFiles(result))),
                     );
                 }
             }
             Message::MoveFiles(path) => {
-                if let Some(path) = path {
-                    self.thumbnails.move_marked_to(path);
+                let count = self.thumbnails.count_marked();
+                match path {
+                    Some(path) if self.settings.confirm_move.needs_confirm(count) => {
+                        return Task::perform(Self::confirm_move_dialog(count, path.clone()), move |result| {
+                            Message::Busy(Box::new(Message::MoveFilesConfirmed(path.clone(), result)))
+                        });
+                    }
+                    Some(path) => {
+                        let message = Message::MoveFilesConfirmed(path, MessageDialogResult::Ok);
+                        return self.update(Message::Busy(Box::new(message)));
+                    }
+                    None => {}
+                }
+            }
+            Message::MoveFilesConfirmed(path, dialog_result) => {
+                if dialog_result == MessageDialogResult::Ok {
+                    let errors = self.thumbnails.move_marked_to(path);
+                    self.check_empty_gallery();
+                    return file_errors_dialog("move", &errors);
                 }
             }
+            Message::PickConfirmDelete(val) => {
+                self.settings.confirm_delete = val;
+                save_settings(&self.settings);
+            }
+            Message::PickConfirmMove(val) => {
+                self.settings.confirm_move = val;
+                save_settings(&self.settings);
+            }
             Message::DeleteFiles(dialog_result) => {
                 if dialog_result == MessageDialogResult::Ok {
-                    self.thumbnails.delete_marked();
+                    let errors = self.thumbnails.delete_marked();
+                    self.check_empty_gallery();
+                    return file_errors_dialog("delete", &errors);
                 }
             }
             Message::OpenThumbnail(index) => {
                 if self.thumbnails.selection() == index {
-                    let path = self.thumbnails.selected().unwrap().path();
-                    self.open_plot(path.to_path_buf());
-                    self.screen = Screen::Editor
+                    if self.settings.gallery_open == GalleryOpen::External {
+                        if let Some(thumbnail) = self.thumbnails.selected() {
+                            open_external(&self.settings.external_tool, thumbnail.path());
+                        }
+                        return Task::none();
+                    }
+                    return self.run_busy(Message::OpenSelected);
                 } else {
                     self.thumbnails.set_selection(index);
+                    return self.preview_selected();
                 }
             }
-            Message::OpenDirDialog => {
-                return Task::perform(Self::open_dir_dialog(), Message::FilesSelected);
+            Message::OpenSelected => {
+                if let Some(path) = self.thumbnails.selected().map(|t| t.path().to_path_buf()) {
+                    self.open_plot(path);
+                    self.screen = Screen::Editor
+                }
             }
-            Message::OpenFileDialog => {
-                return Task::perform(Self::open_files_dialog(), Message::FilesSelected);
+            Message::OpenAsFormat(format) => {
+                if !self.format_prompt.is_empty() {
+                    let path = self.format_prompt.remove(0);
+                    self.thumbnails.set_format(&path, format);
+                    self.thumbnails.push(path.clone());
+                    self.open_plot(&path);
+                    self.screen = Screen::Editor;
+                }
             }
-            Message::FilesSelected(paths) => {
-                if let Some(paths) = paths {
+            Message::StartRename => {
+                if let Some(thumbnail) = self.thumbnails.selected() {
+                    self.rename = Some(thumbnail.filename().into_owned());
+                    self.rename_error = None;
+                    return Task::batch([text_input::focus("rename"), text_input::select_all("rename")]);
+                }
+            }
+            Message::RenameChanged(name) => {
+                self.rename = Some(name);
+                self.rename_error = None;
+            }
+            Message::RenameSubmit => {
+                let Some(name) = self.rename.clone() else {
+                    return Task::none();
+                };
+                let old_path = self.thumbnails.selected().map(|t| t.path().to_path_buf());
+                match self.thumbnails.selected_rename(&name) {
+                    Ok(path) => {
+                        self.rename = None;
+                        // follow the rename with the open plot
+                        if old_path.is_some_and(|old| Some(old.as_path()) == self.plot.as_ref().map(Plot::path)) {
+                            self.open_plot(path);
+                        }
+                    }
+                    Err(err) => self.rename_error = Some(err),
+                }
+            }
+            Message::StartAlias => {
+                if let Some(thumbnail) = self.thumbnails.selected() {
+                    self.alias_edit = Some(thumbnail.alias().unwrap_or_default().to_string());
+                    return Task::batch([text_input::focus("alias"), text_input::select_all("alias")]);
+                }
+            }
+            Message::AliasChanged(alias) => {
+                self.alias_edit = Some(alias);
+            }
+            Message::AliasSubmit => {
+                if let Some(alias) = self.alias_edit.take() {
+                    self.thumbnails.selected_set_alias(&alias);
+                }
+            }
+            Message::Busy(message) => {
+                return self.run_busy(*message);
+            }
+            Message::BusyRun(message) => {
+                let task = self.update(*message);
+                self.busy_count = self.busy_count.saturating_sub(1);
+                return task;
+            }
+            Message::OpenDirDialog => {
+                return Task::perform(Self::open_dir_dialog(), |paths| {
+                    Message::Busy(Box::new(Message::FilesSelected(paths)))
+                });
+            }
+            Message::OpenFileDialog => {
+                return Task::perform(Self::open_files_dialog(), |paths| {
+                    Message::Busy(Box::new(Message::FilesSelected(paths)))
+                });
+            }
+            Message::FilesSelected(paths) => {
+                if let Some(paths) = paths {
+                    // ask for the format of each file with unknown extension
+                    let (unknown, paths): (Vec<_>, Vec<_>) = paths.into_iter().partition(|path| {
+                        path.is_file()
+                            && !dirs::is_iq_file(path)
+                            && !dirs::is_iq_list(path)
+                            && !archive::is_archive(path)
+                    });
+                    self.format_prompt.extend(unknown);
                     if !paths.is_empty() {
                         // println!("FilesSelected {:?}", paths);
                         let first = paths.first().unwrap();
-                        if first.is_file() {
+                        if first.is_file() && !dirs::is_iq_list(first) && !archive::is_archive(first) {
                             self.open_plot(first);
                         } else {
                             self.cwd = paths.first().cloned();
+                            self.remember_recent(first);
                         }
                     }
 
@@ -471,8 +1864,11 @@ impl Viewer {
             Message::FileHovered => self.hover_count += 1,
             Message::FilesHoveredLeft => self.hover_count = 0,
             Message::FileDropped(path) => {
+                if let Some(format) = dirs::unknown_format(&path) {
+                    self.thumbnails.set_format(&path, format);
+                }
                 // println!("FileDropped (of {}) {:?}", self.hover_count, path);
-                if path.is_file() {
+                if path.is_file() && !dirs::is_iq_list(&path) && !archive::is_archive(&path) {
                     if self.hover_count == 1 {
                         // single file: open editor
                         self.screen = Screen::Editor;
@@ -486,50 +1882,103 @@ impl Viewer {
                     self.thumbnails.push(path.clone());
                     self.open_plot(&path);
                 } else {
-                    // dir of files: close editor
+                    // dir, list or archive of files: close editor
                     self.screen = Screen::Gallery;
 
                     self.cwd = Some(path.clone());
+                    self.remember_recent(&path);
 
                     self.thumbnails.push(path);
                 }
             }
+            Message::PasteClipboard => {
+                return iced::clipboard::read().map(Message::ClipboardPasted);
+            }
+            Message::ClipboardPasted(content) => {
+                // silently ignore anything that is not an I/Q file or folder
+                if let Some(path) = content.as_deref().and_then(dirs::iq_path_from_str) {
+                    // handle like a single dropped file or folder
+                    self.hover_count = 1;
+                    return self.update(Message::FileDropped(path));
+                }
+            }
             Message::Watcher(event) => {
+                // file changes wait for the batch to flush, unless not throttled
+                let throttled = self.settings.watch_throttle != WatchThrottle::Off;
+                let Some(event) = (if throttled { self.watch_batch.push(event) } else { Some(event) }) else {
+                    return Task::none();
+                };
                 self.thumbnails.watcher_event(event);
+                self.evict_gallery();
+                self.check_empty_gallery();
+            }
+            Message::FlushWatcher => {
+                for event in self.watch_batch.drain() {
+                    self.thumbnails.watcher_event(event);
+                }
+                self.evict_gallery();
+                self.check_empty_gallery();
+            }
+            Message::RescanFolders => {
+                self.thumbnails.rescan();
+                self.evict_gallery();
+                self.check_empty_gallery();
+            }
+            Message::LoadAll => {
+                self.thumbnails.load_all();
+                self.evict_gallery();
+            }
+            Message::SelectPrev if self.toolbar_focusable() && self.toolbar_focus.is_some() => {
+                self.toolbar_focus = self.toolbar_focus.map(|index| (index + TOOLBAR_CONTROLS - 1) % TOOLBAR_CONTROLS);
+            }
+            Message::SelectNext if self.toolbar_focusable() && self.toolbar_focus.is_some() => {
+                self.toolbar_focus = self.toolbar_focus.map(|index| (index + 1) % TOOLBAR_CONTROLS);
+            }
+            Message::SelectUp if self.toolbar_focusable() && self.toolbar_focus.is_some() => {
+                return self.step_toolbar(-1);
+            }
+            Message::SelectDown if self.toolbar_focusable() && self.toolbar_focus.is_some() => {
+                return self.step_toolbar(1);
             }
             Message::SelectPrev => {
                 self.thumbnails.dec_selection(1);
                 let y = self.thumbnails_scroll_position();
-                return scrollable::snap_to("gallery", RelativeOffset { x: 0.0, y });
+                let snap = scrollable::snap_to("gallery", RelativeOffset { x: 0.0, y });
+                return Task::batch([self.preview_selected(), snap]);
             }
             Message::SelectNext => {
                 self.thumbnails.inc_selection(1);
                 let y = self.thumbnails_scroll_position();
-                return scrollable::snap_to("gallery", RelativeOffset { x: 0.0, y });
+                let snap = scrollable::snap_to("gallery", RelativeOffset { x: 0.0, y });
+                return Task::batch([self.preview_selected(), snap]);
             }
             Message::SelectUp => {
                 self.thumbnails.dec_selection(self.cells_per_row);
                 let y = self.thumbnails_scroll_position();
-                return scrollable::snap_to("gallery", RelativeOffset { x: 0.0, y });
+                let snap = scrollable::snap_to("gallery", RelativeOffset { x: 0.0, y });
+                return Task::batch([self.preview_selected(), snap]);
             }
             Message::SelectDown => {
                 self.thumbnails.inc_selection(self.cells_per_row);
                 let y = self.thumbnails_scroll_position();
-                return scrollable::snap_to("gallery", RelativeOffset { x: 0.0, y });
+                let snap = scrollable::snap_to("gallery", RelativeOffset { x: 0.0, y });
+                return Task::batch([self.preview_selected(), snap]);
             }
             Message::SelectHome => {
                 self.thumbnails.set_selection(0);
-                return scrollable::snap_to(
+                let snap = scrollable::snap_to(
                     "gallery",
                     scrollable::RelativeOffset { x: 0.0, y: 0.0 },
                 );
+                return Task::batch([self.preview_selected(), snap]);
             }
             Message::SelectEnd => {
                 self.thumbnails.set_selection(usize::MAX);
-                return scrollable::snap_to(
+                let snap = scrollable::snap_to(
                     "gallery",
                     scrollable::RelativeOffset { x: 0.0, y: 1.0 },
                 );
+                return Task::batch([self.preview_selected(), snap]);
             }
             Message::IncrementZoom => {
                 if let Some(plot) = self.plot.as_mut() {
@@ -545,123 +1994,622 @@ impl Viewer {
                     plot.set_zoom_at(x, y, plot.zoom() * 2);
                 }
             }
+            Message::ZoomPreset(index) => {
+                if let Some(&zoom) = self.settings.zoom_presets.get(index) {
+                    return self.update(Message::SetZoom(zoom));
+                }
+            }
+            Message::SetZoom(zoom) => {
+                if let Some(plot) = self.plot.as_mut() {
+                    let x = plot.width() / 2; // NOTE: zoom at roughly center
+                    let y = plot.height() / 2;
+                    plot.set_zoom_at(x, y, zoom.max(1));
+                }
+            }
             Message::ResetZoom => {
                 if let Some(plot) = self.plot.as_mut() {
                     plot.set_zoom(0);
                 }
             }
+            Message::ResetPan => {
+                // back to the start, keeps the zoom level
+                if let Some(plot) = self.plot.as_ref() {
+                    plot.set_origin(0);
+                }
+            }
+            Message::ExportRegion => {
+                // the region spans from the marker to the cursor
+                let Some(plot) = self.plot.as_ref().filter(|_| self.marker().sample != 0) else {
+                    println!("Export needs a marker (Shift+Click) at one end of the region");
+                    return Task::none();
+                };
+                let (x, y) = plot_pos(self.cursor);
+                let cursor = plot.sample_at_pos(x, y);
+                let (start, end) = (self.marker().sample.min(cursor), self.marker().sample.max(cursor));
+                if start == end {
+                    return Task::none();
+                }
+                self.pending_export = Some((start, end));
+                let stem = plot.path().file_stem().unwrap_or_default().to_string_lossy();
+                let extension = plot.path().extension().unwrap_or_default().to_string_lossy();
+                // the samples of an archive are written as a plain SigMF recording
+                let extension = if extension == "sigmf" { "sigmf-data".into() } else { extension };
+                let filename = format!("{stem}_{start}-{end}.{extension}");
+                return Task::perform(Self::export_dialog(filename), |path| {
+                    Message::Busy(Box::new(Message::ExportTo(path)))
+                });
+            }
+            Message::ExportTo(dst) => {
+                let region = self.pending_export.take();
+                if let (Some(dst), Some((start, end)), Some(plot)) = (dst, region, self.plot.as_ref()) {
+                    let sample_format = SAMPLE_FORMAT[plot.sample_format() as usize];
+                    let result = export::export_samples(
+                        plot.path(),
+                        sample_format,
+                        start,
+                        end - start,
+                        plot.center_freq(),
+                        plot.sample_rate(),
+                        &dst,
+                    );
+                    if let Err(error) = result {
+                        println!("Export error {error:?}");
+                        return file_errors_dialog("export", &[FileError { path: dst, error }]);
+                    }
+                }
+            }
+            Message::CopyRegionCsv => {
+                if let Some(csv) = self.region_csv() {
+                    return iced::clipboard::write(csv);
+                }
+            }
+            Message::SaveRegionCsv => {
+                let Some(csv) = self.region_csv() else {
+                    return Task::none();
+                };
+                self.pending_csv = Some(csv);
+                let path = self.plot.as_ref().map(|plot| plot.path().to_path_buf()).unwrap_or_default();
+                let filename = format!("{}_region.csv", path.file_stem().unwrap_or_default().to_string_lossy());
+                return Task::perform(Self::csv_dialog(filename), Message::SaveRegionCsvTo);
+            }
+            Message::SaveRegionCsvTo(dst) => {
+                if let (Some(dst), Some(csv)) = (dst, self.pending_csv.take()) {
+                    if let Err(err) = fs::write(&dst, csv) {
+                        println!("Export error {err:?}");
+                    }
+                }
+            }
+            Message::FitWidth => {
+                if let Some(plot) = self.plot.as_mut() {
+                    plot.zoom_to_fit_width();
+                }
+                self.sync_fft_size();
+            }
+            Message::FitHeight => {
+                if let Some(plot) = self.plot.as_mut() {
+                    plot.zoom_to_fit_height();
+                }
+                self.sync_fft_size();
+            }
             Message::PickFftn(val) => {
                 self.opts_fftn = Some(val);
-                self.plot
-                    .as_ref()
-                    .unwrap()
-                    .set_fft_size(val.to_value() as u32);
+                self.pick_view_option(|settings| settings.view_options.fftn = Some(val));
+                if self.defer_options() {
+                    return Task::none();
+                }
+                if let Some(plot) = self.plot.as_ref() {
+                    plot.set_fft_size(val.to_value() as u32);
+                }
             }
             Message::PickWindowf(val) => {
                 self.opts_windowf = Some(val);
-                self.plot
-                    .as_ref()
-                    .unwrap()
-                    .set_fft_window(val.to_value() as u8);
+                self.pick_view_option(|settings| settings.view_options.windowf = Some(val));
+                self.window_notice = Some(format!("{val}: {}", windowing::leakage(val)));
+                if self.defer_options() {
+                    return Task::none();
+                }
+                if let Some(plot) = self.plot.as_ref() {
+                    plot.set_fft_window(val.to_value() as u8);
+                }
+            }
+            Message::CycleWindowf(delta) => {
+                let variants = WindowFunctions::VARIANTS;
+                let current = self.opts_windowf.unwrap_or_default();
+                let index = variants.iter().position(|&v| v == current).unwrap_or(0) as isize;
+                let next = variants[(index + delta).rem_euclid(variants.len() as isize) as usize];
+                return self.update(Message::PickWindowf(next));
+            }
+            Message::ClearWindowNotice => self.window_notice = None,
+            Message::ShowOnMap => {
+                if let Some(geolocation) = self.geolocation {
+                    open_url(&geolocation.map_url());
+                }
+            }
+            Message::SaveViewOptions => {
+                // each pick restarts the delay
+                let settled = Duration::from_secs(VIEW_OPTIONS_SAVE_SECS);
+                if self.view_options_changed.is_some_and(|changed| changed.elapsed() >= settled) {
+                    self.save_view_options();
+                }
             }
             Message::PickGain(val) => {
                 self.opts_gain = Some(val);
-                self.plot.as_ref().unwrap().set_db_gain(val.to_value());
+                self.pick_view_option(|settings| {
+                    settings.view_options.gain = Some(val);
+                    settings.view_gain = None;
+                });
+                self.exact_gain = None;
+                self.gain_text = format_db(val.to_value());
+                if self.defer_options() {
+                    return Task::none();
+                }
+                if let Some(plot) = self.plot.as_ref() {
+                    plot.set_db_gain(val.to_value());
+                }
+                self.update_timeline();
+            }
+            Message::GainInput(content) => self.gain_text = content,
+            Message::GainSubmit => match parse_db(&self.gain_text, -30.0..=60.0) {
+                Some(val) => return self.update(Message::SetGainExact(val)),
+                // revert to the last value
+                None => self.gain_text = format_db(self.db_gain()),
+            },
+            Message::SetGainExact(val) => {
+                self.opts_gain = DbGain::VARIANTS.iter().copied().find(|g| g.to_value() == val);
+                self.exact_gain = Some(val);
+                let preset = self.opts_gain;
+                self.pick_view_option(|settings| {
+                    settings.view_options.gain = preset;
+                    settings.view_gain = Some(val);
+                });
+                self.gain_text = format_db(val);
+                if self.defer_options() {
+                    return Task::none();
+                }
+                if let Some(plot) = self.plot.as_ref() {
+                    plot.set_db_gain(val);
+                }
+                self.update_timeline();
             }
             Message::PickRange(val) => {
                 self.opts_range = Some(val);
-                self.plot.as_ref().unwrap().set_db_range(val.to_value());
+                self.pick_view_option(|settings| {
+                    settings.view_options.range = Some(val);
+                    settings.view_range = None;
+                });
+                self.exact_range = None;
+                self.range_text = format_db(val.to_value());
+                if self.defer_options() {
+                    return Task::none();
+                }
+                if let Some(plot) = self.plot.as_ref() {
+                    plot.set_db_range(val.to_value());
+                }
+                self.update_timeline();
+            }
+            Message::RangeInput(content) => self.range_text = content,
+            Message::RangeSubmit => match parse_db(&self.range_text, 1.0..=150.0) {
+                Some(val) => return self.update(Message::SetRangeExact(val)),
+                // revert to the last value
+                None => self.range_text = format_db(self.db_range()),
+            },
+            Message::SetRangeExact(val) => {
+                self.opts_range = DbRange::VARIANTS.iter().copied().find(|r| r.to_value() == val);
+                self.exact_range = Some(val);
+                let preset = self.opts_range;
+                self.pick_view_option(|settings| {
+                    settings.view_options.range = preset;
+                    settings.view_range = Some(val);
+                });
+                self.range_text = format_db(val);
+                if self.defer_options() {
+                    return Task::none();
+                }
+                if let Some(plot) = self.plot.as_ref() {
+                    plot.set_db_range(val);
+                }
+                self.update_timeline();
             }
             Message::PickColormap(val) => {
                 self.opts_colormap = Some(val);
-                self.plot.as_ref().unwrap().set_cmap(val.to_value() as u32);
+                self.pick_view_option(|settings| settings.view_options.colormap = Some(val));
+                if self.defer_options() {
+                    return Task::none();
+                }
+                if let Some(plot) = self.plot.as_ref() {
+                    plot.set_cmap(val.to_value() as u32);
+                }
             }
             Message::PickOrientation(val) => {
                 self.opts_orientation = Some(val);
-                self.plot
-                    .as_ref()
-                    .unwrap()
-                    .set_layout_direction(val.to_value() as u8);
+                self.pick_view_option(|settings| settings.view_options.orientation = Some(val));
+                if let Some(path) = self.plot.as_ref().map(|plot| plot.path().to_path_buf()) {
+                    self.manual_orientation.insert(path, val);
+                }
+                if self.defer_options() {
+                    return Task::none();
+                }
+                self.carry_layout_direction();
+            }
+            Message::PickTimeDirection(val) => {
+                self.pending_time_direction = Some(val);
+                if self.defer_options() {
+                    return Task::none();
+                }
+                self.carry_layout_direction();
+                self.pending_time_direction = None;
+                self.settings.time_direction = val;
+                save_settings(&self.settings);
+            }
+            Message::PickComponent(val) => {
+                self.opts_component = Some(val);
+            }
+            Message::PickEnhance(val) => {
+                self.opts_enhance = Some(val);
+                if self.defer_options() {
+                    return Task::none();
+                }
+                if let Some(plot) = self.plot.as_ref() {
+                    plot.set_enhance(val.to_value() as u8);
+                }
+            }
+            Message::ToggleColumnNormalize => {
+                // compare raw and normalized, the enhancement picked before is restored
+                let enhance = if self.opts_enhance == Some(Enhance::ColumnNormalize) {
+                    self.enhance_before_normalize.take().unwrap_or_default()
+                } else {
+                    self.enhance_before_normalize = self.opts_enhance;
+                    Enhance::ColumnNormalize
+                };
+                return self.update(Message::PickEnhance(enhance));
+            }
+            Message::ApplyOptions => self.apply_view_options(),
+            Message::RestoreSession(dialog_result) => {
+                let Some(session) = self.recovered_session.take() else {
+                    return Task::none();
+                };
+                if dialog_result == MessageDialogResult::Yes {
+                    self.thumbnails.restore_session(session);
+                    self.evict_gallery();
+                } else {
+                    clear_session();
+                    // an empty gallery opens the most recent file, as without a session
+                    if self.thumbnails.is_empty() {
+                        self.open_recent();
+                    }
+                }
+            }
+            Message::AutosaveSession => {
+                self.flush_settings();
+                // only written on changes
+                let session = self.thumbnails.session_state();
+                if session != self.saved_session {
+                    save_session(&session);
+                    self.saved_session = session;
+                }
+            }
+            Message::PickEmptyGallery(val) => {
+                self.settings.empty_gallery = val;
+                save_settings(&self.settings);
+            }
+            Message::PickRefresh(val) => {
+                self.settings.refresh = val;
+                save_settings(&self.settings);
+            }
+            Message::PickStartup(val) => {
+                self.settings.startup = val;
+                save_settings(&self.settings);
+            }
+            Message::PickSpaceAction(val) => {
+                self.settings.space_action = val;
+                save_settings(&self.settings);
+            }
+            Message::PickReviewOnOpen(val) => {
+                self.settings.review_on_open = val;
+                save_settings(&self.settings);
+            }
+            Message::PickGalleryLimit(val) => {
+                self.settings.gallery_limit = val;
+                save_settings(&self.settings);
+                self.evict_gallery();
+                self.check_empty_gallery();
+            }
+            Message::PickEviction(val) => {
+                self.settings.eviction = val;
+                save_settings(&self.settings);
+            }
+            Message::ToggleReferenceLevels => {
+                self.reference_levels = match self.reference_levels {
+                    None => Some((self.db_gain(), self.db_range())),
+                    Some(_) => None,
+                };
+            }
+            Message::ToggleDcNotch => {
+                self.settings.dc_notch = !self.settings.dc_notch;
+                save_settings(&self.settings);
+                // applied with the other view options, see `apply_view_options()`
+                if self.defer_options() {
+                    return Task::none();
+                }
+                if let Some(plot) = self.plot.as_ref() {
+                    plot.set_dc_notch(self.settings.dc_notch);
+                }
+            }
+            Message::ToggleSampleGrid => {
+                self.settings.sample_grid = !self.settings.sample_grid;
+                save_settings(&self.settings);
+            }
+            Message::PickSupersample(val) => {
+                self.settings.supersample = val;
+                save_settings(&self.settings);
+                self.thumbnails.set_thumbnail_config(thumbnail_config(&self.settings));
+            }
+            Message::PickRenderTimeout(val) => {
+                self.settings.render_timeout = val;
+                save_settings(&self.settings);
+                self.thumbnails.set_thumbnail_config(thumbnail_config(&self.settings));
+            }
+            Message::PickSelectAction(val) => {
+                self.settings.select_action = val;
+                save_settings(&self.settings);
+            }
+            Message::PickSelectionEnds(val) => {
+                self.settings.selection_ends = val;
+                save_settings(&self.settings);
+                self.thumbnails.set_wrap_selection(val == SelectionEnds::Wrap);
+            }
+            Message::PickFolderStride(val) => {
+                self.settings.folder_stride = val;
+                save_settings(&self.settings);
+                self.thumbnails.set_folder_stride(val.to_value());
+            }
+            Message::PickGalleryOpen(val) => {
+                self.settings.gallery_open = val;
+                save_settings(&self.settings);
+            }
+            Message::PickSessionLog(val) => {
+                self.settings.session_log = val;
+                save_settings(&self.settings);
+            }
+            Message::PickRateSplit(val) => {
+                self.settings.rate_split = val;
+                save_settings(&self.settings);
+            }
+            Message::PickNarrowOrientation(val) => {
+                self.settings.narrow_orientation = val;
+                save_settings(&self.settings);
+            }
+            Message::PickWideOrientation(val) => {
+                self.settings.wide_orientation = val;
+                save_settings(&self.settings);
+            }
+            Message::PickBandwidthEstimate(val) => {
+                self.settings.bandwidth_estimate = val;
+                save_settings(&self.settings);
+            }
+            Message::PickFolderNewest(val) => {
+                self.settings.folder_newest = val;
+                save_settings(&self.settings);
+                self.thumbnails.set_folder_newest(val.to_value());
+            }
+            Message::PickFolderDepth(val) => {
+                self.settings.folder_depth = val;
+                save_settings(&self.settings);
+                self.thumbnails.set_folder_depth(val.to_value());
+                self.thumbnails.rescan();
+                self.evict_gallery();
+                self.check_empty_gallery();
+            }
+            Message::PickUiScale(val) => {
+                self.settings.ui_scale = val;
+                save_settings(&self.settings);
+            }
+            Message::PickMarkAdvance(val) => {
+                self.settings.mark_advance = val;
+                save_settings(&self.settings);
+            }
+            Message::PickMarkerSnap(val) => {
+                self.settings.marker_snap = val;
+                save_settings(&self.settings);
+            }
+            Message::PickTimeScrollbar(val) => {
+                self.settings.time_scrollbar = val;
+                save_settings(&self.settings);
+            }
+            Message::PickOptionApply(val) => {
+                self.settings.option_apply = val;
+                save_settings(&self.settings);
+            }
+            Message::PickThumbnailBackground(val) => {
+                self.settings.thumbnail_background = val;
+                save_settings(&self.settings);
+            }
+            Message::PickLevelUnit(val) => {
+                self.settings.level_unit = val;
+                save_settings(&self.settings);
+            }
+            Message::PickClippingWarning(val) => {
+                self.settings.clipping_warning = val;
+                save_settings(&self.settings);
+            }
+            Message::PickWatchThrottle(val) => {
+                self.settings.watch_throttle = val;
+                save_settings(&self.settings);
+                if val == WatchThrottle::Off {
+                    return self.update(Message::FlushWatcher);
+                }
+            }
+            Message::PickSortOrder(val) => {
+                self.settings.sort_order = val;
+                save_settings(&self.settings);
+                self.thumbnails.set_sort(val, self.settings.sort_descending);
+                self.update_cells_per_row();
+            }
+            Message::ToggleSortDescending => {
+                self.settings.sort_descending = !self.settings.sort_descending;
+                save_settings(&self.settings);
+                self.thumbnails.set_sort(self.settings.sort_order, self.settings.sort_descending);
+            }
+            Message::PickNumberFormat(val) => {
+                self.settings.number_format = val;
+                save_settings(&self.settings);
+            }
+            Message::PickMaskOpacity(val) => {
+                self.settings.mask_opacity = val;
+                save_settings(&self.settings);
+            }
+            Message::PickMaskColormap(val) => {
+                self.settings.mask_colormap = val;
+                save_settings(&self.settings);
+            }
+            Message::PickInitialView(val) => {
+                self.settings.initial_view = val;
+                save_settings(&self.settings);
+            }
+            Message::PickGuideColor(val) => {
+                self.settings.guide_color = val;
+                save_settings(&self.settings);
+            }
+            Message::PickGuideFilter(val) => {
+                self.settings.guide_filter = val;
+                save_settings(&self.settings);
+            }
+            Message::GridSpacing(spacing) => {
+                self.settings.grid_spacing = spacing;
+                self.update_cells_per_row();
+            }
+            Message::ThumbnailPadding(padding) => {
+                self.settings.thumbnail_padding = padding;
+            }
+            Message::SettingsSliderRelease => save_settings(&self.settings),
+            Message::ClearThumbnailCache => {
+                thumbcache::clear();
             }
             Message::PlotLeftPress(position) => {
+                let (x, y) = plot_pos(position);
                 if let Some(plot) = self.plot.as_mut() {
                     if self.is_shift_pressed {
-                        if self.marker.sample != 0
-                            && plot.is_nearby(
-                                self.marker.sample,
-                                self.marker.freq,
-                                position.x as u32,
-                                position.y as u32,
-                            )
-                        {
+                        let nearby = self.markers.iter().position(|m| plot.is_nearby(m.sample, m.freq, x, y));
+                        if let Some(index) = nearby {
                             // remove marker
-                            self.marker = PlotMarker::default();
+                            self.markers.remove(index);
                         } else {
-                            // toggle marker
-                            self.marker.sample =
-                                plot.sample_at_pos(position.x as u32, position.y as u32);
-                            self.marker.freq =
-                                plot.freq_at_pos(position.x as u32, position.y as u32);
+                            // toggle marker, on a clear peak nearby if enabled
+                            let (x, y) = match self.settings.marker_snap {
+                                MarkerSnap::Peak => plot
+                                    .peak_near(
+                                        x,
+                                        y,
+                                        PEAK_SNAP_RADIUS,
+                                        Colormap::Grayscale.to_value() as u32,
+                                        PEAK_SNAP_RISE,
+                                    )
+                                    .unwrap_or((x, y)),
+                                MarkerSnap::Exact => (x, y),
+                            };
+                            // add a marker, the oldest makes way at the cap
+                            if self.markers.len() >= MARKERS_MAX {
+                                self.markers.remove(0);
+                            }
+                            self.markers.push(PlotMarker {
+                                sample: plot.sample_at_pos(x, y),
+                                freq: plot.freq_at_pos(x, y),
+                            });
                         }
                     } else {
                         // pan view
-                        self.clicked_sample =
-                            plot.sample_at_pos(position.x as u32, position.y as u32);
+                        self.clicked_sample = plot.sample_at_pos(x, y);
                         self.in_click = true;
                     }
                 }
             }
             Message::PlotMove(position) => {
                 self.cursor = position;
+                self.cursor_in_plot = true;
                 if self.in_click {
+                    let (x, y) = plot_pos(position);
                     if let Some(plot) = self.plot.as_mut() {
-                        plot.pan_to_pos(self.clicked_sample, position.x as u32, position.y as u32);
+                        plot.pan_to_pos(self.clicked_sample, x, y);
                     }
                 }
             }
+            Message::RenderThumbnails => {
+                self.thumbnails.expire_renders();
+                let count = thumbnail_workers().saturating_sub(self.thumbnails_rendering);
+                let pending = self.thumbnails.take_pending(count);
+                self.thumbnails_rendering += pending.len();
+                return Task::batch(pending.into_iter().map(|pending| {
+                    let path = pending.path().to_path_buf();
+                    Task::perform(render_thumbnail(pending), move |thumbnail| {
+                        Message::ThumbnailReady(path, thumbnail)
+                    })
+                }));
+            }
+            Message::ThumbnailReady(path, thumbnail) => {
+                // a render given up on holds its worker until its thread ends, stuck files don't add threads
+                self.thumbnails_rendering = self.thumbnails_rendering.saturating_sub(1);
+                self.thumbnails.set_thumbnail(&path, thumbnail);
+            }
+            Message::PlotExit => {
+                self.cursor_in_plot = false;
+            }
             Message::PlotLeftRelease(position) => {
                 if self.in_click {
+                    let (x, y) = plot_pos(position);
                     if let Some(plot) = self.plot.as_mut() {
-                        plot.pan_to_pos(self.clicked_sample, position.x as u32, position.y as u32);
+                        plot.pan_to_pos(self.clicked_sample, x, y);
                     }
                     self.in_click = false;
                 }
             }
             Message::PlotMiddlePress(position) => {
-                if let Some(plot) = self.plot.as_mut() {
-                    plot.set_zoom_at(
-                        position.x as u32,
-                        position.y as u32,
-                        (plot.zoom() / 2).max(1),
-                    );
+                // start a throwaway measurement, a click without drag zooms in on release
+                let (x, y) = plot_pos(position);
+                if let Some(plot) = self.plot.as_ref() {
+                    let start = PlotMarker {
+                        sample: plot.sample_at_pos(x, y),
+                        freq: plot.freq_at_pos(x, y),
+                    };
+                    self.measure = Some((position, start));
+                    self.cursor = position;
+                }
+            }
+            Message::PlotMiddleRelease(position) => {
+                let Some((start, _)) = self.measure.take() else {
+                    return Task::none();
+                };
+                if start.distance(position) < 3.0 {
+                    let (x, y) = plot_pos(position);
+                    if let Some(plot) = self.plot.as_mut() {
+                        plot.set_zoom_at(x, y, (plot.zoom() / 2).max(1));
+                    }
                 }
             }
             Message::PlotRightPress(position) => {
+                let (x, y) = plot_pos(position);
                 if let Some(plot) = self.plot.as_mut() {
-                    plot.set_zoom_at(position.x as u32, position.y as u32, plot.zoom() * 2);
+                    plot.set_zoom_at(x, y, plot.zoom() * 2);
                 }
             }
+            Message::ClearMarkers => self.markers.clear(),
             Message::PlotDoubleClicked => {
                 if let Some(plot) = self.plot.as_mut() {
                     plot.set_zoom(0);
                 }
             }
             Message::PlotScroll(position, delta) => {
+                let (x, y) = plot_pos(position);
                 let (dx, dy) = match delta {
                     ScrollDelta::Lines { x, y } => (x, y),
                     ScrollDelta::Pixels { x, y } => (x, y),
                 };
                 if dy > 0.0 {
                     if let Some(plot) = self.plot.as_mut() {
-                        plot.set_zoom_at(
-                            position.x as u32,
-                            position.y as u32,
-                            (plot.zoom() / 2).max(1),
-                        );
+                        plot.set_zoom_at(x, y, (plot.zoom() / 2).max(1));
                     }
                 } else if dy < 0.0 {
                     if let Some(plot) = self.plot.as_mut() {
-                        plot.set_zoom_at(position.x as u32, position.y as u32, plot.zoom() * 2);
+                        plot.set_zoom_at(x, y, plot.zoom() * 2);
                     }
                 } else {
                     if let Some(plot) = self.plot.as_mut() {
@@ -670,6 +2618,175 @@ impl Viewer {
                     }
                 }
             }
+            Message::ToggleComparePane => {
+                self.compare_pane = match (self.compare_pane.take(), self.plot.as_ref()) {
+                    (Some(_), _) | (None, None) => None,
+                    (None, Some(plot)) => {
+                        let compare = plot.duplicate();
+                        compare.set_cmap(self.opts_colormap.unwrap_or_default().to_value() as u32);
+                        compare.set_fft_window(self.opts_windowf.unwrap_or_default().to_value() as u8);
+                        Some(compare)
+                    }
+                };
+                self.compare_pane_drag = None;
+            }
+            Message::MatchCompareTime => {
+                // equal distances are equal times in both panes, centered on the same time
+                let (Some(plot), Some(compare)) = (self.plot.as_ref(), self.compare_pane.as_ref()) else {
+                    return Task::none();
+                };
+                let ratio = compare.sample_rate() / plot.sample_rate();
+                if !ratio.is_finite() || ratio <= 0.0 {
+                    return Task::none();
+                }
+                let center = plot.sample_at_pos(plot.width() / 2, plot.height() / 2);
+                // the zoom is whole samples per pixel, other rate ratios only match approximately
+                compare.set_zoom(((plot.zoom() as f64 * ratio).round() as u32).max(1));
+                compare.pan_to_pos((center as f64 * ratio) as u64, compare.width() / 2, compare.height() / 2);
+            }
+            Message::ComparePanePress(position) => {
+                let (x, y) = plot_pos(position);
+                self.compare_pane_drag = self.compare_pane.as_ref().map(|plot| plot.sample_at_pos(x, y));
+            }
+            Message::ComparePaneMove(position) => {
+                let (x, y) = plot_pos(position);
+                if let (Some(plot), Some(sample)) = (self.compare_pane.as_ref(), self.compare_pane_drag) {
+                    plot.pan_to_pos(sample, x, y);
+                }
+            }
+            Message::ComparePaneRelease(position) => {
+                let (x, y) = plot_pos(position);
+                if let (Some(plot), Some(sample)) = (self.compare_pane.as_ref(), self.compare_pane_drag.take()) {
+                    plot.pan_to_pos(sample, x, y);
+                }
+            }
+            Message::ComparePaneScroll(position, delta) => {
+                let (x, y) = plot_pos(position);
+                let dy = match delta {
+                    ScrollDelta::Lines { y, .. } => y,
+                    ScrollDelta::Pixels { y, .. } => y,
+                };
+                if let Some(plot) = self.compare_pane.as_ref() {
+                    if dy > 0.0 {
+                        plot.set_zoom_at(x, y, (plot.zoom() / 2).max(1));
+                    } else if dy < 0.0 {
+                        plot.set_zoom_at(x, y, plot.zoom() * 2);
+                    }
+                }
+            }
+            Message::TimelinePress(position) => {
+                if let Some(plot) = self.plot.as_mut() {
+                    // the timeline spans the plot width, center the clicked time
+                    let fraction = (position.x / plot.width().max(1) as f32).clamp(0.0, 1.0);
+                    let sample = (plot.sample_count() as f64 * fraction as f64) as u64;
+                    plot.pan_to_pos(sample, plot.width() / 2, plot.height() / 2);
+                }
+            }
+            Message::ExternalTool(command) => {
+                // saved on submit, or with the next autosave if left without
+                self.settings.external_tool = command;
+                self.settings_unsaved = true;
+            }
+            Message::ExternalToolSubmit => self.flush_settings(),
+            Message::TogglePowerHistogram => {
+                self.show_power_histogram = !self.show_power_histogram;
+            }
+            Message::HistogramPress(position) => {
+                // grab the nearer edge of the displayed window
+                let level = self.histogram_level(position);
+                let (low, high) = (-self.db_range() - self.db_gain(), -self.db_gain());
+                self.histogram_drag = Some((level - high).abs() < (level - low).abs());
+                return self.update(Message::HistogramMove(position));
+            }
+            Message::HistogramMove(position) => {
+                let level = self.histogram_level(position);
+                match self.histogram_drag {
+                    Some(true) => return self.update(Message::SetGainExact((-level).clamp(-30.0, 60.0).round())),
+                    Some(false) => {
+                        let range = (-self.db_gain() - level).clamp(1.0, 150.0).round();
+                        return self.update(Message::SetRangeExact(range));
+                    }
+                    None => {}
+                }
+            }
+            Message::HistogramRelease(_position) => {
+                self.histogram_drag = None;
+            }
+            Message::SplitPress(_position) => {
+                self.split_drag = true;
+            }
+            Message::SplitMove(ratio) => {
+                if self.split_drag {
+                    self.settings.split_ratio = ratio.clamp(SPLIT_RATIO_MIN, SPLIT_RATIO_MAX);
+                }
+            }
+            Message::SplitRelease(_position) => {
+                // releases anywhere over the split, only a drag is saved
+                if self.split_drag {
+                    self.split_drag = false;
+                    save_settings(&self.settings);
+                }
+            }
+            Message::ScrollbarPress(position) => {
+                if let Some(plot) = self.plot.as_ref() {
+                    // grab the thumb where pressed, elsewhere jump to center there
+                    let fraction = (position.x / plot.width().max(1) as f32).clamp(0.0, 1.0);
+                    let (start, end) = scrollbar_thumb(plot);
+                    let grab = if (start..=end).contains(&fraction) {
+                        fraction - (start + end) / 2.0
+                    } else {
+                        0.0
+                    };
+                    self.scrollbar_drag = Some(grab);
+                    return self.update(Message::ScrollbarMove(position));
+                }
+            }
+            Message::ScrollbarMove(position) => {
+                if let (Some(plot), Some(grab)) = (self.plot.as_mut(), self.scrollbar_drag) {
+                    // the scrollbar spans the plot width, like the timeline
+                    let fraction = (position.x / plot.width().max(1) as f32 - grab).clamp(0.0, 1.0);
+                    let sample = (plot.sample_count() as f64 * fraction as f64) as u64;
+                    plot.pan_to_pos(sample, plot.width() / 2, plot.height() / 2);
+                }
+            }
+            Message::ScrollbarRelease(_position) => {
+                self.scrollbar_drag = None;
+            }
+            Message::ZoomPresetsInput(content) => self.zoom_presets_text = content,
+            Message::ZoomPresetsSubmit => {
+                if let Some(presets) = parse_zoom_presets(&self.zoom_presets_text) {
+                    self.settings.zoom_presets = presets;
+                    save_settings(&self.settings);
+                }
+                // normalize, or revert to the last value
+                self.zoom_presets_text = format_zoom_presets(&self.settings.zoom_presets);
+            }
+            Message::DbmOffsetInput(content) => self.dbm_offset_text = content,
+            Message::DbmOffsetSubmit => {
+                if let Some(offset) = parse_db(&self.dbm_offset_text, -200.0..=200.0) {
+                    self.settings.dbm_offset = offset;
+                    save_settings(&self.settings);
+                }
+                // normalize, or revert to the last value
+                self.dbm_offset_text = format_db(self.settings.dbm_offset);
+            }
+            Message::TuningTemplate(template) => {
+                // saved on submit, or with the next autosave if left without
+                self.settings.tuning_template = template;
+                self.settings_unsaved = true;
+            }
+            Message::TuningTemplateSubmit => self.flush_settings(),
+            Message::CopyTuningCommand => {
+                if let Some(plot) = self.plot.as_ref() {
+                    let command = tuning_command(&self.settings.tuning_template, plot);
+                    return iced::clipboard::write(command);
+                }
+            }
+            Message::TimelineThreshold(threshold) => {
+                self.settings.timeline_threshold = threshold;
+            }
+            // only the value the slider is released at is saved
+            Message::TimelineThresholdRelease => save_settings(&self.settings),
             Message::ShiftPressed => self.is_shift_pressed = true,
             Message::ShiftReleased => self.is_shift_pressed = false,
         }
@@ -678,55 +2795,126 @@ impl Viewer {
 
     fn view(&self) -> Element<'_, Message> {
         let content: Element<'_, Message> = match (self.screen, self.zoom_editor) {
+            (Screen::Editor, _) if self.plot.is_none() => {
+                self.view_gallery().height(Length::FillPortion(1)).into()
+            }
             (Screen::Gallery, _) => self.view_gallery().height(Length::FillPortion(1)).into(),
             (Screen::Editor, true) => self.view_editor().into(),
-            (Screen::Editor, false) => column![
-                self.view_gallery().height(Length::FillPortion(1)),
-                self.view_editor().height(Length::FillPortion(2)),
-            ]
-            .align_x(Center)
-            .into(),
+            (Screen::Editor, false) => responsive(|size| self.view_split(size)).into(),
         };
 
         let content = column![content, self.view_statusbar(),].into();
 
-        if self.show_help {
+        let content = if self.rename.is_some() {
+            Stack::with_children([content, self.view_rename().into()]).into()
+        } else if let Some(alias) = self.alias_edit.as_deref() {
+            Stack::with_children([content, self.view_alias(alias).into()]).into()
+        } else if let Some(path) = self.format_prompt.first() {
+            Stack::with_children([content, self.view_format_prompt(path).into()]).into()
+        } else if self.show_help {
             Stack::with_children([content, self.view_help().into()]).into()
+        } else if self.show_settings {
+            Stack::with_children([content, self.view_settings().into()]).into()
+        } else if self.show_deleted {
+            Stack::with_children([content, self.view_deleted().into()]).into()
+        } else if self.show_overview {
+            Stack::with_children([content, self.view_overview().into()]).into()
+        } else if self.show_session_stats {
+            Stack::with_children([content, self.view_session_stats().into()]).into()
+        } else if let Some(hex_peek) = self.hex_peek.as_deref() {
+            Stack::with_children([content, self.view_hex_peek(hex_peek).into()]).into()
+        } else if let Some(report) = self.verify_report.as_deref() {
+            Stack::with_children([content, self.view_verify_report(report).into()]).into()
+        } else if let Some(compare) = self.compare.as_ref() {
+            Stack::with_children([content, self.view_compare(compare).into()]).into()
+        } else {
+            content
+        };
+
+        if self.busy_count > 0 {
+            let busy = MouseArea::new(Space::new(Length::Fill, Length::Fill))
+                .interaction(mouse::Interaction::Working);
+            Stack::with_children([content, busy.into()]).into()
         } else {
             content
         }
     }
 
+    /// Gallery above the editor, divided at the split ratio, the divider can be dragged.
+    fn view_split(&self, size: Size) -> Element<'_, Message> {
+        let gallery_height = ((size.height - SPLIT_DIVIDER_HEIGHT) * self.settings.split_ratio).round();
+        let divider = MouseArea::new(container(Space::new(Length::Fill, SPLIT_DIVIDER_HEIGHT)).style(container::bordered_box))
+            .on_press(Message::SplitPress)
+            .interaction(mouse::Interaction::ResizingVertically);
+        let split = column![
+            self.view_gallery().height(gallery_height),
+            divider,
+            self.view_editor().height(Length::Fill),
+        ]
+        .align_x(Center);
+
+        // while dragging the divider follows the cursor anywhere over the split
+        let height = size.height.max(1.0);
+        MouseArea::new(split)
+            .on_move_maybe(self.split_drag.then_some(move |position: Point| Message::SplitMove(position.y / height)))
+            .on_release(Message::SplitRelease)
+            .into()
+    }
+
     fn view_statusbar(&self) -> Container<Message> {
         let watches = self.thumbnails.count_watches();
         let filtered = self.thumbnails.len();
         let marked = self.thumbnails.count_marked();
         let to_delete = self.thumbnails.count_to_delete();
+        let reviewed = self.thumbnails.count_reviewed();
         let item_count = self.thumbnails.unfiltered_len();
         let status_text = row![
-            row![icons::eye(), text(format!(" {watches}"))],
+            tooltip(
+                row![icons::eye(), text(format!(" {watches}"))],
+                container(text(watches_caption(self.thumbnails.watches())).size(14))
+                    .padding(4)
+                    .style(container::rounded_box),
+                tooltip::Position::Top,
+            ),
             row![icons::grid(), text(format!(" {item_count}"))],
             row![icons::search(), text(format!(" {filtered}"))],
             row![icons::bookmark(), text(format!(" {marked}"))],
             row![icons::trash(), text(format!(" {to_delete}"))],
+            text(format!("reviewed {reviewed}/{item_count}")),
         ]
         .spacing(16);
 
         let selection_text = if let Some(thumbnail) = self.thumbnails.selected() {
-            let filename = thumbnail.filename();
-            let size = thumbnail.size().unwrap_or_default() / 1024;
+            let filename = thumbnail.display_name();
+            let number_format = self.settings.number_format;
+            let size = number_format.format_size(thumbnail.size().unwrap_or_default());
             let sample_format = thumbnail.sample_format();
             let sample_count = thumbnail.sample_count();
+            let count_text = number_format.format(sample_count);
             let center_freq = thumbnail.center_freq() / 1_000_000.0;
-            let sample_rate = thumbnail.sample_rate() / 1_000.0;
-            let duration = thumbnail.sample_count() as f64 / thumbnail.sample_rate();
+            let sample_rate = match thumbnail.sample_rate() {
+                rate if rate > 0.0 => format!("{} kHz", rate / 1_000.0),
+                _ => "— kHz".into(),
+            };
+            let duration = match plot_ffi::duration(sample_count, thumbnail.sample_rate()) {
+                Some(secs) => format!("{secs:.2} s"),
+                None => "— s".into(),
+            };
+            let clipped: Element<Message> = match clipped_over(thumbnail, self.settings.clipping_warning) {
+                Some(clipped) => container(text(format!("clipped {}", format_percent(clipped))))
+                    .padding([0, 4])
+                    .style(container::danger)
+                    .into(),
+                None => Space::new(0, 0).into(),
+            };
             row![
                 row![icons::file(), text(format!(" {filename}"))],
-                row![icons::drive(), text(format!(" {size} kB  {sample_format}"))],
-                row![icons::resize_horizontal(), text(format!(" {sample_count} S"))],
-                row![icons::clock(), text(format!(" {duration:.2} s"))],
+                row![icons::drive(), text(format!(" {size}  {sample_format}"))],
+                row![icons::resize_horizontal(), text(format!(" {count_text} S"))],
+                row![icons::clock(), text(format!(" {duration}"))],
                 row![icons::signal(), text(format!(" {center_freq} MHz"))],
-                row![icons::gauge(), text(format!(" {sample_rate} kHz"))],
+                row![icons::gauge(), text(format!(" {sample_rate}"))],
+                clipped,
             ]
             .spacing(16)
         } else {
@@ -754,18 +2942,33 @@ impl Viewer {
                             text("Hotkeys:"),
                             dt_text("o", "open files"),
                             dt_text("O", "open and watch folder"),
+                            dt_text("^V", "open pasted path"),
                             dt_text("x", "clear list and watches"),
                             dt_text("DEL", "remove item"),
+                            dt_text("F2", "rename file"),
+                            dt_text("⇧F2", "set display name"),
                             dt_text("d", "mark file for delete"),
                             dt_text("f", "mark file for move"),
+                            dt_text("i", "mark file reviewed"),
+                            dt_text("I", "show only unreviewed"),
                             dt_text("D", "delete marked"),
                             dt_text("M", "move marked"),
+                            dt_text("L", "show deleted files"),
+                            dt_text("c", "overview of all files"),
+                            dt_text("H", "hex peek of file bytes"),
+                            dt_text("V", "verify all files decode"),
+                            dt_text("A", "compare thumbnail options"),
                             dt_text("SPACE", "toggle viewer"),
                             dt_text("l", "toggle thumbnail size"),
                             dt_text("z", "toggle viewer size"),
+                            dt_text("b", "cycle gallery, split and viewer"),
+                            dt_text("k", "toggle compare pane"),
+                            dt_text("K", "compare pane at the editor time scale"),
                             dt_text("s", "focus filter/search"),
+                            dt_text("S", "session statistics"),
                             dt_text("q", "quit app"),
                             dt_text("h", "toggle this help"),
+                            dt_text(",", "toggle settings"),
                             dt_text("↑↓←→", "move selection"),
                             dt_text("⤒⤓", "move first / last"),
                         ]
@@ -774,19 +2977,36 @@ impl Viewer {
                             text(""),
                             text("Viewer hotkeys:"),
                             dt_text("ESC", "close viewer"),
-                            dt_text("SPACE", "toggle viewer"),
+                            dt_text("SPACE", "toggle viewer (see settings)"),
+                            dt_text("u", "toggle toolbars"),
+                            dt_text("TAB", "focus toolbar control (⇧ back)"),
+                            dt_text("↑↓ ↵", "change focused control"),
                             dt_text("+", "zoom in"),
                             dt_text("-", "zoom out"),
                             dt_text("0", "reset zoom"),
+                            dt_text("1–9", "zoom presets"),
+                            dt_text("r", "pan to start, keep zoom"),
+                            dt_text("e", "export marker to cursor samples"),
+                            dt_text("[ ]", "previous / next FFT window"),
+                            dt_text("n", "toggle per-column normalization"),
+                            dt_text("^C", "copy marker to cursor levels as CSV"),
+                            dt_text("^E", "save marker to cursor levels as CSV"),
+                            dt_text("t", "copy tuning command"),
+                            dt_text("g", "toggle sample index grid"),
+                            dt_text("p", "toggle power histogram"),
+                            dt_text("w", "fit width (time or frequency)"),
+                            dt_text("W", "fit height (frequency or time)"),
                             text(""),
                             text("Viewer mouse controls:"),
                             dt2_text("Scroll Wheel", "zoom"),
                             dt2_text("Horizontal Scroll", "pan"),
                             dt2_text("Click+Drag", "pan"),
                             dt2_text("Middle Click", "zoom in"),
+                            dt2_text("Middle Drag", "measure"),
                             dt2_text("Right Click", "zoom out"),
                             dt2_text("Hold Shift", "measure"),
-                            dt2_text("Shift+Click", "set a marker"),
+                            dt2_text("Shift+Click", "add / remove a marker"),
+                            dt2_text("Shift+ESC", "clear all markers"),
                         ]
                         .padding(20)
                     ],
@@ -801,65 +3021,609 @@ impl Viewer {
         .center(Length::Fill)
     }
 
-    fn thumbnail_style(&self, index: usize) -> fn(&Theme, button::Status) -> button::Style {
-        if index == self.thumbnails.selection() {
-            button::primary
-        } else {
-            let thumbnail = self.thumbnails.get(index).unwrap();
-            if thumbnail.has_delete() {
-                button::danger
-            } else if thumbnail.has_mark() {
-                button::success
-            } else {
-                button::text
-            }
-        }
-    }
+    fn view_settings(&self) -> Container<Message> {
+        let options_empty_gallery = pick_list(
+            EmptyGallery::VARIANTS,
+            Some(self.settings.empty_gallery),
+            Message::PickEmptyGallery,
+        );
 
-    fn thumbnail_text_style(&self, index: usize) -> fn(&Theme) -> container::Style {
-        let thumbnail = self.thumbnails.get(index).unwrap();
-        if thumbnail.has_delete() {
-            container::danger
-        } else if thumbnail.has_mark() {
-            container::success
-        } else {
-            container::transparent
-        }
-    }
+        let options_refresh =
+            pick_list(RefreshInterval::VARIANTS, Some(self.settings.refresh), Message::PickRefresh);
 
-    fn view_thumbnails(&self) -> Container<Message> {
-        //let thumbnails: Vec<iced::Element<'_, Message>> = vec![];
-        let thumbnails = self.thumbnails.iter().enumerate().map(|(index, thumbnail)|
-                // TODO: mouse_area for double_click?
-                button(column![
-                    image(thumbnail.handle())
-                        .filter_method(image::FilterMethod::Nearest),
-                    container(
-                    text(thumbnail.filename())
-                        .size(14)
-                        .wrapping(text::Wrapping::Glyph),
-                    )
-                    .width(Length::Fill)
-                    .height(Length::Fill)
-                    .style(self.thumbnail_text_style(index))
-                ])
-                .on_press(Message::OpenThumbnail(index))
-                .padding(2)
-                //.style(button::text)
-                .style(self.thumbnail_style(index))
-                .into());
+        let options_startup =
+            pick_list(Startup::VARIANTS, Some(self.settings.startup), Message::PickStartup);
 
-        let t_width = self.thumbnail_size;
-        let t_height = self.thumbnail_size + GRID_TEXT_HEIGHT as u32;
+        let options_gallery_limit = pick_list(
+            GalleryLimit::VARIANTS,
+            Some(self.settings.gallery_limit),
+            Message::PickGalleryLimit,
+        );
 
-        // // width = n * (cell + spacing) - spacing, given n > 0
-        // let cells_per_row = ((self.scrollable_available_width + GRID_SPACING) / (t_width as f32 + GRID_SPACING)).ceil() as usize;
+        let options_eviction =
+            pick_list(Eviction::VARIANTS, Some(self.settings.eviction), Message::PickEviction);
+
+        let options_supersample = pick_list(
+            Supersample::VARIANTS,
+            Some(self.settings.supersample),
+            Message::PickSupersample,
+        );
+
+        let options_render_timeout = pick_list(
+            RenderTimeout::VARIANTS,
+            Some(self.settings.render_timeout),
+            Message::PickRenderTimeout,
+        );
+
+        let options_guide_color = pick_list(
+            GuideColor::VARIANTS,
+            Some(self.settings.guide_color),
+            Message::PickGuideColor,
+        );
+
+        let options_guide_filter = pick_list(
+            GuideFilter::VARIANTS,
+            Some(self.settings.guide_filter),
+            Message::PickGuideFilter,
+        );
+
+        let options_space_action = pick_list(
+            SpaceAction::VARIANTS,
+            Some(self.settings.space_action),
+            Message::PickSpaceAction,
+        );
+
+        let options_select_action = pick_list(
+            SelectAction::VARIANTS,
+            Some(self.settings.select_action),
+            Message::PickSelectAction,
+        );
+
+        let options_selection_ends = pick_list(
+            SelectionEnds::VARIANTS,
+            Some(self.settings.selection_ends),
+            Message::PickSelectionEnds,
+        );
+
+        let options_folder_stride = pick_list(
+            FolderStride::VARIANTS,
+            Some(self.settings.folder_stride),
+            Message::PickFolderStride,
+        );
+
+        let options_gallery_open = pick_list(
+            GalleryOpen::VARIANTS,
+            Some(self.settings.gallery_open),
+            Message::PickGalleryOpen,
+        );
+
+        let options_session_log = pick_list(
+            SessionLog::VARIANTS,
+            Some(self.settings.session_log),
+            Message::PickSessionLog,
+        );
+
+        let options_rate_split = pick_list(
+            RateSplit::VARIANTS,
+            Some(self.settings.rate_split),
+            Message::PickRateSplit,
+        );
+
+        let options_narrow_orientation = pick_list(
+            Orientation::VARIANTS,
+            Some(self.settings.narrow_orientation),
+            Message::PickNarrowOrientation,
+        );
+
+        let options_wide_orientation = pick_list(
+            Orientation::VARIANTS,
+            Some(self.settings.wide_orientation),
+            Message::PickWideOrientation,
+        );
+
+        let options_bandwidth_estimate = pick_list(
+            BandwidthEstimate::VARIANTS,
+            Some(self.settings.bandwidth_estimate),
+            Message::PickBandwidthEstimate,
+        );
+
+        let options_folder_newest = pick_list(
+            FolderNewest::VARIANTS,
+            Some(self.settings.folder_newest),
+            Message::PickFolderNewest,
+        );
+
+        let options_folder_depth = pick_list(
+            FolderDepth::VARIANTS,
+            Some(self.settings.folder_depth),
+            Message::PickFolderDepth,
+        );
+
+        let options_ui_scale = pick_list(
+            UiScale::VARIANTS,
+            Some(self.settings.ui_scale),
+            Message::PickUiScale,
+        );
+
+        let options_mark_advance = pick_list(
+            MarkAdvance::VARIANTS,
+            Some(self.settings.mark_advance),
+            Message::PickMarkAdvance,
+        );
+
+        let options_marker_snap = pick_list(
+            MarkerSnap::VARIANTS,
+            Some(self.settings.marker_snap),
+            Message::PickMarkerSnap,
+        );
+
+        let options_time_scrollbar = pick_list(
+            TimeScrollbar::VARIANTS,
+            Some(self.settings.time_scrollbar),
+            Message::PickTimeScrollbar,
+        );
+
+        let options_option_apply = pick_list(
+            OptionApply::VARIANTS,
+            Some(self.settings.option_apply),
+            Message::PickOptionApply,
+        );
+
+        let options_thumbnail_background = pick_list(
+            ThumbnailBackground::VARIANTS,
+            Some(self.settings.thumbnail_background),
+            Message::PickThumbnailBackground,
+        );
+
+        let options_level_unit = pick_list(
+            LevelUnit::VARIANTS,
+            Some(self.settings.level_unit),
+            Message::PickLevelUnit,
+        );
+
+        let options_clipping_warning = pick_list(
+            ClippingWarning::VARIANTS,
+            Some(self.settings.clipping_warning),
+            Message::PickClippingWarning,
+        );
+
+        let options_watch_throttle = pick_list(
+            WatchThrottle::VARIANTS,
+            Some(self.settings.watch_throttle),
+            Message::PickWatchThrottle,
+        );
+
+        let options_sort_order = pick_list(
+            SortOrder::VARIANTS,
+            Some(self.settings.sort_order),
+            Message::PickSortOrder,
+        );
+
+        let options_confirm_delete = pick_list(
+            ConfirmThreshold::VARIANTS,
+            Some(self.settings.confirm_delete),
+            Message::PickConfirmDelete,
+        );
+
+        let options_confirm_move = pick_list(
+            ConfirmThreshold::VARIANTS,
+            Some(self.settings.confirm_move),
+            Message::PickConfirmMove,
+        );
+
+        let options_number_format = pick_list(
+            NumberFormat::VARIANTS,
+            Some(self.settings.number_format),
+            Message::PickNumberFormat,
+        );
+
+        let options_mask_opacity = pick_list(
+            MaskOpacity::VARIANTS,
+            Some(self.settings.mask_opacity),
+            Message::PickMaskOpacity,
+        );
+
+        let options_mask_colormap = pick_list(
+            MaskColormap::VARIANTS,
+            Some(self.settings.mask_colormap),
+            Message::PickMaskColormap,
+        );
+
+        let options_initial_view = pick_list(
+            InitialView::VARIANTS,
+            Some(self.settings.initial_view),
+            Message::PickInitialView,
+        );
+
+        let options_review_on_open = pick_list(
+            ReviewOnOpen::VARIANTS,
+            Some(self.settings.review_on_open),
+            Message::PickReviewOnOpen,
+        );
+
+        container(
+            container(
+                column![
+                    text("Settings").size(20).style(text::primary),
+                    text(""),
+                    setting_row("When the gallery empties", options_empty_gallery),
+                    setting_row("Interface scale", options_ui_scale),
+                    setting_row("Rescan folders every", options_refresh),
+                    setting_row("On startup open", options_startup),
+                    setting_row("Limit watched gallery to", options_gallery_limit),
+                    setting_row("Drop first", options_eviction),
+                    setting_row("Load from folders", options_folder_stride),
+                    setting_row("Keep from each folder", options_folder_newest),
+                    setting_row("Read subfolders", options_folder_depth),
+                    setting_row("Space in editor", options_space_action),
+                    setting_row("Confirm delete", options_confirm_delete),
+                    setting_row("Confirm move", options_confirm_move),
+                    setting_row("Watch throttle", options_watch_throttle),
+                    setting_row("Selecting a file", options_select_action),
+                    setting_row("At the gallery ends", options_selection_ends),
+                    setting_row("After flagging a file", options_mark_advance),
+                    setting_row("Opening a gallery file", options_gallery_open),
+                    setting_row("Session statistics on quit", options_session_log),
+                    setting_row(
+                        "External tool",
+                        text_input("inspectrum {path}", &self.settings.external_tool)
+                            .on_input(Message::ExternalTool)
+                            .on_submit(Message::ExternalToolSubmit)
+                            .width(400)
+                    ),
+                    setting_row("Opening a file", options_review_on_open),
+                    setting_row("Guides overlay", options_guide_filter),
+                    setting_row("Guides color", options_guide_color),
+                    setting_row("View option changes", options_option_apply),
+                    setting_row("Time scrollbar", options_time_scrollbar),
+                    setting_row("Shift+Click marker at", options_marker_snap),
+                    setting_row("Occupied bandwidth in infobar", options_bandwidth_estimate),
+                    setting_row("Orientation by sample rate, split at", options_rate_split),
+                    setting_row("Orientation below the split", options_narrow_orientation),
+                    setting_row("Orientation above the split", options_wide_orientation),
+                    setting_row(
+                        "Timeline threshold",
+                        slider(0.0..=1.0, self.settings.timeline_threshold, Message::TimelineThreshold)
+                            .on_release(Message::TimelineThresholdRelease)
+                            .step(0.05)
+                            .width(200)
+                    ),
+                    setting_row(
+                        "Tuning command (t to copy)",
+                        text_input("{center} {rate} {samples} {path}", &self.settings.tuning_template)
+                            .on_input(Message::TuningTemplate)
+                            .on_submit(Message::TuningTemplateSubmit)
+                            .width(400)
+                    ),
+                    setting_row(
+                        "Thumbnail spacing",
+                        slider(0.0..=40.0, self.settings.grid_spacing, Message::GridSpacing)
+                            .on_release(Message::SettingsSliderRelease)
+                            .step(1.0)
+                            .width(200)
+                    ),
+                    setting_row(
+                        "Thumbnail padding",
+                        slider(0.0..=10.0, self.settings.thumbnail_padding, Message::ThumbnailPadding)
+                            .on_release(Message::SettingsSliderRelease)
+                            .step(1.0)
+                            .width(200)
+                    ),
+                    setting_row("Thumbnail supersampling (new files)", options_supersample),
+                    setting_row("Detection mask opacity", options_mask_opacity),
+                    setting_row("Detection mask colors", options_mask_colormap),
+                    setting_row("Numbers", options_number_format),
+                    setting_row("Gallery order", options_sort_order),
+                    setting_row("Clipping warning", options_clipping_warning),
+                    setting_row("Level unit", options_level_unit),
+                    setting_row("Initial view on open", options_initial_view),
+                    setting_row(
+                        "Zoom presets on keys 1–9 (samples per pixel)",
+                        text_input("1 4 16 64", &self.zoom_presets_text)
+                            .on_input(Message::ZoomPresetsInput)
+                            .on_submit(Message::ZoomPresetsSubmit)
+                            .width(400)
+                    ),
+                    setting_row(
+                        "dBm calibration offset (dBm = dBFS + offset)",
+                        text_input("dB", &self.dbm_offset_text)
+                            .on_input(Message::DbmOffsetInput)
+                            .on_submit(Message::DbmOffsetSubmit)
+                            .width(80)
+                    ),
+                    setting_row("Thumbnail background", options_thumbnail_background),
+                    setting_row("Give up rendering a thumbnail after", options_render_timeout),
+                    setting_row(
+                        "Thumbnail cache",
+                        button("Clear").on_press(Message::ClearThumbnailCache)
+                    ),
+                ]
+                .spacing(10)
+                .align_x(Alignment::Center),
+            )
+            .padding(50)
+            .style(container::rounded_box),
+        )
+        .center(Length::Fill)
+    }
+
+    fn view_format_prompt<'a>(&self, path: &'a Path) -> Container<'a, Message> {
+        let filename = path.file_name().unwrap_or_default().to_string_lossy();
+        let formats = pick_list(SAMPLE_FORMAT, None::<&'static str>, Message::OpenAsFormat)
+            .placeholder("Sample format");
+
+        container(
+            container(
+                column![
+                    text("Open as format").size(20).style(text::primary),
+                    text(""),
+                    text(format!("\"{filename}\" has no known I/Q extension")),
+                    formats,
+                    button("Cancel").style(button::secondary).on_press(Message::CloseEditor),
+                ]
+                .spacing(10)
+                .align_x(Alignment::Center),
+            )
+            .padding(50)
+            .style(container::rounded_box),
+        )
+        .center(Length::Fill)
+    }
+
+    fn view_rename(&self) -> Container<Message> {
+        let name = self.rename.as_deref().unwrap_or_default();
+        let error = self.rename_error.as_deref().unwrap_or_default();
+
+        container(
+            container(
+                column![
+                    text("Rename file").size(20).style(text::primary),
+                    text(""),
+                    text_input("File name", name)
+                        .id("rename")
+                        .on_input(Message::RenameChanged)
+                        .on_submit(Message::RenameSubmit)
+                        .width(400),
+                    text(error).style(text::danger),
+                    row![
+                        button("Cancel").style(button::secondary).on_press(Message::CloseEditor),
+                        button("Rename").on_press(Message::RenameSubmit),
+                    ]
+                    .spacing(10),
+                ]
+                .spacing(10)
+                .align_x(Alignment::Center),
+            )
+            .padding(50)
+            .style(container::rounded_box),
+        )
+        .center(Length::Fill)
+    }
+
+    fn view_alias<'a>(&self, alias: &'a str) -> Container<'a, Message> {
+        let filename = self.thumbnails.selected().map(|t| t.filename().into_owned()).unwrap_or_default();
+
+        container(
+            container(
+                column![
+                    text("Display name").size(20).style(text::primary),
+                    text(""),
+                    text(filename),
+                    text_input("Empty shows the file name", alias)
+                        .id("alias")
+                        .on_input(Message::AliasChanged)
+                        .on_submit(Message::AliasSubmit)
+                        .width(400),
+                    row![
+                        button("Cancel").style(button::secondary).on_press(Message::CloseEditor),
+                        button("Set").on_press(Message::AliasSubmit),
+                    ]
+                    .spacing(10),
+                ]
+                .spacing(10)
+                .align_x(Alignment::Center),
+            )
+            .padding(50)
+            .style(container::rounded_box),
+        )
+        .center(Length::Fill)
+    }
+
+    fn view_session_stats(&self) -> Container<Message> {
+        let stats = self.thumbnails.session_stats();
+        let stat_row = |term: &'static str, count: usize| -> Element<'static, Message> {
+            row![
+                container(text(term).style(text::success)).center_x(150),
+                text(format!("{count} files")),
+            ]
+            .into()
+        };
+
+        container(
+            container(
+                column![
+                    text("Session statistics").size(20).style(text::primary),
+                    text(""),
+                    stat_row("Opened", stats.opened),
+                    stat_row("Marked", stats.marked),
+                    stat_row("Deleted", stats.deleted),
+                    stat_row("Moved", stats.moved),
+                    text(""),
+                    text("Counted since the start or the last clear").size(12),
+                ]
+                .align_x(Alignment::Center),
+            )
+            .padding(50)
+            .style(container::rounded_box),
+        )
+        .center(Length::Fill)
+    }
+
+    fn view_deleted(&self) -> Container<Message> {
+        let deleted = self.thumbnails.deleted();
+        let entries: Element<Message> = if deleted.is_empty() {
+            text("No files deleted in this session").into()
+        } else {
+            let rows = deleted.iter().rev().map(|(path, time)| {
+                let filename = path.file_name().unwrap_or_default().to_string_lossy();
+                let folder = path.parent().unwrap_or(path).to_string_lossy();
+                row![
+                    text(format_utc_time(*time)).style(text::success),
+                    text(filename.into_owned()),
+                    text(folder.into_owned()).size(12),
+                ]
+                .spacing(16)
+                .align_y(Alignment::Center)
+                .into()
+            });
+            scrollable(Column::with_children(rows).spacing(4)).into()
+        };
+
+        container(
+            container(
+                column![
+                    text("Deleted files").size(20).style(text::primary),
+                    text(""),
+                    entries,
+                ]
+                .align_x(Alignment::Center),
+            )
+            .padding(50)
+            .style(container::rounded_box),
+        )
+        .center(Length::Fill)
+    }
+
+    /// The editor toolbar takes the keyboard focus while it is shown,
+    /// unless a text input is focused or an overlay is open.
+    fn toolbar_focusable(&self) -> bool {
+        self.screen == Screen::Editor
+            && self.plot.is_some()
+            && self.editor_chrome_visible
+            && !self.text_focused
+            && !self.has_overlay()
+    }
+
+    /// Tests if a dialog or panel is shown over the content, see `view()`.
+    fn has_overlay(&self) -> bool {
+        self.rename.is_some()
+            || self.alias_edit.is_some()
+            || !self.format_prompt.is_empty()
+            || self.show_help
+            || self.show_settings
+            || self.show_deleted
+            || self.show_overview
+            || self.show_session_stats
+            || self.hex_peek.is_some()
+            || self.verify_report.is_some()
+            || self.compare.is_some()
+    }
+
+    /// Steps the value of the focused toolbar control by `delta` variants, toggles the buttons.
+    fn step_toolbar(&mut self, delta: isize) -> Task<Message> {
+        fn step<T: Copy + Default + PartialEq>(variants: &[T], current: Option<T>, delta: isize) -> T {
+            let current = current.unwrap_or_default();
+            let index = variants.iter().position(|&v| v == current).unwrap_or(0) as isize;
+            variants[(index + delta).clamp(0, variants.len() as isize - 1) as usize]
+        }
+        let message = match self.toolbar_focus {
+            Some(0) => Message::PickFftn(step(FftSize::VARIANTS, self.opts_fftn, delta)),
+            Some(1) => Message::PickWindowf(step(WindowFunctions::VARIANTS, self.opts_windowf, delta)),
+            Some(2) => Message::PickGain(step(DbGain::VARIANTS, self.opts_gain, delta)),
+            Some(3) => Message::PickRange(step(DbRange::VARIANTS, self.opts_range, delta)),
+            Some(4) => Message::PickColormap(step(Colormap::VARIANTS, self.opts_colormap, delta)),
+            Some(5) => Message::PickOrientation(step(Orientation::VARIANTS, self.opts_orientation, delta)),
+            // the horizontal spectrogram always runs left to right
+            Some(6) if self.opts_orientation.unwrap_or_default() == Orientation::Spectrogram => return Task::none(),
+            Some(6) => Message::PickTimeDirection(step(
+                TimeDirection::VARIANTS,
+                Some(self.time_direction()),
+                delta,
+            )),
+            Some(7) => Message::PickComponent(step(Component::VARIANTS, self.opts_component, delta)),
+            Some(8) => Message::PickEnhance(step(Enhance::VARIANTS, self.opts_enhance, delta)),
+            Some(9) => Message::ToggleDcNotch,
+            Some(10) => Message::ToggleReferenceLevels,
+            _ => return Task::none(),
+        };
+        self.update(message)
+    }
+
+    fn toolbar_focus_style(&self, index: usize) -> fn(&Theme) -> container::Style {
+        if self.toolbar_focus == Some(index) && self.toolbar_focusable() {
+            focus_ring
+        } else {
+            container::transparent
+        }
+    }
+
+    fn thumbnail_style(&self, index: usize) -> fn(&Theme, button::Status) -> button::Style {
+        if index == self.thumbnails.selection() {
+            button::primary
+        } else {
+            let thumbnail = self.thumbnails.get(index).unwrap();
+            if thumbnail.has_delete() {
+                button::danger
+            } else if thumbnail.has_mark() {
+                button::success
+            } else {
+                button::text
+            }
+        }
+    }
+
+    fn thumbnail_text_style(&self, index: usize) -> fn(&Theme) -> container::Style {
+        let thumbnail = self.thumbnails.get(index).unwrap();
+        if thumbnail.has_delete() || thumbnail.has_failed() {
+            container::danger
+        } else if thumbnail.has_mark() {
+            container::success
+        } else {
+            container::transparent
+        }
+    }
+
+    fn view_thumbnails(&self) -> Container<Message> {
+        //let thumbnails: Vec<iced::Element<'_, Message>> = vec![];
+        // the file open in the editor, marked apart from the selection
+        let open_path = self.plot.as_ref().filter(|_| self.screen == Screen::Editor).map(Plot::path);
+        let band_scan = self.settings.sort_order == SortOrder::BandScan;
+        let thumbnails = self.thumbnails.iter().enumerate().map(|(index, thumbnail)|
+                // TODO: mouse_area for double_click?
+                container(button(column![
+                    container(
+                        image(thumbnail.handle())
+                            .filter_method(image::FilterMethod::Nearest)
+                            .width(Length::Fill)
+                            .height(Length::Fill)
+                    )
+                    .style(thumbnail_background(self.settings.thumbnail_background)),
+                    container(if band_scan {
+                        text(band_caption(thumbnail)).size(14).into()
+                    } else {
+                        thumbnail_caption(thumbnail, self.settings.clipping_warning)
+                    })
+                    .width(Length::Fill)
+                    .style(self.thumbnail_text_style(index))
+                ])
+                .on_press(Message::OpenThumbnail(index))
+                .padding(if band_scan { 0.0 } else { self.settings.thumbnail_padding })
+                //.style(button::text)
+                .style(self.thumbnail_style(index)))
+                .style(if open_path == Some(thumbnail.path()) { open_ring } else { container::transparent })
+                .into());
+
+        let t_width = self.thumbnail_size;
+        let t_height = self.thumbnail_size + GRID_TEXT_HEIGHT as u32;
+
+        // // width = n * (cell + spacing) - spacing, given n > 0
+        // let cells_per_row = ((self.scrollable_available_width + spacing) / (t_width as f32 + spacing)).ceil() as usize;
         // println!("{} / {} -> {}", self.scrollable_available_width, t_width, cells_per_row);
 
         let gallery = grid(thumbnails)
             .fluid(t_width)
             .height(grid::aspect_ratio(t_width, t_height))
-            .spacing(GRID_SPACING);
+            .spacing(self.grid_spacing());
 
         container(
             scrollable(gallery)
@@ -870,7 +3634,139 @@ impl Viewer {
         .padding(10)
     }
 
+    fn view_hex_peek<'a>(&self, hex_peek: &'a str) -> Container<'a, Message> {
+        let filename = self.thumbnails.selected().map(|t| t.filename().into_owned()).unwrap_or_default();
+
+        container(
+            container(
+                column![
+                    text(filename).size(20).style(text::primary),
+                    scrollable(text(hex_peek).font(iced::Font::MONOSPACE).size(13)).spacing(10),
+                ]
+                .spacing(10)
+                .align_x(Alignment::Center),
+            )
+            .padding(20)
+            .style(container::rounded_box),
+        )
+        .padding(40)
+        .center(Length::Fill)
+    }
+
+    fn view_verify_report<'a>(&self, report: &'a [(PathBuf, verify::Verdict)]) -> Container<'a, Message> {
+        let failed = report.iter().filter(|(_, v)| matches!(v, verify::Verdict::Fail(_))).count();
+        let warned = report.iter().filter(|(_, v)| matches!(v, verify::Verdict::Warn(_))).count();
+
+        let rows = report.iter().map(|(path, verdict)| {
+            let filename = path.file_name().unwrap_or_default().to_string_lossy();
+            let style = match verdict {
+                verify::Verdict::Pass => text::success,
+                verify::Verdict::Warn(_) => text::warning,
+                verify::Verdict::Fail(_) => text::danger,
+            };
+            row![text(verdict.to_string()).style(style).width(300), text(filename.into_owned())]
+                .spacing(10)
+                .into()
+        });
+
+        container(
+            container(
+                column![
+                    text(format!("Verified {} files, {failed} failed, {warned} warnings", report.len()))
+                        .size(20)
+                        .style(text::primary),
+                    scrollable(Column::with_children(rows).spacing(2)).spacing(10),
+                ]
+                .spacing(10)
+                .align_x(Alignment::Center),
+            )
+            .padding(20)
+            .style(container::rounded_box),
+        )
+        .padding(40)
+        .center(Length::Fill)
+    }
+
+    /// Side by side renders of the selected file's thumbnail with two sets of options.
+    fn view_compare<'a>(&self, compare: &'a [(ThumbnailConfig, image::Handle); 2]) -> Container<'a, Message> {
+        let sides = compare.iter().enumerate().map(|(side, (config, handle))| {
+            column![
+                text(if side == 0 { "A" } else { "B" }).size(20),
+                image(handle.clone()).width(256).height(256),
+                pick_list(Colormap::VARIANTS, Some(config.colormap), move |val| {
+                    Message::PickCompareColormap(side, val)
+                }),
+                pick_list(FftSize::VARIANTS, Some(config.fftn), move |val| {
+                    Message::PickCompareFftn(side, val)
+                }),
+                button("Use for thumbnails").on_press(Message::ApplyCompare(side)),
+            ]
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .into()
+        });
+
+        container(
+            container(
+                column![
+                    text("Compare thumbnail options").size(20).style(text::primary),
+                    text(""),
+                    row(sides).spacing(40),
+                    text("Applies to newly loaded files").size(12),
+                ]
+                .spacing(10)
+                .align_x(Alignment::Center),
+            )
+            .padding(50)
+            .style(container::rounded_box),
+        )
+        .center(Length::Fill)
+    }
+
+    /// Contact sheet of all filtered items at tiny size, a click opens the file.
+    fn view_overview(&self) -> Container<Message> {
+        let tiles = self.thumbnails.iter().enumerate().map(|(index, thumbnail)| {
+            button(image(thumbnail.handle()).filter_method(image::FilterMethod::Linear))
+                .on_press(Message::OverviewPress(index))
+                .padding(1)
+                .style(self.thumbnail_style(index))
+                .into()
+        });
+
+        let sheet = grid(tiles)
+            .fluid(OVERVIEW_TILE_SIZE)
+            .height(grid::aspect_ratio(OVERVIEW_TILE_SIZE, OVERVIEW_TILE_SIZE))
+            .spacing(2);
+
+        container(
+            container(
+                column![
+                    text(format!("Overview of {} files", self.thumbnails.len()))
+                        .size(20)
+                        .style(text::primary),
+                    scrollable(sheet).spacing(10),
+                ]
+                .spacing(10)
+                .align_x(Alignment::Center),
+            )
+            .padding(20)
+            .style(container::rounded_box),
+        )
+        .padding(40)
+        .center(Length::Fill)
+    }
+
     fn view_menubar(&self) -> Container<Message> {
+        // only shown if folders were loaded with a stride
+        let load_all: Element<Message> = if self.thumbnails.is_strided() {
+            button(row![icons::folder(), " Load all"])
+                .style(button::text)
+                .on_press(Message::Busy(Box::new(Message::LoadAll)))
+                .into()
+        } else {
+            Space::new(0, 0).into()
+        };
+
         let menubar = row![
             button(row![icons::folder(), " Open folder"])
                 .style(button::text)
@@ -884,10 +3780,28 @@ impl Viewer {
             button(row![icons::help(), " Help"])
                 .style(button::text)
                 .on_press(Message::ShowHelp),
+            button(row![icons::gauge(), " Settings"])
+                .style(button::text)
+                .on_press(Message::ShowSettings),
             horizontal_space(),
+            load_all,
+            pick_list(SortOrder::VARIANTS, Some(self.settings.sort_order), Message::PickSortOrder)
+                .text_size(14),
+            button(if self.settings.sort_descending { "↓" } else { "↑" })
+                .style(button::text)
+                .on_press(Message::ToggleSortDescending),
             text_input("Filter...", self.thumbnails.filter())
                 .id("filter")
                 .on_input(Message::FilterChanged),
+            button(row![icons::bookmark(), text(format!(" {}", self.thumbnails.count_marked()))])
+                .style(if self.thumbnails.only_marked() { button::success } else { button::text })
+                .on_press(Message::ToggleOnlyMarked),
+            button(row![icons::trash(), text(format!(" {}", self.thumbnails.count_to_delete()))])
+                .style(if self.thumbnails.only_delete() { button::danger } else { button::text })
+                .on_press(Message::ToggleOnlyDelete),
+            button(" Unreviewed")
+                .style(if self.thumbnails.only_unreviewed() { button::success } else { button::text })
+                .on_press(Message::ToggleOnlyUnreviewed),
             horizontal_space(),
             container(slider(
                 64.0..=256.0,
@@ -914,6 +3828,51 @@ impl Viewer {
         column![self.view_menubar(), content,].align_x(Center)
     }
 
+    /// Sample index, time offset and frequency of each marker, with the delta to the marker before.
+    fn view_marker_table(&self, plot: &Plot) -> Column<'_, Message> {
+        let number_format = self.settings.number_format;
+        let rate = plot.sample_rate();
+        let secs = |samples: f64| match rate {
+            rate if rate > 0.0 => format!("{:.6} s", samples / rate),
+            _ => "— s".into(),
+        };
+        let signed = |delta: i64| {
+            let sign = if delta < 0 { "-" } else { "+" };
+            format!("{sign}{}", number_format.format(delta.unsigned_abs()))
+        };
+        let line = |columns: [String; 7]| {
+            let [index, sample, time, freq, d_sample, d_time, d_freq] = columns;
+            let content = format!("{index:>2} {sample:>14} {time:>12} {freq:>15}  {d_sample:>14} {d_time:>13} {d_freq:>13}");
+            text(content).font(iced::Font::MONOSPACE).size(12).into()
+        };
+
+        let header = ["#", "Sample", "Time", "Frequency", "Δ Sample", "Δ Time", "Δ Frequency"].map(String::from);
+        let mut lines: Vec<Element<Message>> = vec![line(header)];
+        let mut previous: Option<PlotMarker> = None;
+        for (index, marker) in self.markers.iter().enumerate() {
+            let (d_sample, d_time, d_freq) = match previous {
+                Some(prev) => {
+                    let delta = marker.sample as i64 - prev.sample as i64;
+                    let d_time = secs(delta as f64);
+                    let d_freq = format!("{:+.3} kHz", (marker.freq - prev.freq) / 1_000.0);
+                    (signed(delta), d_time, d_freq)
+                }
+                None => Default::default(),
+            };
+            lines.push(line([
+                (index + 1).to_string(),
+                number_format.format(marker.sample),
+                secs(marker.sample as f64),
+                format!("{:.6} MHz", marker.freq / 1_000_000.0),
+                d_sample,
+                d_time,
+                d_freq,
+            ]));
+            previous = Some(*marker);
+        }
+        Column::with_children(lines)
+    }
+
     fn view_editor(&self) -> Column<Message> {
         let options_fftn =
             pick_list(FftSize::VARIANTS, self.opts_fftn, Message::PickFftn).placeholder("FFT N");
@@ -945,23 +3904,85 @@ impl Viewer {
         )
         .placeholder("Orientation");
 
-        let toolbar = row![
-            column![text("FFT window width").size(12), options_fftn].align_x(Alignment::Center),
-            column![text("FFT windowing function").size(12), options_windowf]
-                .align_x(Alignment::Center),
+        // libtriq reverses the vertical layouts only, the horizontal spectrogram runs left to right
+        let options_time_direction: Element<Message> = if self.opts_orientation.unwrap_or_default() == Orientation::Spectrogram {
+            container(text("Left to right").size(14)).padding(5).into()
+        } else {
+            pick_list(TimeDirection::VARIANTS, Some(self.time_direction()), Message::PickTimeDirection).into()
+        };
+
+        let options_enhance = pick_list(
+            Enhance::VARIANTS,
+            self.opts_enhance,
+            Message::PickEnhance,
+        )
+        .placeholder("Enhancement");
+
+        let options_component = pick_list(
+            Component::VARIANTS,
+            self.opts_component,
+            Message::PickComponent,
+        )
+        .placeholder("Component");
+
+        let apply: Element<Message> = if self.options_pending {
             column![
+                text("Pending changes").size(12),
+                button("Apply").style(button::success).on_press(Message::ApplyOptions)
+            ]
+            .align_x(Alignment::Center)
+            .into()
+        } else {
+            Space::new(0, 0).into()
+        };
+
+        let focusable = |index, control| container(control).padding(2).style(self.toolbar_focus_style(index));
+
+        let toolbar = row![
+            focusable(0, column![text("FFT window width").size(12), options_fftn].align_x(Alignment::Center)),
+            focusable(1, column![text("FFT windowing function").size(12), options_windowf].align_x(Alignment::Center)),
+            focusable(2, column![
                 text("Overall gain" /*"(signal amplification)"*/).size(12),
-                options_gain
+                row![
+                    options_gain,
+                    text_input("dB", &self.gain_text)
+                        .on_input(Message::GainInput)
+                        .on_submit(Message::GainSubmit)
+                        .width(60)
+                ]
             ]
-            .align_x(Alignment::Center),
-            column![
+            .align_x(Alignment::Center)),
+            focusable(3, column![
                 text("Gain range" /*"(cut-off to black)"*/).size(12),
-                options_range
+                row![
+                    options_range,
+                    text_input("dB", &self.range_text)
+                        .on_input(Message::RangeInput)
+                        .on_submit(Message::RangeSubmit)
+                        .width(60)
+                ]
             ]
-            .align_x(Alignment::Center),
-            column![text("Color map").size(12), options_colormap].align_x(Alignment::Center),
-            column![text("Display orientation").size(12), options_orientation]
-                .align_x(Alignment::Center),
+            .align_x(Alignment::Center)),
+            focusable(4, column![text("Color map").size(12), options_colormap].align_x(Alignment::Center)),
+            focusable(5, column![text("Display orientation").size(12), options_orientation].align_x(Alignment::Center)),
+            focusable(6, column![text("Time direction").size(12), options_time_direction].align_x(Alignment::Center)),
+            focusable(7, column![text("Display component").size(12), options_component].align_x(Alignment::Center)),
+            focusable(8, column![text("Contrast enhancement").size(12), options_enhance].align_x(Alignment::Center)),
+            focusable(9, column![
+                text("DC spike").size(12),
+                button("Notch")
+                    .style(if self.settings.dc_notch { button::success } else { button::secondary })
+                    .on_press(Message::ToggleDcNotch)
+            ]
+            .align_x(Alignment::Center)),
+            focusable(10, column![
+                text("Reference levels").size(12),
+                button("Lock")
+                    .style(if self.reference_levels.is_some() { button::success } else { button::secondary })
+                    .on_press(Message::ToggleReferenceLevels)
+            ]
+            .align_x(Alignment::Center)),
+            apply,
         ]
         .wrap();
         let toolbar = container(toolbar).padding([0, 10]);
@@ -977,34 +3998,281 @@ impl Viewer {
         .padding(5);
         */
 
-        let infos = self.plot.as_ref().unwrap().infos();
+        let Some(plot) = self.plot.as_ref() else {
+            return column![toolbar];
+        };
+
+        let number_format = self.settings.number_format;
+        let mut infos = plot.infos(self.dbm_offset(), |count| number_format.format(count));
+        if let Some(bandwidth) = self.occupied_bandwidth(plot) {
+            // approximate, spans all signals in the view
+            infos.push(format!("BW ≈ {} ({})", format_hz(bandwidth), self.settings.bandwidth_estimate));
+        }
+        match self.component_handle(plot) {
+            Some(Ok(_)) => infos.push(format!("Showing {}", self.opts_component.unwrap_or_default())),
+            Some(Err(err)) => infos.push(err),
+            None => {}
+        }
+        if self.opts_enhance == Some(Enhance::ColumnNormalize) {
+            infos.push("Per-column normalized (n)".into());
+        }
+        infos.extend(self.window_notice.clone());
+        // the readout keeps its place while the cursor is away
+        // whole dB, the levels are read in steps of about 0.6 dB
+        infos.push(match (self.cursor_level(plot), self.dbm_offset()) {
+            (Some(level), Some(offset)) => format!("Cursor {:.0} dBm", level + offset),
+            (Some(level), None) => format!("Cursor {level:.0} dBFS"),
+            (None, _) => "Cursor —".into(),
+        });
+        if !self.detections.is_empty() {
+            infos.push(format!("{} detections", self.detections.len()));
+        }
+        infos.extend(self.provenance.iter().map(|(label, value)| format!("{label}: {value}")));
         let infobar = infos.into_iter().map(|info| {
             container(text(info).size(14))
                 .style(container::rounded_box)
                 .into()
         });
-        let infobar = row(infobar).spacing(5).padding([5, 10]);
+        let mut infobar = row(infobar).spacing(5).padding([5, 10]);
+        if let Some((gain, range)) = self.reference_levels {
+            let levels = format!("Reference levels {} / {} dB", format_db(gain), format_db(range));
+            infobar = infobar.push(container(text(levels).size(14)).style(container::bordered_box));
+        }
+        if let Some(geolocation) = self.geolocation {
+            let location = row![
+                text(format!("Location: {geolocation}")).size(14),
+                button(text("Show on map").size(12))
+                    .padding([0, 4])
+                    .style(button::secondary)
+                    .on_press(Message::ShowOnMap),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center);
+            infobar = infobar.push(container(location).style(container::rounded_box));
+        }
+        if !self.thumbnails.contains(plot.path()) {
+            infobar = infobar.push(container(text("File removed").size(14)).style(container::danger));
+        }
+
+        let plot_ref = plot;
+        let plot = plotarea(plot)
+            .markers(match self.measure.as_ref() {
+                Some((_, start)) => std::slice::from_ref(start),
+                None => &self.markers,
+            })
+            .cursor(self.cursor)
+            .sample_grid(self.settings.sample_grid)
+            .mask(
+                self.mask_handle(plot_ref),
+                self.settings.mask_opacity.to_value().unwrap_or_default(),
+            )
+            .guides_color(match (self.settings.guide_color, self.settings.guide_color.to_rgb()) {
+                (_, Some(rgb)) => GuidesColor::Fixed(rgb),
+                (GuideColor::Contrast, None) => GuidesColor::Contrast,
+                _ => GuidesColor::Native,
+            })
+            .guides_filter(match self.settings.guide_filter {
+                GuideFilter::Sharp => image::FilterMethod::Nearest,
+                GuideFilter::Smooth => image::FilterMethod::Linear,
+            });
+
+        // a single component covers the spectrogram, the plot keeps the layout and the axes mapping
+        let plot: Element<Message> = match self.component_handle(plot_ref) {
+            Some(Ok(handle)) => {
+                let strip = image(handle)
+                    .filter_method(image::FilterMethod::Nearest)
+                    .content_fit(ContentFit::Fill)
+                    .width(Length::Fill)
+                    .height(Length::Fill);
+                Stack::with_children([plot.into(), strip.into()]).into()
+            }
+            _ => plot.into(),
+        };
 
-        let plot = plotarea(self.plot.as_ref().unwrap())
-            .marker(self.marker)
-            .cursor(self.cursor);
+        // the measurement table over the plot, once there are markers to compare
+        let plot: Element<Message> = if self.markers.len() > 1 {
+            let table = container(self.view_marker_table(plot_ref)).padding(5).style(container::rounded_box);
+            Stack::with_children([plot, container(table).padding(10).into()]).into()
+        } else {
+            plot
+        };
 
         let plot = MouseArea::new(plot)
             .on_press(Message::PlotLeftPress)
-            .on_move_maybe((self.in_click || self.is_shift_pressed).then_some(Message::PlotMove))
+            .on_move(Message::PlotMove)
+            .on_exit(Message::PlotExit)
             .on_release(Message::PlotLeftRelease)
             .on_middle_press(Message::PlotMiddlePress)
+            .on_middle_release(Message::PlotMiddleRelease)
             .on_right_press(Message::PlotRightPress)
             .on_double_click(Message::PlotDoubleClicked)
             .on_scroll(Message::PlotScroll)
             .interaction(mouse::Interaction::Crosshair);
 
-        column![
-            toolbar,
-            //actionbar,
-            infobar,
-            plot,
-        ]
+        // the compare pane next to the plot, panned and zoomed on its own
+        let plot: Element<Message> = match self.compare_pane.as_ref() {
+            Some(compare) => {
+                let filename = compare.path().file_name().unwrap_or_default().to_string_lossy();
+                let caption = match plot_ffi::duration(compare.zoom() as u64, compare.sample_rate()) {
+                    Some(secs) => format!("{filename}  1px = {} smps = {:.3} µs", compare.zoom(), secs * 1e6),
+                    None => format!("{filename}  1px = {} smps", compare.zoom()),
+                };
+                let pane = MouseArea::new(plotarea(compare))
+                    .on_press(Message::ComparePanePress)
+                    .on_move_maybe(self.compare_pane_drag.is_some().then_some(Message::ComparePaneMove))
+                    .on_release(Message::ComparePaneRelease)
+                    .on_scroll(Message::ComparePaneScroll)
+                    .interaction(mouse::Interaction::Grab);
+                row![
+                    plot,
+                    column![container(text(caption).size(14)).padding([0, 5]), pane].width(Length::Fill),
+                ]
+                .spacing(5)
+                .into()
+            }
+            None => plot.into(),
+        };
+
+        let timeline = image(timeline_handle(&self.timeline, self.settings.timeline_threshold))
+            .filter_method(image::FilterMethod::Nearest)
+            .content_fit(ContentFit::Fill)
+            .width(Length::Fill)
+            .height(8);
+        let timeline = MouseArea::new(timeline)
+            .on_press(Message::TimelinePress)
+            .interaction(mouse::Interaction::Pointer);
+
+        if self.editor_chrome_visible {
+            let histogram: Element<Message> = if self.show_power_histogram {
+                let histogram = image(self.power_histogram_handle(plot_ref))
+                    .filter_method(image::FilterMethod::Nearest)
+                    .content_fit(ContentFit::Fill)
+                    .width(Length::Fill)
+                    .height(48);
+                MouseArea::new(histogram)
+                    .on_press(Message::HistogramPress)
+                    .on_move_maybe(self.histogram_drag.is_some().then_some(Message::HistogramMove))
+                    .on_release(Message::HistogramRelease)
+                    .interaction(mouse::Interaction::ResizingHorizontally)
+                    .into()
+            } else {
+                Space::new(0, 0).into()
+            };
+            let scrollbar: Element<Message> = if self.settings.time_scrollbar == TimeScrollbar::Shown {
+                let scrollbar = image(scrollbar_handle(scrollbar_thumb(plot_ref)))
+                    .filter_method(image::FilterMethod::Nearest)
+                    .content_fit(ContentFit::Fill)
+                    .width(Length::Fill)
+                    .height(12);
+                MouseArea::new(scrollbar)
+                    .on_press(Message::ScrollbarPress)
+                    .on_move_maybe(self.scrollbar_drag.is_some().then_some(Message::ScrollbarMove))
+                    .on_release(Message::ScrollbarRelease)
+                    .interaction(mouse::Interaction::Grab)
+                    .into()
+            } else {
+                Space::new(0, 0).into()
+            };
+            column![
+                toolbar,
+                //actionbar,
+                infobar,
+                plot,
+                scrollbar,
+                timeline,
+                histogram,
+            ]
+        } else {
+            // focus mode: the plot fills the editor
+            column![plot]
+        }
+    }
+}
+
+/// Visible time range of the plot as fractions of all samples, at least a sliver wide.
+fn scrollbar_thumb(plot: &Plot) -> (f32, f32) {
+    let count = plot.sample_count().max(1) as f64;
+    let visible = (plot.visible_samples() as f64 / count).clamp(0.005, 1.0);
+    let center = plot.sample_at_pos(plot.width() / 2, plot.height() / 2) as f64 / count;
+    let start = (center - visible / 2.0).clamp(0.0, 1.0 - visible);
+    (start as f32, (start + visible) as f32)
+}
+
+/// Caption of a gallery item, the real file name shows on hover if an alias is set.
+fn thumbnail_caption(thumbnail: &FileItem, clipping_warning: ClippingWarning) -> Element<'_, Message> {
+    let name = match thumbnail.has_reviewed() {
+        true => format!("✓ {}", thumbnail.display_name()),
+        false => thumbnail.display_name().into_owned(),
+    };
+    let caption = text(name)
+        .size(14)
+        .wrapping(text::Wrapping::Glyph);
+    let caption: Element<'_, Message> = if let Some(reason) = read_error_badge(thumbnail) {
+        let badge = container(text(reason).size(12)).padding([0, 4]).style(container::danger);
+        column![badge, caption].into()
+    } else if thumbnail.is_pending() {
+        caption.into()
+    } else if thumbnail.is_empty() {
+        let badge = container(text("empty").size(12)).padding([0, 4]).style(container::danger);
+        column![badge, caption].into()
+    } else if let Some(clipped) = clipped_over(thumbnail, clipping_warning) {
+        let badge = container(text(format!("clipped {}", format_percent(clipped))).size(12))
+            .padding([0, 4])
+            .style(container::danger);
+        column![badge, caption].into()
+    } else {
+        caption.into()
+    };
+    if thumbnail.alias().is_some() {
+        let filename = container(text(thumbnail.filename()).size(14))
+            .padding(4)
+            .style(container::rounded_box);
+        tooltip(caption, filename, tooltip::Position::Bottom).into()
+    } else {
+        caption
+    }
+}
+
+/// Badge text for a file that can't be read, a missing file is not badged.
+fn read_error_badge(thumbnail: &FileItem) -> Option<&'static str> {
+    match thumbnail.read_error()? {
+        std::io::ErrorKind::NotFound => None,
+        std::io::ErrorKind::PermissionDenied => Some("no read permission"),
+        _ => Some("unreadable"),
+    }
+}
+
+/// Frequency span of a band scan tile, from the center frequency and the sample rate.
+fn band_caption(thumbnail: &FileItem) -> String {
+    let (center, rate) = (thumbnail.center_freq(), thumbnail.sample_rate());
+    if center <= 0.0 || !center.is_finite() {
+        return "— MHz".into();
+    }
+    let half = rate.max(0.0) / 2.0;
+    format!("{:.3}–{:.3} MHz", (center - half) / 1_000_000.0, (center + half) / 1_000_000.0)
+}
+
+/// Columns `(start, end)` of the timeline around its loudest column, as long as the level stays
+/// above the `threshold`, with a margin of a quarter of the span on each side.
+fn loudest_span(timeline: &[f32], threshold: f32) -> Option<(usize, usize)> {
+    let peak = (0..timeline.len()).max_by(|&a, &b| timeline[a].total_cmp(&timeline[b]))?;
+    let start = (0..peak).rev().find(|&i| timeline[i] < threshold).map_or(0, |i| i + 1);
+    let end = (peak + 1..timeline.len()).find(|&i| timeline[i] < threshold).unwrap_or(timeline.len());
+    let margin = (end - start).div_ceil(4);
+    Some((start.saturating_sub(margin), (end + margin).min(timeline.len())))
+}
+
+/// Fraction of clipped samples if over the warning threshold, `None` if the warning is off.
+fn clipped_over(thumbnail: &FileItem, clipping_warning: ClippingWarning) -> Option<f32> {
+    let threshold = clipping_warning.to_value()?;
+    thumbnail.clipped_fraction().filter(|&clipped| clipped > threshold)
+}
+
+fn format_percent(fraction: f32) -> String {
+    match fraction * 100.0 {
+        percent if percent < 0.1 => format!("{percent:.2}%"),
+        percent if percent < 10.0 => format!("{percent:.1}%"),
+        percent => format!("{percent:.0}%"),
     }
 }
 
@@ -1024,6 +4292,262 @@ fn dt2_text<'a>(term: &'a str, definition: &'a str) -> Element<'a, Message> {
     .into()
 }
 
+/// Formats the time of day as `HH:MM:SS UTC`.
+fn format_utc_time(time: std::time::SystemTime) -> String {
+    let secs = time
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    format!(
+        "{:02}:{:02}:{:02} UTC",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// Appends the session statistics with the date to `session.log` in the config folder.
+/// Errors are only logged.
+fn append_session_log(stats: SessionStats) {
+    use std::io::Write;
+
+    let Some(path) = dirs::config_dir().map(|dir| dir.join("session.log")) else {
+        return;
+    };
+    let now = std::time::SystemTime::now();
+    let line = format!(
+        "{} {}: opened {}, marked {}, deleted {}, moved {}\n",
+        format_utc_date(now),
+        format_utc_time(now),
+        stats.opened,
+        stats.marked,
+        stats.deleted,
+        stats.moved,
+    );
+    let result = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(line.as_bytes()));
+    if let Err(err) = result {
+        println!("Session log error {err:?}");
+    }
+}
+
+/// Formats the date as `YYYY-MM-DD` (UTC).
+fn format_utc_date(time: std::time::SystemTime) -> String {
+    let days = time
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86400)
+        .unwrap_or_default() as i64;
+    // civil from days, see https://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Asks to restore a session left by a crash.
+async fn restore_session_dialog(count: usize) -> MessageDialogResult {
+    let description = format!("The last session did not quit cleanly, restore its {count} files?");
+    MessageDialog::new()
+        .set_buttons(MessageButtons::YesNo)
+        .set_description(description)
+        .set_level(MessageLevel::Info)
+        .set_title("Restore previous session?")
+        .show()
+}
+
+/// Reports the files that failed to `action` (move, delete or export) with the cause, if any failed.
+fn file_errors_dialog(action: &str, errors: &[FileError]) -> Task<Message> {
+    if errors.is_empty() {
+        return Task::none();
+    }
+    let mut lines: Vec<String> = errors.iter().take(FILE_ERRORS_SHOWN).map(ToString::to_string).collect();
+    if errors.len() > FILE_ERRORS_SHOWN {
+        lines.push(format!("… and {} more", errors.len() - FILE_ERRORS_SHOWN));
+    }
+    let kept = if action == "export" { "" } else { ", they are kept" };
+    let description = format!("Could not {action} {} files{kept}:\n\n{}", errors.len(), lines.join("\n"));
+    let title = format!("Failed to {action} files");
+    Task::future(async move {
+        MessageDialog::new()
+            .set_buttons(MessageButtons::Ok)
+            .set_description(description)
+            .set_level(MessageLevel::Error)
+            .set_title(title)
+            .show();
+    })
+    .discard()
+}
+
+/// Lists the watched folders, one per line.
+fn watches_caption(watches: &[PathBuf]) -> String {
+    if watches.is_empty() {
+        return "No watched folders".into();
+    }
+    let folders: Vec<_> = watches.iter().map(|path| path.to_string_lossy()).collect();
+    format!("Watching {} folder(s):\n{}", watches.len(), folders.join("\n"))
+}
+
+/// Formats a frequency span in Hz, kHz or MHz.
+fn format_hz(hz: f64) -> String {
+    if hz >= 1_000_000.0 {
+        format!("{:.3} MHz", hz / 1_000_000.0)
+    } else if hz >= 1_000.0 {
+        format!("{:.3} kHz", hz / 1_000.0)
+    } else {
+        format!("{hz:.0} Hz")
+    }
+}
+
+/// Formats a dB value for the exact entry fields, without trailing zeros.
+fn format_db(value: f32) -> String {
+    format!("{value}")
+}
+
+/// Parses an exact dB value, optionally with a "dB" suffix, within the given `range`.
+fn parse_db(text: &str, range: std::ops::RangeInclusive<f32>) -> Option<f32> {
+    let text = text.trim();
+    let text = text.strip_suffix("dB").or(text.strip_suffix("db")).unwrap_or(text);
+    text.trim().parse::<f32>().ok().filter(|value| range.contains(value))
+}
+
+/// Parses up to 9 zoom presets in samples per pixel, separated by spaces or commas.
+fn parse_zoom_presets(text: &str) -> Option<Vec<u32>> {
+    let presets: Option<Vec<u32>> = text
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.parse().ok().filter(|&zoom| zoom > 0))
+        .collect();
+    presets.filter(|presets| (1..=9).contains(&presets.len()))
+}
+
+fn format_zoom_presets(presets: &[u32]) -> String {
+    presets.iter().map(u32::to_string).collect::<Vec<_>>().join(" ")
+}
+
+/// Thumbnail rendering from the settings.
+fn thumbnail_config(settings: &Settings) -> ThumbnailConfig {
+    ThumbnailConfig {
+        supersample: settings.supersample.to_value(),
+        colormap: settings.thumbnail_colormap,
+        fftn: settings.thumbnail_fftn,
+        timeout: Duration::from_secs(settings.render_timeout.to_value()),
+    }
+}
+
+/// Number of thumbnails rendered at once in the background, one per core.
+fn thumbnail_workers() -> usize {
+    std::thread::available_parallelism().map_or(4, |count| count.get())
+}
+
+/// Renders a thumbnail on a thread of its own, the rendering blocks in the FFI call.
+/// A panicked render shows as failed.
+async fn render_thumbnail(pending: PendingThumbnail) -> Thumbnail {
+    let (sender, receiver) = iced::futures::channel::oneshot::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(pending.render());
+    });
+    receiver.await.unwrap_or_else(|_| Thumbnail::failed())
+}
+
+/// Renders a thumbnail of the file with the given options, for the compare dialog.
+fn compare_handle(path: &Path, format: Option<&'static str>, config: &ThumbnailConfig) -> image::Handle {
+    let (bitmap, _) = config.render(path, format);
+    image::Handle::from_rgba(bitmap.width as u32, bitmap.height as u32, bitmap.pixels)
+}
+
+/// Fills the capture command template from the visible part of the plot.
+/// Placeholders are `{center}` and `{rate}` in Hz, `{samples}` visible and `{path}` of the file.
+fn tuning_command(template: &str, plot: &Plot) -> String {
+    template
+        .replace("{center}", &format!("{:.0}", plot.center_freq()))
+        .replace("{rate}", &format!("{:.0}", plot.sample_rate()))
+        .replace("{samples}", &plot.visible_samples().to_string())
+        .replace("{path}", &plot.path().to_string_lossy())
+}
+
+/// Runs the external tool command on the file, `{path}` in the command is replaced.
+/// The path is appended if the command has no placeholder. Errors are only logged.
+fn open_external(command: &str, path: &Path) {
+    let path = path.to_string_lossy();
+    let mut args: Vec<String> = command.split_whitespace().map(|arg| arg.replace("{path}", &path)).collect();
+    if !command.contains("{path}") {
+        args.push(path.into_owned());
+    }
+    let Some((program, args)) = args.split_first() else {
+        println!("External tool not set");
+        return;
+    };
+    if let Err(err) = std::process::Command::new(program).args(args).spawn() {
+        println!("External tool error {err:?}");
+    }
+}
+
+/// Opens the `url` in the default browser.
+fn open_url(url: &str) {
+    #[cfg(target_os = "windows")]
+    // `cmd /C start` would split the URL at `&`
+    let mut command = std::process::Command::new("explorer");
+    #[cfg(target_os = "macos")]
+    let mut command = std::process::Command::new("open");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = std::process::Command::new("xdg-open");
+
+    if let Err(err) = command.arg(url).spawn() {
+        println!("Open URL error {err:?}");
+    }
+}
+
+/// Visible keyboard focus around an editor toolbar control.
+fn focus_ring(theme: &Theme) -> container::Style {
+    container::Style {
+        border: iced::Border {
+            color: theme.extended_palette().primary.strong.color,
+            width: 2.0,
+            radius: 4.0.into(),
+        },
+        ..container::Style::default()
+    }
+}
+
+/// Style of the letterbox around a thumbnail image, the theme surface color by default.
+fn thumbnail_background(background: ThumbnailBackground) -> impl Fn(&Theme) -> container::Style {
+    move |theme| {
+        let color = match background.to_rgb() {
+            Some([r, g, b]) => iced::Color::from_rgb8(r, g, b),
+            None => theme.extended_palette().background.base.color,
+        };
+        container::Style::default().background(color)
+    }
+}
+
+/// Border around the gallery item of the file open in the editor.
+fn open_ring(theme: &Theme) -> container::Style {
+    container::Style {
+        border: iced::Border {
+            color: theme.extended_palette().background.strong.text,
+            width: 2.0,
+            radius: 4.0.into(),
+        },
+        ..container::Style::default()
+    }
+}
+
+/// Settings row, `label` is left aligned within 250px, followed by the `control`.
+fn setting_row<'a>(label: &'a str, control: impl Into<Element<'a, Message>>) -> Element<'a, Message> {
+    row![container(text(label)).width(250), control.into()]
+        .align_y(Alignment::Center)
+        .into()
+}
+
 /*
 #[cfg(test)]
 mod tests {
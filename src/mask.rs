@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (2025) Christian W. Zuckschwerdt
+
+//! I/Q Viewer -- Detection mask, regions found by an external detector.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::sigmf;
+
+/// A detected region, in samples and Hz. A missing frequency edge spans to the band edge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Detection {
+    pub sample_start: u64,
+    /// `None` spans to the end of the recording.
+    pub sample_count: Option<u64>,
+    pub freq_lower: Option<f64>,
+    pub freq_upper: Option<f64>,
+    /// Detector score (confidence) from 0 to 1, picks the color from the mask colormap.
+    pub score: f32,
+}
+
+/// The mask file of a recording, e.g. `capture.cu8.mask.csv`.
+fn mask_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".mask.csv");
+    PathBuf::from(name)
+}
+
+/// Parses a mask line `sample_start,sample_count,freq_lower,freq_upper[,score]`.
+/// An empty count spans to the end, empty frequency fields span the band, a missing score is taken as 1.
+fn parse_line(line: &str) -> Option<Detection> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    if fields.len() < 4 {
+        return None;
+    }
+    fn optional<T: std::str::FromStr>(field: &str) -> Option<Option<T>> {
+        if field.is_empty() { Some(None) } else { field.parse().ok().map(Some) }
+    }
+    Some(Detection {
+        sample_start: fields[0].parse().ok()?,
+        sample_count: optional(fields[1])?,
+        freq_lower: optional(fields[2])?,
+        freq_upper: optional(fields[3])?,
+        score: match fields.get(4) {
+            Some(score) => score.parse::<f32>().ok()?.clamp(0.0, 1.0),
+            None => 1.0,
+        },
+    })
+}
+
+/// Reads the detections for a recording, from a `.mask.csv` file next to it,
+/// otherwise from the SigMF annotations. Empty if there are none.
+pub fn load(path: &Path) -> Vec<Detection> {
+    if let Ok(text) = fs::read_to_string(mask_path(path)) {
+        // lines not parsing, e.g. a header or comments, are skipped
+        return text.lines().filter_map(parse_line).collect();
+    }
+
+    sigmf::annotations(path)
+        .into_iter()
+        .map(|(sample_start, sample_count, freq_lower, freq_upper)| Detection {
+            sample_start,
+            sample_count,
+            freq_lower,
+            freq_upper,
+            score: 1.0,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detection(
+        sample_start: u64,
+        sample_count: Option<u64>,
+        freqs: (Option<f64>, Option<f64>),
+        score: f32,
+    ) -> Detection {
+        Detection {
+            sample_start,
+            sample_count,
+            freq_lower: freqs.0,
+            freq_upper: freqs.1,
+            score,
+        }
+    }
+
+    #[test]
+    fn parse_line_valid() {
+        assert_eq!(
+            parse_line("1000,500,433.9e6,434.1e6,0.75"),
+            Some(detection(1000, Some(500), (Some(433.9e6), Some(434.1e6)), 0.75))
+        );
+        assert_eq!(
+            parse_line(" 0 , 10 , -5e3 , 5e3 "),
+            Some(detection(0, Some(10), (Some(-5e3), Some(5e3)), 1.0))
+        );
+        // scores are clamped
+        assert_eq!(parse_line("0,10,1,2,7").map(|d| d.score), Some(1.0));
+    }
+
+    #[test]
+    fn parse_line_partial() {
+        assert_eq!(parse_line("1000,500,,"), Some(detection(1000, Some(500), (None, None), 1.0)));
+        assert_eq!(parse_line("1000,,433.9e6,"), Some(detection(1000, None, (Some(433.9e6), None), 1.0)));
+    }
+
+    #[test]
+    fn parse_line_malformed() {
+        assert_eq!(parse_line(""), None);
+        assert_eq!(parse_line("sample_start,sample_count,freq_lower,freq_upper,score"), None);
+        assert_eq!(parse_line("# a comment"), None);
+        assert_eq!(parse_line("1000,500,433.9e6"), None);
+        assert_eq!(parse_line(",500,1,2"), None);
+        assert_eq!(parse_line("-1,500,1,2"), None);
+        assert_eq!(parse_line("0,x,1,2"), None);
+        assert_eq!(parse_line("0,10,low,2"), None);
+        assert_eq!(parse_line("0,10,1,2,high"), None);
+    }
+}
@@ -3,6 +3,613 @@
 
 //! I/Q Viewer -- Spectrogram options.
 
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::dirs::config_dir;
+
+/// App settings, persisted in the platform config folder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub empty_gallery: EmptyGallery,
+    pub refresh: RefreshInterval,
+    pub timeline_threshold: f32,
+    pub grid_spacing: f32,
+    pub thumbnail_padding: f32,
+    pub startup: Startup,
+    pub space_action: SpaceAction,
+    pub gallery_limit: GalleryLimit,
+    pub guide_filter: GuideFilter,
+    pub guide_color: GuideColor,
+    pub dc_notch: bool,
+    pub sample_grid: bool,
+    pub supersample: Supersample,
+    pub render_timeout: RenderTimeout,
+    pub thumbnail_colormap: Colormap,
+    pub thumbnail_fftn: FftSize,
+    pub select_action: SelectAction,
+    pub selection_ends: SelectionEnds,
+    pub folder_stride: FolderStride,
+    pub gallery_open: GalleryOpen,
+    /// External tool command, `{path}` is replaced with the file.
+    pub external_tool: String,
+    pub session_log: SessionLog,
+    pub rate_split: RateSplit,
+    pub narrow_orientation: Orientation,
+    pub wide_orientation: Orientation,
+    pub bandwidth_estimate: BandwidthEstimate,
+    pub folder_newest: FolderNewest,
+    pub folder_depth: FolderDepth,
+    pub ui_scale: UiScale,
+    pub mark_advance: MarkAdvance,
+    pub marker_snap: MarkerSnap,
+    pub time_scrollbar: TimeScrollbar,
+    pub option_apply: OptionApply,
+    pub time_direction: TimeDirection,
+    pub thumbnail_background: ThumbnailBackground,
+    pub level_unit: LevelUnit,
+    /// Calibration offset in dB, dBm = dBFS + offset.
+    pub dbm_offset: f32,
+    /// Samples per pixel on the keys 1 to 9.
+    pub zoom_presets: Vec<u32>,
+    pub clipping_warning: ClippingWarning,
+    pub watch_throttle: WatchThrottle,
+    pub sort_order: SortOrder,
+    pub sort_descending: bool,
+    pub confirm_delete: ConfirmThreshold,
+    pub confirm_move: ConfirmThreshold,
+    pub number_format: NumberFormat,
+    pub mask_opacity: MaskOpacity,
+    pub mask_colormap: MaskColormap,
+    pub initial_view: InitialView,
+    /// Share of the gallery in the editor split view, the divider position.
+    pub split_ratio: f32,
+    /// Spectrogram options of the last session, restored on start.
+    pub view_options: ViewOptions,
+    /// Exact gain and range in dB of the last session, over the presets of the `view_options`.
+    pub view_gain: Option<f32>,
+    pub view_range: Option<f32>,
+    pub review_on_open: ReviewOnOpen,
+    pub eviction: Eviction,
+    /// Capture command template, see `tuning_command()`.
+    pub tuning_template: String,
+    /// Most recently viewed files and folders, newest first.
+    pub recent: Vec<PathBuf>,
+}
+
+/// Maximum number of entries kept in the recently viewed list.
+const RECENT_MAX: usize = 10;
+
+/// Limits of the gallery share in the split view, both panes stay usable.
+pub const SPLIT_RATIO_MIN: f32 = 0.1;
+pub const SPLIT_RATIO_MAX: f32 = 0.9;
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            empty_gallery: EmptyGallery::default(),
+            refresh: RefreshInterval::default(),
+            timeline_threshold: 0.5,
+            grid_spacing: 10.0,
+            thumbnail_padding: 2.0,
+            startup: Startup::default(),
+            space_action: SpaceAction::default(),
+            gallery_limit: GalleryLimit::default(),
+            guide_filter: GuideFilter::default(),
+            guide_color: GuideColor::default(),
+            dc_notch: false,
+            sample_grid: false,
+            supersample: Supersample::default(),
+            render_timeout: RenderTimeout::default(),
+            thumbnail_colormap: Colormap::default(),
+            thumbnail_fftn: FftSize::default(),
+            select_action: SelectAction::default(),
+            selection_ends: SelectionEnds::default(),
+            folder_stride: FolderStride::default(),
+            gallery_open: GalleryOpen::default(),
+            external_tool: "inspectrum {path}".into(),
+            session_log: SessionLog::default(),
+            rate_split: RateSplit::default(),
+            narrow_orientation: Orientation::Waterfall,
+            wide_orientation: Orientation::Spectrogram,
+            bandwidth_estimate: BandwidthEstimate::default(),
+            folder_newest: FolderNewest::default(),
+            folder_depth: FolderDepth::default(),
+            ui_scale: UiScale::default(),
+            mark_advance: MarkAdvance::default(),
+            marker_snap: MarkerSnap::default(),
+            time_scrollbar: TimeScrollbar::default(),
+            option_apply: OptionApply::default(),
+            time_direction: TimeDirection::default(),
+            thumbnail_background: ThumbnailBackground::default(),
+            level_unit: LevelUnit::default(),
+            dbm_offset: 0.0,
+            zoom_presets: vec![1, 4, 16, 64, 256, 1024, 4096, 16384, 65536],
+            clipping_warning: ClippingWarning::default(),
+            watch_throttle: WatchThrottle::default(),
+            sort_order: SortOrder::default(),
+            sort_descending: false,
+            confirm_delete: ConfirmThreshold::OverOne,
+            confirm_move: ConfirmThreshold::Always,
+            number_format: NumberFormat::default(),
+            mask_opacity: MaskOpacity::default(),
+            mask_colormap: MaskColormap::default(),
+            initial_view: InitialView::default(),
+            split_ratio: 1.0 / 3.0,
+            view_options: ViewOptions::default(),
+            view_gain: None,
+            view_range: None,
+            review_on_open: ReviewOnOpen::default(),
+            eviction: Eviction::default(),
+            tuning_template: "rtl_sdr -f {center} -s {rate} -n {samples} capture.cu8".into(),
+            recent: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    fn from_table(table: &toml::Table) -> Self {
+        let default = Self::default();
+        let variant = |key: &str| table.get(key).and_then(toml::Value::as_str);
+        let float = |key: &str| {
+            table.get(key).and_then(|v| v.as_float().or(v.as_integer().map(|i| i as f64)))
+        };
+
+        Self {
+            empty_gallery: variant("empty_gallery")
+                .and_then(|v| parse_variant(EmptyGallery::VARIANTS, v))
+                .unwrap_or(default.empty_gallery),
+            refresh: variant("refresh")
+                .and_then(|v| parse_variant(RefreshInterval::VARIANTS, v))
+                .unwrap_or(default.refresh),
+            timeline_threshold: float("timeline_threshold")
+                .map_or(default.timeline_threshold, |v| (v as f32).clamp(0.0, 1.0)),
+            grid_spacing: float("grid_spacing")
+                .map_or(default.grid_spacing, |v| (v as f32).clamp(0.0, 40.0)),
+            thumbnail_padding: float("thumbnail_padding")
+                .map_or(default.thumbnail_padding, |v| (v as f32).clamp(0.0, 10.0)),
+            startup: variant("startup")
+                .and_then(|v| parse_variant(Startup::VARIANTS, v))
+                .unwrap_or(default.startup),
+            space_action: variant("space_action")
+                .and_then(|v| parse_variant(SpaceAction::VARIANTS, v))
+                .unwrap_or(default.space_action),
+            gallery_limit: variant("gallery_limit")
+                .and_then(|v| parse_variant(GalleryLimit::VARIANTS, v))
+                .unwrap_or(default.gallery_limit),
+            guide_filter: variant("guide_filter")
+                .and_then(|v| parse_variant(GuideFilter::VARIANTS, v))
+                .unwrap_or(default.guide_filter),
+            dc_notch: table.get("dc_notch").and_then(toml::Value::as_bool).unwrap_or(default.dc_notch),
+            sample_grid: table.get("sample_grid").and_then(toml::Value::as_bool).unwrap_or(default.sample_grid),
+            supersample: variant("supersample")
+                .and_then(|v| parse_variant(Supersample::VARIANTS, v))
+                .unwrap_or(default.supersample),
+            guide_color: variant("guide_color")
+                .and_then(|v| parse_variant(GuideColor::VARIANTS, v))
+                .unwrap_or(default.guide_color),
+            render_timeout: variant("render_timeout")
+                .and_then(|v| parse_variant(RenderTimeout::VARIANTS, v))
+                .unwrap_or(default.render_timeout),
+            thumbnail_colormap: variant("thumbnail_colormap")
+                .and_then(|v| parse_variant(Colormap::VARIANTS, v))
+                .unwrap_or(default.thumbnail_colormap),
+            thumbnail_fftn: variant("thumbnail_fftn")
+                .and_then(|v| parse_variant(FftSize::VARIANTS, v))
+                .unwrap_or(default.thumbnail_fftn),
+            select_action: variant("select_action")
+                .and_then(|v| parse_variant(SelectAction::VARIANTS, v))
+                .unwrap_or(default.select_action),
+            selection_ends: variant("selection_ends")
+                .and_then(|v| parse_variant(SelectionEnds::VARIANTS, v))
+                .unwrap_or(default.selection_ends),
+            folder_stride: variant("folder_stride")
+                .and_then(|v| parse_variant(FolderStride::VARIANTS, v))
+                .unwrap_or(default.folder_stride),
+            gallery_open: variant("gallery_open")
+                .and_then(|v| parse_variant(GalleryOpen::VARIANTS, v))
+                .unwrap_or(default.gallery_open),
+            external_tool: variant("external_tool")
+                .map_or(default.external_tool, str::to_string),
+            session_log: variant("session_log")
+                .and_then(|v| parse_variant(SessionLog::VARIANTS, v))
+                .unwrap_or(default.session_log),
+            rate_split: variant("rate_split")
+                .and_then(|v| parse_variant(RateSplit::VARIANTS, v))
+                .unwrap_or(default.rate_split),
+            narrow_orientation: variant("narrow_orientation")
+                .and_then(|v| parse_variant(Orientation::VARIANTS, v))
+                .unwrap_or(default.narrow_orientation),
+            wide_orientation: variant("wide_orientation")
+                .and_then(|v| parse_variant(Orientation::VARIANTS, v))
+                .unwrap_or(default.wide_orientation),
+            bandwidth_estimate: variant("bandwidth_estimate")
+                .and_then(|v| parse_variant(BandwidthEstimate::VARIANTS, v))
+                .unwrap_or(default.bandwidth_estimate),
+            folder_newest: variant("folder_newest")
+                .and_then(|v| parse_variant(FolderNewest::VARIANTS, v))
+                .unwrap_or(default.folder_newest),
+            folder_depth: variant("folder_depth")
+                .and_then(|v| parse_variant(FolderDepth::VARIANTS, v))
+                .unwrap_or(default.folder_depth),
+            ui_scale: variant("ui_scale")
+                .and_then(|v| parse_variant(UiScale::VARIANTS, v))
+                .unwrap_or(default.ui_scale),
+            mark_advance: variant("mark_advance")
+                .and_then(|v| parse_variant(MarkAdvance::VARIANTS, v))
+                .unwrap_or(default.mark_advance),
+            marker_snap: variant("marker_snap")
+                .and_then(|v| parse_variant(MarkerSnap::VARIANTS, v))
+                .unwrap_or(default.marker_snap),
+            time_scrollbar: variant("time_scrollbar")
+                .and_then(|v| parse_variant(TimeScrollbar::VARIANTS, v))
+                .unwrap_or(default.time_scrollbar),
+            option_apply: variant("option_apply")
+                .and_then(|v| parse_variant(OptionApply::VARIANTS, v))
+                .unwrap_or(default.option_apply),
+            time_direction: variant("time_direction")
+                .and_then(|v| parse_variant(TimeDirection::VARIANTS, v))
+                .unwrap_or(default.time_direction),
+            thumbnail_background: variant("thumbnail_background")
+                .and_then(|v| parse_variant(ThumbnailBackground::VARIANTS, v))
+                .unwrap_or(default.thumbnail_background),
+            level_unit: variant("level_unit")
+                .and_then(|v| parse_variant(LevelUnit::VARIANTS, v))
+                .unwrap_or(default.level_unit),
+            dbm_offset: float("dbm_offset")
+                .map_or(default.dbm_offset, |v| (v as f32).clamp(-200.0, 200.0)),
+            zoom_presets: table
+                .get("zoom_presets")
+                .and_then(toml::Value::as_array)
+                .map(|list| {
+                    let zooms = list.iter().filter_map(toml::Value::as_integer);
+                    zooms.map(|zoom| zoom.clamp(1, u32::MAX as i64) as u32).take(9).collect()
+                })
+                .unwrap_or(default.zoom_presets),
+            clipping_warning: variant("clipping_warning")
+                .and_then(|v| parse_variant(ClippingWarning::VARIANTS, v))
+                .unwrap_or(default.clipping_warning),
+            watch_throttle: variant("watch_throttle")
+                .and_then(|v| parse_variant(WatchThrottle::VARIANTS, v))
+                .unwrap_or(default.watch_throttle),
+            sort_order: variant("sort_order")
+                .and_then(|v| parse_variant(SortOrder::VARIANTS, v))
+                .unwrap_or(default.sort_order),
+            sort_descending: table.get("sort_descending").and_then(toml::Value::as_bool).unwrap_or(default.sort_descending),
+            confirm_delete: variant("confirm_delete")
+                .and_then(|v| parse_variant(ConfirmThreshold::VARIANTS, v))
+                .unwrap_or(default.confirm_delete),
+            confirm_move: variant("confirm_move")
+                .and_then(|v| parse_variant(ConfirmThreshold::VARIANTS, v))
+                .unwrap_or(default.confirm_move),
+            number_format: variant("number_format")
+                .and_then(|v| parse_variant(NumberFormat::VARIANTS, v))
+                .unwrap_or(default.number_format),
+            mask_opacity: variant("mask_opacity")
+                .and_then(|v| parse_variant(MaskOpacity::VARIANTS, v))
+                .unwrap_or(default.mask_opacity),
+            mask_colormap: variant("mask_colormap")
+                .and_then(|v| parse_variant(MaskColormap::VARIANTS, v))
+                .unwrap_or(default.mask_colormap),
+            initial_view: variant("initial_view")
+                .and_then(|v| parse_variant(InitialView::VARIANTS, v))
+                .unwrap_or(default.initial_view),
+            split_ratio: float("split_ratio")
+                .map_or(default.split_ratio, |v| (v as f32).clamp(SPLIT_RATIO_MIN, SPLIT_RATIO_MAX)),
+            view_options: table
+                .get("view_options")
+                .and_then(|v| v.as_table())
+                .map_or(default.view_options, ViewOptions::from_table),
+            view_gain: float("view_gain").map(|v| (v as f32).clamp(-30.0, 60.0)),
+            view_range: float("view_range").map(|v| (v as f32).clamp(1.0, 150.0)),
+            review_on_open: variant("review_on_open")
+                .and_then(|v| parse_variant(ReviewOnOpen::VARIANTS, v))
+                .unwrap_or(default.review_on_open),
+            eviction: variant("eviction")
+                .and_then(|v| parse_variant(Eviction::VARIANTS, v))
+                .unwrap_or(default.eviction),
+            tuning_template: variant("tuning_template")
+                .map_or(default.tuning_template, str::to_string),
+            recent: table
+                .get("recent")
+                .and_then(toml::Value::as_array)
+                .map(|list| list.iter().filter_map(toml::Value::as_str).map(PathBuf::from).collect())
+                .unwrap_or(default.recent),
+        }
+    }
+
+    fn to_table(&self) -> toml::Table {
+        let mut table = toml::Table::new();
+        table.insert("empty_gallery".into(), self.empty_gallery.to_string().into());
+        table.insert("refresh".into(), self.refresh.to_string().into());
+        table.insert("timeline_threshold".into(), (self.timeline_threshold as f64).into());
+        table.insert("grid_spacing".into(), (self.grid_spacing as f64).into());
+        table.insert("thumbnail_padding".into(), (self.thumbnail_padding as f64).into());
+        table.insert("startup".into(), self.startup.to_string().into());
+        table.insert("space_action".into(), self.space_action.to_string().into());
+        table.insert("gallery_limit".into(), self.gallery_limit.to_string().into());
+        table.insert("eviction".into(), self.eviction.to_string().into());
+        table.insert("guide_filter".into(), self.guide_filter.to_string().into());
+        table.insert("guide_color".into(), self.guide_color.to_string().into());
+        table.insert("dc_notch".into(), self.dc_notch.into());
+        table.insert("sample_grid".into(), self.sample_grid.into());
+        table.insert("supersample".into(), self.supersample.to_string().into());
+        table.insert("render_timeout".into(), self.render_timeout.to_string().into());
+        table.insert("thumbnail_colormap".into(), self.thumbnail_colormap.to_string().into());
+        table.insert("thumbnail_fftn".into(), self.thumbnail_fftn.to_string().into());
+        table.insert("select_action".into(), self.select_action.to_string().into());
+        table.insert("selection_ends".into(), self.selection_ends.to_string().into());
+        table.insert("folder_stride".into(), self.folder_stride.to_string().into());
+        table.insert("gallery_open".into(), self.gallery_open.to_string().into());
+        table.insert("external_tool".into(), self.external_tool.clone().into());
+        table.insert("session_log".into(), self.session_log.to_string().into());
+        table.insert("rate_split".into(), self.rate_split.to_string().into());
+        table.insert("narrow_orientation".into(), self.narrow_orientation.to_string().into());
+        table.insert("wide_orientation".into(), self.wide_orientation.to_string().into());
+        table.insert("bandwidth_estimate".into(), self.bandwidth_estimate.to_string().into());
+        table.insert("folder_newest".into(), self.folder_newest.to_string().into());
+        table.insert("folder_depth".into(), self.folder_depth.to_string().into());
+        table.insert("ui_scale".into(), self.ui_scale.to_string().into());
+        table.insert("mark_advance".into(), self.mark_advance.to_string().into());
+        table.insert("marker_snap".into(), self.marker_snap.to_string().into());
+        table.insert("time_scrollbar".into(), self.time_scrollbar.to_string().into());
+        table.insert("option_apply".into(), self.option_apply.to_string().into());
+        table.insert("time_direction".into(), self.time_direction.to_string().into());
+        table.insert("thumbnail_background".into(), self.thumbnail_background.to_string().into());
+        table.insert("level_unit".into(), self.level_unit.to_string().into());
+        table.insert("dbm_offset".into(), (self.dbm_offset as f64).into());
+        let zoom_presets: Vec<toml::Value> = self.zoom_presets.iter().map(|&zoom| (zoom as i64).into()).collect();
+        table.insert("zoom_presets".into(), zoom_presets.into());
+        table.insert("clipping_warning".into(), self.clipping_warning.to_string().into());
+        table.insert("watch_throttle".into(), self.watch_throttle.to_string().into());
+        table.insert("sort_order".into(), self.sort_order.to_string().into());
+        table.insert("sort_descending".into(), self.sort_descending.into());
+        table.insert("confirm_delete".into(), self.confirm_delete.to_string().into());
+        table.insert("confirm_move".into(), self.confirm_move.to_string().into());
+        table.insert("number_format".into(), self.number_format.to_string().into());
+        table.insert("mask_opacity".into(), self.mask_opacity.to_string().into());
+        table.insert("mask_colormap".into(), self.mask_colormap.to_string().into());
+        table.insert("initial_view".into(), self.initial_view.to_string().into());
+        table.insert("split_ratio".into(), (self.split_ratio as f64).into());
+        table.insert("view_options".into(), self.view_options.to_table().into());
+        if let Some(gain) = self.view_gain {
+            table.insert("view_gain".into(), (gain as f64).into());
+        }
+        if let Some(range) = self.view_range {
+            table.insert("view_range".into(), (range as f64).into());
+        }
+        table.insert("review_on_open".into(), self.review_on_open.to_string().into());
+        table.insert("tuning_template".into(), self.tuning_template.clone().into());
+        let recent: Vec<toml::Value> =
+            self.recent.iter().map(|path| path.to_string_lossy().into_owned().into()).collect();
+        table.insert("recent".into(), recent.into());
+        table
+    }
+
+    /// Moves the path to the front of the recently viewed list.
+    pub fn add_recent(&mut self, path: &Path) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.recent.retain(|p| *p != path);
+        self.recent.insert(0, path);
+        self.recent.truncate(RECENT_MAX);
+    }
+}
+
+fn settings_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("settings.toml"))
+}
+
+/// Loads the settings, falls back to defaults if missing or malformed.
+pub fn load_settings() -> Settings {
+    let Some(content) = settings_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Settings::default();
+    };
+    match content.parse::<toml::Table>() {
+        Ok(table) => Settings::from_table(&table),
+        Err(err) => {
+            println!("Settings error {err:?}");
+            Settings::default()
+        }
+    }
+}
+
+/// Saves the settings, errors are only logged.
+pub fn save_settings(settings: &Settings) {
+    let Some(path) = settings_path() else {
+        return;
+    };
+    let result = match path.parent() {
+        Some(dir) => fs::create_dir_all(dir),
+        None => Ok(()),
+    };
+    if let Err(err) = result.and_then(|_| fs::write(&path, settings.to_table().to_string())) {
+        println!("Settings error {err:?}");
+    }
+}
+
+fn aliases_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("aliases.toml"))
+}
+
+/// Loads the file display names by canonical path, empty if missing or malformed.
+pub fn load_aliases() -> BTreeMap<PathBuf, String> {
+    let Some(content) = aliases_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return BTreeMap::new();
+    };
+    match content.parse::<toml::Table>() {
+        Ok(table) => table
+            .iter()
+            .filter_map(|(path, alias)| Some((PathBuf::from(path), alias.as_str()?.to_string())))
+            .collect(),
+        Err(err) => {
+            println!("Aliases error {err:?}");
+            BTreeMap::new()
+        }
+    }
+}
+
+/// Saves the file display names, errors are only logged.
+pub fn save_aliases(aliases: &BTreeMap<PathBuf, String>) {
+    let Some(path) = aliases_path() else {
+        return;
+    };
+    let table: toml::Table = aliases
+        .iter()
+        .map(|(path, alias)| (path.to_string_lossy().into_owned(), alias.clone().into()))
+        .collect();
+    let result = match path.parent() {
+        Some(dir) => fs::create_dir_all(dir),
+        None => Ok(()),
+    };
+    if let Err(err) = result.and_then(|_| fs::write(&path, table.to_string())) {
+        println!("Aliases error {err:?}");
+    }
+}
+
+fn reviewed_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("reviewed.toml"))
+}
+
+/// Loads the canonical paths of the reviewed files, empty if missing or malformed.
+pub fn load_reviewed() -> BTreeSet<PathBuf> {
+    let Some(content) = reviewed_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return BTreeSet::new();
+    };
+    match content.parse::<toml::Table>() {
+        Ok(table) => table
+            .get("reviewed")
+            .and_then(toml::Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|path| Some(PathBuf::from(path.as_str()?)))
+            .collect(),
+        Err(err) => {
+            println!("Reviewed error {err:?}");
+            BTreeSet::new()
+        }
+    }
+}
+
+/// Saves the canonical paths of the reviewed files, errors are only logged.
+pub fn save_reviewed(reviewed: &BTreeSet<PathBuf>) {
+    let Some(path) = reviewed_path() else {
+        return;
+    };
+    let paths: Vec<toml::Value> = reviewed.iter().map(|path| path.to_string_lossy().into_owned().into()).collect();
+    let mut table = toml::Table::new();
+    table.insert("reviewed".into(), paths.into());
+    let result = match path.parent() {
+        Some(dir) => fs::create_dir_all(dir),
+        None => Ok(()),
+    };
+    if let Err(err) = result.and_then(|_| fs::write(&path, table.to_string())) {
+        println!("Reviewed error {err:?}");
+    }
+}
+
+/// Gallery state kept for the recovery after a crash, the items and the selection.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct SessionState {
+    pub items: Vec<SessionItem>,
+    pub selection: usize,
+}
+
+/// A gallery item of the session, with what is needed to open it the same way again.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct SessionItem {
+    /// Canonical path, of the archive for an archive member.
+    pub path: PathBuf,
+    /// Name of the member in the archive at `path`, the extracted file is gone with the instance.
+    pub entry: Option<PathBuf>,
+    /// Sample format set for a file without a known I/Q extension.
+    pub format: Option<String>,
+    pub mark: bool,
+    pub delete: bool,
+}
+
+fn session_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("session.toml"))
+}
+
+/// Loads the session left by an unclean exit, `None` if there is none.
+pub fn load_session() -> Option<SessionState> {
+    let content = fs::read_to_string(session_path()?).ok()?;
+    let table = match content.parse::<toml::Table>() {
+        Ok(table) => table,
+        Err(err) => {
+            println!("Session error {err:?}");
+            return None;
+        }
+    };
+    let flag = |item: &toml::Table, key: &str| item.get(key).and_then(toml::Value::as_bool).unwrap_or_default();
+    let items = table
+        .get("items")
+        .and_then(toml::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(toml::Value::as_table)
+        .filter_map(|item| {
+            let text = |key: &str| item.get(key).and_then(toml::Value::as_str).map(str::to_string);
+            Some(SessionItem {
+                path: PathBuf::from(item.get("path")?.as_str()?),
+                entry: text("entry").map(PathBuf::from),
+                format: text("format"),
+                mark: flag(item, "mark"),
+                delete: flag(item, "delete"),
+            })
+        })
+        .collect();
+    let selection = table.get("selection").and_then(toml::Value::as_integer).unwrap_or_default();
+    Some(SessionState {
+        items,
+        selection: selection.max(0) as usize,
+    })
+}
+
+/// Saves the session for recovery, errors are only logged.
+pub fn save_session(session: &SessionState) {
+    let Some(path) = session_path() else {
+        return;
+    };
+    let items: Vec<toml::Value> = session
+        .items
+        .iter()
+        .map(|session_item| {
+            let mut item = toml::Table::new();
+            item.insert("path".into(), session_item.path.to_string_lossy().into_owned().into());
+            if let Some(entry) = &session_item.entry {
+                item.insert("entry".into(), entry.to_string_lossy().into_owned().into());
+            }
+            if let Some(format) = &session_item.format {
+                item.insert("format".into(), format.clone().into());
+            }
+            item.insert("mark".into(), session_item.mark.into());
+            item.insert("delete".into(), session_item.delete.into());
+            item.into()
+        })
+        .collect();
+    let mut table = toml::Table::new();
+    table.insert("selection".into(), (session.selection as i64).into());
+    table.insert("items".into(), items.into());
+    let result = match path.parent() {
+        Some(dir) => fs::create_dir_all(dir),
+        None => Ok(()),
+    };
+    if let Err(err) = result.and_then(|_| fs::write(&path, table.to_string())) {
+        println!("Session error {err:?}");
+    }
+}
+
+/// Removes the session on a clean exit.
+pub fn clear_session() {
+    if let Some(path) = session_path().filter(|path| path.exists()) {
+        if let Err(err) = fs::remove_file(path) {
+            println!("Session error {err:?}");
+        }
+    }
+}
+
 /// FFT window size.
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FftSize {
@@ -309,6 +916,18 @@ impl Orientation {
     }
 }
 
+impl Orientation {
+    /// The layout for this orientation with time running in `direction`.
+    /// The horizontal spectrogram always runs left to right.
+    pub fn with_direction(self, direction: TimeDirection) -> Self {
+        match (self, direction) {
+            (Self::Waterfall, TimeDirection::Reverse) => Self::Geyser,
+            (Self::Geyser, TimeDirection::Reverse) => Self::Waterfall,
+            (orientation, _) => orientation,
+        }
+    }
+}
+
 impl std::fmt::Display for Orientation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(match self {
@@ -318,3 +937,1456 @@ impl std::fmt::Display for Orientation {
         })
     }
 }
+
+/// Local contrast enhancement of the spectrogram.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Enhance {
+    #[default]
+    Off,
+    RowNormalize,
+    Unsharp,
+    ColumnNormalize,
+}
+
+impl Enhance {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Off,
+        Self::RowNormalize,
+        Self::Unsharp,
+        Self::ColumnNormalize,
+    ];
+
+    pub fn to_value(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl std::fmt::Display for Enhance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Off => "Off",
+            Self::RowNormalize => "Per-row normalize",
+            Self::Unsharp => "Unsharp mask",
+            Self::ColumnNormalize => "Per-column normalize",
+        })
+    }
+}
+
+/// Display mode, the complex spectrogram or the amplitude of a single component.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Component {
+    #[default]
+    Both,
+    Real,
+    Imaginary,
+}
+
+impl Component {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Both,
+        Self::Real,
+        Self::Imaginary,
+    ];
+}
+
+impl std::fmt::Display for Component {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Both => "I/Q spectrogram",
+            Self::Real => "I (real)",
+            Self::Imaginary => "Q (imaginary)",
+        })
+    }
+}
+
+/// Per-file overrides for the spectrogram options.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ViewOptions {
+    pub fftn: Option<FftSize>,
+    pub windowf: Option<WindowFunctions>,
+    pub gain: Option<DbGain>,
+    pub range: Option<DbRange>,
+    pub colormap: Option<Colormap>,
+    pub orientation: Option<Orientation>,
+}
+
+impl ViewOptions {
+    /// Sets an option from a `key=value` token, returns false if not recognized.
+    pub fn parse_token(&mut self, token: &str) -> bool {
+        let Some((key, value)) = token.split_once('=') else {
+            return false;
+        };
+        let parsed = match key.to_ascii_lowercase().as_str() {
+            "fft" | "fftn" => parse_variant(FftSize::VARIANTS, value).map(|v| self.fftn = Some(v)),
+            "window" | "windowf" => {
+                parse_variant(WindowFunctions::VARIANTS, value).map(|v| self.windowf = Some(v))
+            }
+            "gain" => parse_variant(DbGain::VARIANTS, value).map(|v| self.gain = Some(v)),
+            "range" => parse_variant(DbRange::VARIANTS, value).map(|v| self.range = Some(v)),
+            "cmap" | "colormap" => {
+                parse_variant(Colormap::VARIANTS, value).map(|v| self.colormap = Some(v))
+            }
+            "orientation" => {
+                parse_variant(Orientation::VARIANTS, value).map(|v| self.orientation = Some(v))
+            }
+            _ => None,
+        };
+        parsed.is_some()
+    }
+
+    /// Reads the options from a settings table, unknown keys and values are skipped.
+    pub fn from_table(table: &toml::Table) -> Self {
+        let mut options = Self::default();
+        for (key, value) in table {
+            let Some(value) = value.as_str() else {
+                continue;
+            };
+            if !options.parse_token(&format!("{key}={value}")) {
+                println!("Settings error, unknown view option {key}={value}");
+            }
+        }
+        options
+    }
+
+    /// The options that are set as a settings table, the inverse of `from_table()`.
+    pub fn to_table(&self) -> toml::Table {
+        let mut table = toml::Table::new();
+        let entries = [
+            ("fft", self.fftn.map(|v| v.to_string())),
+            ("window", self.windowf.map(|v| v.to_string())),
+            ("gain", self.gain.map(|v| v.to_string())),
+            ("range", self.range.map(|v| v.to_string())),
+            ("cmap", self.colormap.map(|v| v.to_string())),
+            ("orientation", self.orientation.map(|v| v.to_string())),
+        ];
+        for (key, value) in entries {
+            if let Some(value) = value {
+                table.insert(key.into(), value.into());
+            }
+        }
+        table
+    }
+}
+
+/// Finds the variant whose display text matches, ignoring case, spaces, signs and units.
+/// E.g. "1024" matches "N=1024", "6" matches "+6 dB", "afmhot" matches "Afm-Hot".
+pub fn parse_variant<T: Copy + std::fmt::Display>(variants: &[T], value: &str) -> Option<T> {
+    fn normalize(text: &str) -> String {
+        let text = text.to_ascii_lowercase();
+        let text = text.strip_prefix("n=").unwrap_or(text.as_str());
+        let text = text.strip_suffix("db").unwrap_or(text);
+        text.chars().filter(|c| c.is_ascii_alphanumeric()).collect()
+    }
+
+    let value = normalize(value);
+    variants
+        .iter()
+        .copied()
+        .find(|variant| normalize(&variant.to_string()) == value)
+}
+
+/// Behavior when the last gallery item is removed while the editor is open.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmptyGallery {
+    #[default]
+    ReturnToGallery,
+    KeepPlot,
+}
+
+impl EmptyGallery {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::ReturnToGallery,
+        Self::KeepPlot,
+    ];
+}
+
+impl std::fmt::Display for EmptyGallery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::ReturnToGallery => "Return to gallery",
+            Self::KeepPlot => "Keep last plot",
+        })
+    }
+}
+
+/// Interval to rescan loaded folders, a fallback for unreliable filesystem events.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshInterval {
+    #[default]
+    Off,
+    Refresh5,
+    Refresh15,
+    Refresh60,
+    Refresh300,
+}
+
+impl RefreshInterval {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Off,
+        Self::Refresh5,
+        Self::Refresh15,
+        Self::Refresh60,
+        Self::Refresh300,
+    ];
+
+    /// Interval in seconds, zero if off.
+    pub fn to_value(&self) -> u64 {
+        match self {
+            Self::Off => 0,
+            Self::Refresh5 => 5,
+            Self::Refresh15 => 15,
+            Self::Refresh60 => 60,
+            Self::Refresh300 => 300,
+        }
+    }
+}
+
+impl std::fmt::Display for RefreshInterval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Off => "Off",
+            Self::Refresh5 => "5 s",
+            Self::Refresh15 => "15 s",
+            Self::Refresh60 => "1 min",
+            Self::Refresh300 => "5 min",
+        })
+    }
+}
+
+/// What to open on startup when no files are given on the command line.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Startup {
+    #[default]
+    Empty,
+    RecentGallery,
+    RecentEditor,
+}
+
+impl Startup {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Empty,
+        Self::RecentGallery,
+        Self::RecentEditor,
+    ];
+}
+
+impl std::fmt::Display for Startup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Empty => "Empty gallery",
+            Self::RecentGallery => "Most recent in gallery",
+            Self::RecentEditor => "Most recent in editor",
+        })
+    }
+}
+
+/// Action of the spacebar while the editor is shown.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpaceAction {
+    #[default]
+    ToggleGallery,
+    NextFile,
+    Nothing,
+}
+
+impl SpaceAction {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::ToggleGallery,
+        Self::NextFile,
+        Self::Nothing,
+    ];
+}
+
+impl std::fmt::Display for SpaceAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::ToggleGallery => "Toggle gallery",
+            Self::NextFile => "Next file",
+            Self::Nothing => "Nothing",
+        })
+    }
+}
+
+/// Maximum number of gallery items, e.g. when watching a recording folder.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GalleryLimit {
+    #[default]
+    Unlimited,
+    Items100,
+    Items500,
+    Items1000,
+    Items5000,
+}
+
+impl GalleryLimit {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Unlimited,
+        Self::Items100,
+        Self::Items500,
+        Self::Items1000,
+        Self::Items5000,
+    ];
+
+    /// Number of items, zero if unlimited.
+    pub fn to_value(&self) -> usize {
+        match self {
+            Self::Unlimited => 0,
+            Self::Items100 => 100,
+            Self::Items500 => 500,
+            Self::Items1000 => 1000,
+            Self::Items5000 => 5000,
+        }
+    }
+}
+
+impl std::fmt::Display for GalleryLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Unlimited => "Unlimited",
+            Self::Items100 => "100 files",
+            Self::Items500 => "500 files",
+            Self::Items1000 => "1000 files",
+            Self::Items5000 => "5000 files",
+        })
+    }
+}
+
+/// Which items to drop first when the gallery limit is exceeded.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Eviction {
+    #[default]
+    FirstAdded,
+    OldestModified,
+}
+
+impl Eviction {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::FirstAdded,
+        Self::OldestModified,
+    ];
+}
+
+impl std::fmt::Display for Eviction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::FirstAdded => "First added",
+            Self::OldestModified => "Oldest modified",
+        })
+    }
+}
+
+/// Scaling of the guides and marker overlay.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuideFilter {
+    #[default]
+    Sharp,
+    Smooth,
+}
+
+impl GuideFilter {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Sharp,
+        Self::Smooth,
+    ];
+}
+
+impl std::fmt::Display for GuideFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Sharp => "Sharp (nearest)",
+            Self::Smooth => "Smooth (linear)",
+        })
+    }
+}
+
+/// Thumbnail render size relative to the displayed size, to reduce aliasing.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Supersample {
+    Off,
+    #[default]
+    X2,
+    X4,
+}
+
+impl Supersample {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Off,
+        Self::X2,
+        Self::X4,
+    ];
+
+    pub fn to_value(&self) -> u32 {
+        match self {
+            Self::Off => 1,
+            Self::X2 => 2,
+            Self::X4 => 4,
+        }
+    }
+}
+
+impl std::fmt::Display for Supersample {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Off => "Off",
+            Self::X2 => "2x",
+            Self::X4 => "4x",
+        })
+    }
+}
+
+/// Color of the guides and marker overlay.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuideColor {
+    #[default]
+    Default,
+    Contrast,
+    White,
+    Black,
+    Yellow,
+    Cyan,
+    Magenta,
+}
+
+impl GuideColor {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Default,
+        Self::Contrast,
+        Self::White,
+        Self::Black,
+        Self::Yellow,
+        Self::Cyan,
+        Self::Magenta,
+    ];
+
+    /// Fixed RGB color, `None` for the default and contrast modes.
+    pub fn to_rgb(&self) -> Option<[u8; 3]> {
+        match self {
+            Self::Default | Self::Contrast => None,
+            Self::White => Some([255, 255, 255]),
+            Self::Black => Some([0, 0, 0]),
+            Self::Yellow => Some([255, 255, 0]),
+            Self::Cyan => Some([0, 255, 255]),
+            Self::Magenta => Some([255, 0, 255]),
+        }
+    }
+}
+
+impl std::fmt::Display for GuideColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Default => "Default",
+            Self::Contrast => "Auto contrast",
+            Self::White => "White",
+            Self::Black => "Black",
+            Self::Yellow => "Yellow",
+            Self::Cyan => "Cyan",
+            Self::Magenta => "Magenta",
+        })
+    }
+}
+
+/// Time limit to render a thumbnail, guards against files that hang the decoder.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderTimeout {
+    Off,
+    Timeout5,
+    #[default]
+    Timeout15,
+    Timeout60,
+}
+
+impl RenderTimeout {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Off,
+        Self::Timeout5,
+        Self::Timeout15,
+        Self::Timeout60,
+    ];
+
+    /// Timeout in seconds, zero if off.
+    pub fn to_value(&self) -> u64 {
+        match self {
+            Self::Off => 0,
+            Self::Timeout5 => 5,
+            Self::Timeout15 => 15,
+            Self::Timeout60 => 60,
+        }
+    }
+}
+
+impl std::fmt::Display for RenderTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Off => "Off",
+            Self::Timeout5 => "5 s",
+            Self::Timeout15 => "15 s",
+            Self::Timeout60 => "1 min",
+        })
+    }
+}
+
+/// What selecting a gallery item does.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectAction {
+    #[default]
+    Browse,
+    Preview,
+}
+
+impl SelectAction {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Browse,
+        Self::Preview,
+    ];
+}
+
+impl std::fmt::Display for SelectAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Browse => "Only select",
+            Self::Preview => "Open in editor",
+        })
+    }
+}
+
+/// Behavior of the keyboard selection at the ends of the gallery.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionEnds {
+    #[default]
+    Stop,
+    Wrap,
+}
+
+impl SelectionEnds {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Stop,
+        Self::Wrap,
+    ];
+}
+
+impl std::fmt::Display for SelectionEnds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Stop => "Stop",
+            Self::Wrap => "Wrap around",
+        })
+    }
+}
+
+/// Load only every Nth file of a folder, for a quick overview of huge folders.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FolderStride {
+    #[default]
+    All,
+    Every2,
+    Every10,
+    Every100,
+}
+
+impl FolderStride {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::All,
+        Self::Every2,
+        Self::Every10,
+        Self::Every100,
+    ];
+
+    /// Step between loaded files, 1 loads all.
+    pub fn to_value(&self) -> usize {
+        match self {
+            Self::All => 1,
+            Self::Every2 => 2,
+            Self::Every10 => 10,
+            Self::Every100 => 100,
+        }
+    }
+}
+
+impl std::fmt::Display for FolderStride {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::All => "All files",
+            Self::Every2 => "Every 2nd",
+            Self::Every10 => "Every 10th",
+            Self::Every100 => "Every 100th",
+        })
+    }
+}
+
+/// What opening a gallery item (click on the selected item) does.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GalleryOpen {
+    #[default]
+    Editor,
+    External,
+}
+
+impl GalleryOpen {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Editor,
+        Self::External,
+    ];
+}
+
+impl std::fmt::Display for GalleryOpen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Editor => "Open in editor",
+            Self::External => "Run external tool",
+        })
+    }
+}
+
+/// Writing the session statistics on quit.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionLog {
+    #[default]
+    Off,
+    Append,
+}
+
+impl SessionLog {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Off,
+        Self::Append,
+    ];
+}
+
+impl std::fmt::Display for SessionLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Off => "Off",
+            Self::Append => "Append to session.log",
+        })
+    }
+}
+
+/// Sample rate that splits narrowband from wideband captures, for the default orientation.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateSplit {
+    #[default]
+    Off,
+    Rate250k,
+    Rate1M,
+    Rate2M,
+    Rate10M,
+}
+
+impl RateSplit {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Off,
+        Self::Rate250k,
+        Self::Rate1M,
+        Self::Rate2M,
+        Self::Rate10M,
+    ];
+
+    /// Sample rate in Hz, zero if off.
+    pub fn to_value(&self) -> f64 {
+        match self {
+            Self::Off => 0.0,
+            Self::Rate250k => 250_000.0,
+            Self::Rate1M => 1_000_000.0,
+            Self::Rate2M => 2_000_000.0,
+            Self::Rate10M => 10_000_000.0,
+        }
+    }
+}
+
+impl std::fmt::Display for RateSplit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Off => "Off",
+            Self::Rate250k => "250 kHz",
+            Self::Rate1M => "1 MHz",
+            Self::Rate2M => "2 MHz",
+            Self::Rate10M => "10 MHz",
+        })
+    }
+}
+
+/// Occupied bandwidth estimate shown in the infobar.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BandwidthEstimate {
+    #[default]
+    Off,
+    Power99,
+    Power90,
+}
+
+impl BandwidthEstimate {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Off,
+        Self::Power99,
+        Self::Power90,
+    ];
+
+    /// Fraction of the power inside the band, zero if off.
+    pub fn to_value(&self) -> f64 {
+        match self {
+            Self::Off => 0.0,
+            Self::Power99 => 0.99,
+            Self::Power90 => 0.90,
+        }
+    }
+}
+
+impl std::fmt::Display for BandwidthEstimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Off => "Off",
+            Self::Power99 => "99% power",
+            Self::Power90 => "90% power",
+        })
+    }
+}
+
+/// Levels of subfolders read along with a dropped or opened folder.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FolderDepth {
+    #[default]
+    Flat,
+    One,
+    Three,
+    All,
+}
+
+impl FolderDepth {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Flat,
+        Self::One,
+        Self::Three,
+        Self::All,
+    ];
+
+    /// Levels of subfolders read, zero reads the folder only.
+    pub fn to_value(&self) -> usize {
+        match self {
+            Self::Flat => 0,
+            Self::One => 1,
+            Self::Three => 3,
+            Self::All => usize::MAX,
+        }
+    }
+}
+
+impl std::fmt::Display for FolderDepth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Flat => "Folder only",
+            Self::One => "1 level of subfolders",
+            Self::Three => "3 levels of subfolders",
+            Self::All => "All subfolders",
+        })
+    }
+}
+
+/// Number of most recently modified files loaded from a folder.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FolderNewest {
+    #[default]
+    All,
+    Newest10,
+    Newest50,
+    Newest100,
+    Newest500,
+}
+
+impl FolderNewest {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::All,
+        Self::Newest10,
+        Self::Newest50,
+        Self::Newest100,
+        Self::Newest500,
+    ];
+
+    /// Number of files kept per folder, zero keeps all.
+    pub fn to_value(&self) -> usize {
+        match self {
+            Self::All => 0,
+            Self::Newest10 => 10,
+            Self::Newest50 => 50,
+            Self::Newest100 => 100,
+            Self::Newest500 => 500,
+        }
+    }
+}
+
+impl std::fmt::Display for FolderNewest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::All => "All files",
+            Self::Newest10 => "Newest 10",
+            Self::Newest50 => "Newest 50",
+            Self::Newest100 => "Newest 100",
+            Self::Newest500 => "Newest 500",
+        })
+    }
+}
+
+/// Interface scale on top of the scale factor of the monitor showing the window.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UiScale {
+    #[default]
+    Monitor,
+    Scale80,
+    Scale125,
+    Scale150,
+    Scale200,
+}
+
+impl UiScale {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Monitor,
+        Self::Scale80,
+        Self::Scale125,
+        Self::Scale150,
+        Self::Scale200,
+    ];
+
+    /// Factor applied to all sizes, icons and fonts.
+    pub fn to_value(&self) -> f64 {
+        match self {
+            Self::Monitor => 1.0,
+            Self::Scale80 => 0.8,
+            Self::Scale125 => 1.25,
+            Self::Scale150 => 1.5,
+            Self::Scale200 => 2.0,
+        }
+    }
+}
+
+impl std::fmt::Display for UiScale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Monitor => "Monitor",
+            Self::Scale80 => "80%",
+            Self::Scale125 => "125%",
+            Self::Scale150 => "150%",
+            Self::Scale200 => "200%",
+        })
+    }
+}
+
+/// What flagging a file for delete or move does to the selection.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkAdvance {
+    #[default]
+    Stay,
+    Next,
+}
+
+impl MarkAdvance {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Stay,
+        Self::Next,
+    ];
+}
+
+impl std::fmt::Display for MarkAdvance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Stay => "Stay on the file",
+            Self::Next => "Advance to next file",
+        })
+    }
+}
+
+/// Where Shift+Click places the marker.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkerSnap {
+    #[default]
+    Exact,
+    Peak,
+}
+
+impl MarkerSnap {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Exact,
+        Self::Peak,
+    ];
+}
+
+impl std::fmt::Display for MarkerSnap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Exact => "Exact position",
+            Self::Peak => "Nearby peak",
+        })
+    }
+}
+
+/// Scrollbar of the visible time range below the plot.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeScrollbar {
+    #[default]
+    Hidden,
+    Shown,
+}
+
+impl TimeScrollbar {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Hidden,
+        Self::Shown,
+    ];
+}
+
+impl std::fmt::Display for TimeScrollbar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Hidden => "Hidden",
+            Self::Shown => "Shown",
+        })
+    }
+}
+
+/// When view option changes re-render the plot.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionApply {
+    #[default]
+    Live,
+    Commit,
+}
+
+impl OptionApply {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Live,
+        Self::Commit,
+    ];
+}
+
+impl std::fmt::Display for OptionApply {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Live => "Live preview",
+            Self::Commit => "Apply on commit",
+        })
+    }
+}
+
+/// Which end of the vertical time axis is the file start.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeDirection {
+    #[default]
+    Forward,
+    Reverse,
+}
+
+impl TimeDirection {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Forward,
+        Self::Reverse,
+    ];
+}
+
+impl std::fmt::Display for TimeDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Forward => "File start first",
+            Self::Reverse => "File end first",
+        })
+    }
+}
+
+/// Background of the thumbnail image, shows as letterbox around non-square images.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThumbnailBackground {
+    #[default]
+    Surface,
+    Black,
+    Gray,
+    White,
+}
+
+impl ThumbnailBackground {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Surface,
+        Self::Black,
+        Self::Gray,
+        Self::White,
+    ];
+
+    /// Fixed RGB color, `None` for the theme surface color.
+    pub fn to_rgb(&self) -> Option<[u8; 3]> {
+        match self {
+            Self::Surface => None,
+            Self::Black => Some([0, 0, 0]),
+            Self::Gray => Some([128, 128, 128]),
+            Self::White => Some([255, 255, 255]),
+        }
+    }
+}
+
+impl std::fmt::Display for ThumbnailBackground {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Surface => "Theme surface",
+            Self::Black => "Black",
+            Self::Gray => "Gray",
+            Self::White => "White",
+        })
+    }
+}
+
+/// Unit of the level readouts, dBm applies the calibration offset.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelUnit {
+    #[default]
+    Dbfs,
+    Dbm,
+}
+
+impl LevelUnit {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Dbfs,
+        Self::Dbm,
+    ];
+}
+
+impl std::fmt::Display for LevelUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Dbfs => "dBFS",
+            Self::Dbm => "dBm (calibrated)",
+        })
+    }
+}
+
+/// Fraction of clipped samples that warns of an overdriven capture.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClippingWarning {
+    Off,
+    Tiny,
+    #[default]
+    Small,
+    Large,
+}
+
+impl ClippingWarning {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Off,
+        Self::Tiny,
+        Self::Small,
+        Self::Large,
+    ];
+
+    /// Fraction of clipped samples, `None` if off.
+    pub fn to_value(&self) -> Option<f32> {
+        match self {
+            Self::Off => None,
+            Self::Tiny => Some(0.0001),
+            Self::Small => Some(0.001),
+            Self::Large => Some(0.01),
+        }
+    }
+}
+
+impl std::fmt::Display for ClippingWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Off => "Off",
+            Self::Tiny => "0.01%",
+            Self::Small => "0.1%",
+            Self::Large => "1%",
+        })
+    }
+}
+
+/// Time to collect a burst of watched file changes before refreshing.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchThrottle {
+    Off,
+    #[default]
+    Short,
+    Medium,
+    Long,
+}
+
+impl WatchThrottle {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Off,
+        Self::Short,
+        Self::Medium,
+        Self::Long,
+    ];
+
+    /// Collection time in milliseconds, zero refreshes on every change.
+    pub fn to_value(&self) -> u64 {
+        match self {
+            Self::Off => 0,
+            Self::Short => 250,
+            Self::Medium => 1000,
+            Self::Long => 5000,
+        }
+    }
+}
+
+impl std::fmt::Display for WatchThrottle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Off => "Off",
+            Self::Short => "250 ms",
+            Self::Medium => "1 s",
+            Self::Long => "5 s",
+        })
+    }
+}
+
+/// Order of the gallery items, the band scan shows frequency order as contiguous tiles.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    #[default]
+    Loaded,
+    Name,
+    Size,
+    Modified,
+    SampleCount,
+    SampleRate,
+    Frequency,
+    BandScan,
+}
+
+impl SortOrder {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Loaded,
+        Self::Name,
+        Self::Size,
+        Self::Modified,
+        Self::SampleCount,
+        Self::SampleRate,
+        Self::Frequency,
+        Self::BandScan,
+    ];
+}
+
+impl std::fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Loaded => "As loaded",
+            Self::Name => "File name",
+            Self::Size => "File size",
+            Self::Modified => "Modification time",
+            Self::SampleCount => "Sample count",
+            Self::SampleRate => "Sample rate",
+            Self::Frequency => "Center frequency",
+            Self::BandScan => "Band scan tiles",
+        })
+    }
+}
+
+/// When a bulk delete or move asks for confirmation, by the number of files.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmThreshold {
+    #[default]
+    Always,
+    OverOne,
+    OverTen,
+    OverHundred,
+    Never,
+}
+
+impl ConfirmThreshold {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Always,
+        Self::OverOne,
+        Self::OverTen,
+        Self::OverHundred,
+        Self::Never,
+    ];
+
+    /// Tests if acting on `count` files needs a confirmation.
+    pub fn needs_confirm(&self, count: usize) -> bool {
+        match self {
+            Self::Always => true,
+            Self::OverOne => count > 1,
+            Self::OverTen => count > 10,
+            Self::OverHundred => count > 100,
+            Self::Never => false,
+        }
+    }
+}
+
+impl std::fmt::Display for ConfirmThreshold {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Always => "Always",
+            Self::OverOne => "More than 1 file",
+            Self::OverTen => "More than 10 files",
+            Self::OverHundred => "More than 100 files",
+            Self::Never => "Never",
+        })
+    }
+}
+
+/// Display of sample counts and file sizes.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberFormat {
+    Plain,
+    #[default]
+    Grouped,
+    Hex,
+}
+
+impl NumberFormat {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Plain,
+        Self::Grouped,
+        Self::Hex,
+    ];
+
+    pub fn format(&self, value: u64) -> String {
+        match self {
+            Self::Plain => value.to_string(),
+            Self::Grouped => {
+                let digits = value.to_string();
+                let mut grouped = String::new();
+                for (index, digit) in digits.chars().enumerate() {
+                    if index > 0 && (digits.len() - index) % 3 == 0 {
+                        grouped.push(',');
+                    }
+                    grouped.push(digit);
+                }
+                grouped
+            }
+            Self::Hex => format!("{value:#x}"),
+        }
+    }
+
+    /// File size with unit, in kB for decimal and in bytes for hex.
+    pub fn format_size(&self, bytes: u64) -> String {
+        match self {
+            Self::Hex => format!("{} B", self.format(bytes)),
+            _ => format!("{} kB", self.format(bytes / 1024)),
+        }
+    }
+}
+
+impl std::fmt::Display for NumberFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Plain => "Plain",
+            Self::Grouped => "Grouped (1,234,567)",
+            Self::Hex => "Hex (0x12d687)",
+        })
+    }
+}
+
+/// Opacity of the detection mask over the plot.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskOpacity {
+    Off,
+    Faint,
+    #[default]
+    Half,
+    Strong,
+}
+
+impl MaskOpacity {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Off,
+        Self::Faint,
+        Self::Half,
+        Self::Strong,
+    ];
+
+    pub fn to_value(&self) -> Option<f32> {
+        match self {
+            Self::Off => None,
+            Self::Faint => Some(0.25),
+            Self::Half => Some(0.5),
+            Self::Strong => Some(0.75),
+        }
+    }
+}
+
+impl std::fmt::Display for MaskOpacity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Off => "Off",
+            Self::Faint => "25 %",
+            Self::Half => "50 %",
+            Self::Strong => "75 %",
+        })
+    }
+}
+
+/// Colors of the detection mask, from low to high detector score.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskColormap {
+    #[default]
+    Heat,
+    Magenta,
+    Cyan,
+    Green,
+}
+
+impl MaskColormap {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Heat,
+        Self::Magenta,
+        Self::Cyan,
+        Self::Green,
+    ];
+
+    /// Colors for the lowest and the highest score.
+    pub fn to_ramp(&self) -> [[u8; 3]; 2] {
+        match self {
+            Self::Heat => [[255, 224, 0], [255, 0, 0]],
+            Self::Magenta => [[128, 0, 128], [255, 0, 255]],
+            Self::Cyan => [[0, 128, 128], [0, 255, 255]],
+            Self::Green => [[0, 128, 0], [0, 255, 0]],
+        }
+    }
+}
+
+impl std::fmt::Display for MaskColormap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Heat => "Heat (yellow to red)",
+            Self::Magenta => "Magenta",
+            Self::Cyan => "Cyan",
+            Self::Green => "Green",
+        })
+    }
+}
+
+/// View of a newly opened file.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitialView {
+    #[default]
+    Overview,
+    FitPeak,
+    ZoomPreset,
+}
+
+impl InitialView {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Overview,
+        Self::FitPeak,
+        Self::ZoomPreset,
+    ];
+}
+
+impl std::fmt::Display for InitialView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Overview => "Full overview",
+            Self::FitPeak => "Fit to loudest region",
+            Self::ZoomPreset => "First zoom preset",
+        })
+    }
+}
+
+/// If opening a file in the editor marks it as reviewed.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewOnOpen {
+    #[default]
+    Mark,
+    Manual,
+}
+
+impl ReviewOnOpen {
+    #[rustfmt::skip]
+    pub const VARIANTS: &[Self] = &[
+        Self::Mark,
+        Self::Manual,
+    ];
+}
+
+impl std::fmt::Display for ReviewOnOpen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Mark => "Mark as reviewed",
+            Self::Manual => "Manually only",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn view_options_round_trip() {
+        let options = ViewOptions {
+            fftn: Some(FftSize::Size1024),
+            windowf: Some(WindowFunctions::VARIANTS[5]),
+            gain: Some(DbGain::Gain12),
+            range: Some(DbRange::VARIANTS[3]),
+            colormap: Some(Colormap::VARIANTS[6]),
+            orientation: Some(Orientation::Geyser),
+        };
+        assert_eq!(ViewOptions::from_table(&options.to_table()), options);
+        assert_eq!(ViewOptions::from_table(&ViewOptions::default().to_table()), ViewOptions::default());
+    }
+
+    #[test]
+    fn view_options_round_trip_every_variant() {
+        fn round_trip(options: ViewOptions) {
+            assert_eq!(ViewOptions::from_table(&options.to_table()), options);
+        }
+        for &fftn in FftSize::VARIANTS {
+            round_trip(ViewOptions { fftn: Some(fftn), ..Default::default() });
+        }
+        for &windowf in WindowFunctions::VARIANTS {
+            round_trip(ViewOptions { windowf: Some(windowf), ..Default::default() });
+        }
+        for &gain in DbGain::VARIANTS {
+            round_trip(ViewOptions { gain: Some(gain), ..Default::default() });
+        }
+        for &range in DbRange::VARIANTS {
+            round_trip(ViewOptions { range: Some(range), ..Default::default() });
+        }
+        for &colormap in Colormap::VARIANTS {
+            round_trip(ViewOptions { colormap: Some(colormap), ..Default::default() });
+        }
+        for &orientation in Orientation::VARIANTS {
+            round_trip(ViewOptions { orientation: Some(orientation), ..Default::default() });
+        }
+    }
+
+    #[test]
+    fn view_options_skip_unknown() {
+        let table: toml::Table = toml::from_str("fft = \"N=256\"\nfoo = \"bar\"\ngain = \"+99 dB\"\nrange = 6").unwrap();
+        let options = ViewOptions::from_table(&table);
+        assert_eq!(options, ViewOptions { fftn: Some(FftSize::Size256), ..Default::default() });
+    }
+}
@@ -5,6 +5,7 @@
 //!
 //! The foreign function interface (C-API) which exposes this library.
 
+use std::cell::Cell;
 use std::ffi::{CString, c_char, c_void};
 
 #[allow(non_camel_case_types)]
@@ -128,8 +129,11 @@ unsafe extern "C" {
 
 use std::path::{Path, PathBuf};
 
+use crate::dirs;
+use crate::options::Colormap;
+
 #[rustfmt::skip]
-const SAMPLE_FORMAT: &[&str] = &[
+pub const SAMPLE_FORMAT: &[&str] = &[
     "CU4",
     "CS4",
     "CU8",
@@ -146,6 +150,66 @@ const SAMPLE_FORMAT: &[&str] = &[
     "CF64",
 ];
 
+/// Bytes per I/Q pair of a sample format, `None` if unknown.
+pub fn sample_pair_size(sample_format: &str) -> Option<usize> {
+    match sample_format {
+        "CU4" | "CS4" => Some(1),
+        "CU8" | "CS8" => Some(2),
+        "CU12" | "CS12" => Some(3),
+        "CU16" | "CS16" => Some(4),
+        "CU32" | "CS32" | "CF32" => Some(8),
+        "CU64" | "CS64" | "CF64" => Some(16),
+        _ => None,
+    }
+}
+
+/// Duration in seconds, `None` without samples or a sample rate.
+pub fn duration(sample_count: u64, sample_rate: f64) -> Option<f64> {
+    (sample_count > 0 && sample_rate > 0.0 && sample_rate.is_finite()).then(|| sample_count as f64 / sample_rate)
+}
+
+/// Power levels in dB of a spectrogram region, one row per frequency bin and one column per time step.
+pub struct RegionLevels {
+    /// Start sample of each column.
+    pub samples: Vec<u64>,
+    /// Frequency in Hz of each row.
+    pub freqs: Vec<f64>,
+    pub levels: Vec<Vec<f32>>,
+    /// The levels are quantized to steps of this many dB.
+    pub step: f32,
+}
+
+/// A grayscale rendering of a view, see `Plot::render_levels()` and `Plot::render_view_levels()`.
+/// The levels are quantized to 256 steps over `db_span`, e.g. about 0.6 dB over 150 dB.
+pub struct LevelRender {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<u32>,
+    db_span: f32,
+    /// Start sample of each column.
+    samples: Vec<u64>,
+    /// Frequency in Hz of each row.
+    freqs: Vec<f64>,
+}
+
+impl LevelRender {
+    /// The brightness of all pixels, row by row.
+    pub fn brightnesses(&self) -> impl Iterator<Item = u8> + '_ {
+        self.pixels.iter().map(|pixel| (pixel & 0xff) as u8)
+    }
+
+    /// The level step in dB, `db_span` over 255.
+    pub fn step(&self) -> f32 {
+        self.db_span / 255.0
+    }
+
+    /// The power level at a pixel in dB, -`db_span` to 0 dB.
+    pub fn level(&self, x: usize, y: usize) -> f32 {
+        ((self.pixels[y * self.width + x] & 0xff) as f32 / 255.0 - 1.0) * self.db_span
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct FileInfo {
     pub sample_format: &'static str,
     pub sample_count: u64,
@@ -161,7 +225,10 @@ pub struct PlotMarker {
 
 pub struct Plot {
     path: PathBuf,
+    format: Option<&'static str>,
     plot: *mut splt_t,
+    dc_notch: Cell<bool>,
+    enhance: Cell<u8>,
 }
 
 impl Drop for Plot {
@@ -174,20 +241,45 @@ impl Drop for Plot {
 
 #[allow(unused)]
 impl Plot {
-    pub fn with_path(path: impl AsRef<Path>) -> Self {
+    /// Opens the file, as `format` (e.g. "CS16") if given, otherwise by its extension.
+    pub fn with_path(path: impl AsRef<Path>, format: Option<&'static str>) -> Self {
         let path = path.as_ref();
-        let plot = Self::create_plot(path);
+        let plot = Self::create_plot(path, format);
 
         Self {
             path: path.to_path_buf(),
+            format,
             plot: plot,
+            dc_notch: Cell::new(false),
+            enhance: Cell::new(0),
         }
     }
 
-    pub fn open(&mut self, path: impl AsRef<Path>) {
+    /// A second plot on the same file with the same view: zoom, origin, levels, FFT size and layout.
+    /// Color map and FFT window can't be read back, the caller sets them.
+    pub fn duplicate(&self) -> Self {
+        let plot = Self::with_path(&self.path, self.format);
+        plot.set_fft_size(self.fft_size());
+        plot.set_db_gain(self.db_gain());
+        plot.set_db_range(self.db_range());
+        plot.set_layout_direction(self.layout_direction());
+        plot.set_layout_histo_width(self.layout_histo_width());
+        plot.set_layout_deci_height(self.layout_deci_height());
+        plot.set_layout_ask_height(self.layout_ask_height());
+        plot.set_dc_notch(self.dc_notch());
+        plot.set_enhance(self.enhance());
+        plot.set_zoom(self.zoom());
+        plot.set_origin(self.origin());
+        plot
+    }
+
+    pub fn open(&mut self, path: impl AsRef<Path>, format: Option<&'static str>) {
         let path = path.as_ref();
-        let plot = Self::create_plot(path);
+        let plot = Self::create_plot(path, format);
+        // keep the layout size until the next draw, the initial view is fit to it
+        unsafe { splt_set_layout_size(plot, self.width(), self.height()) };
         self.path = path.to_path_buf();
+        self.format = format;
         self.plot = plot
     }
 
@@ -195,16 +287,31 @@ impl Plot {
         self.path.as_path()
     }
 
-    pub fn thumbnail(path: impl AsRef<Path>) -> (RawBitmap, FileInfo) {
+    /// The sample format the file was opened as, `None` if detected from the extension.
+    pub fn format(&self) -> Option<&'static str> {
+        self.format
+    }
+
+    /// Renders a 256px thumbnail, at `supersample` times the size and then scaled down.
+    pub fn thumbnail(
+        path: impl AsRef<Path>,
+        format: Option<&'static str>,
+        supersample: u32,
+        cmap: u32,
+        fft_size: u32,
+    ) -> (RawBitmap, FileInfo) {
         let path = path.as_ref();
-        let plot = Self::create_plot(path);
+        let plot = Self::create_plot(path, format);
 
-        let width = 256;
-        let height = 256;
+        let supersample = supersample.clamp(1, 4);
+        let width = 256 * supersample;
+        let height = 256 * supersample;
 
         // Setup Spectroplot
         unsafe {
             // splt_set_dark_theme(plot, true);
+            splt_set_cmap(plot, cmap);
+            splt_set_fft_size(plot, fft_size);
             splt_set_layout_size(plot, width, height);
         }
 
@@ -226,13 +333,221 @@ impl Plot {
             sample_rate: unsafe { splt_get_sample_rate(plot) },
         };
 
-        (RawBitmap::from_rgba(pixels, width, height), file_info)
+        let bitmap = RawBitmap::from_rgba(pixels, width, height);
+        (bitmap.downsample(supersample as usize), file_info)
+    }
+
+    /// Reads the file info without rendering, `None` if the file can't be decoded.
+    pub fn probe(path: impl AsRef<Path>, format: Option<&'static str>) -> Option<FileInfo> {
+        let path = Self::format_path(path.as_ref(), format);
+        let path_str_c = CString::new(path.as_os_str().as_encoded_bytes()).ok()?;
+        let plot = unsafe { splt_create(path_str_c.as_ptr()) };
+        if plot.is_null() {
+            return None;
+        }
+
+        let format = unsafe { splt_get_sample_format(plot) } as usize;
+        let file_info = FileInfo {
+            sample_format: SAMPLE_FORMAT.get(format).copied().unwrap_or("unknown"),
+            sample_count: unsafe { splt_get_sample_count(plot) },
+            center_freq: unsafe { splt_get_center_freq(plot) },
+            sample_rate: unsafe { splt_get_sample_rate(plot) },
+        };
+        unsafe { splt_destroy(plot) };
+
+        Some(file_info)
+    }
+
+    /// Coarse per-column peak level in dB (-`db_span` to 0 dB) over the whole file,
+    /// rendered at zoom 0 with time along x. Independent of the gain and range, so it is
+    /// rendered once per file. Use a grayscale `cmap` so the pixel brightness follows the signal power.
+    pub fn level_profile(
+        path: impl AsRef<Path>,
+        format: Option<&'static str>,
+        columns: u32,
+        cmap: u32,
+        db_span: f32,
+    ) -> Vec<f32> {
+        let plot = Self::create_plot(path, format);
+
+        // Setup Spectroplot, plot area only
+        unsafe {
+            splt_set_cmap(plot, cmap);
+            splt_set_db_gain(plot, 0.0);
+            splt_set_db_range(plot, db_span);
+            splt_set_layout_direction(plot, 0);
+            splt_set_layout_histo_width(plot, 0);
+            splt_set_layout_deci_height(plot, 0);
+            splt_set_layout_ask_height(plot, 0);
+            splt_set_layout_size(plot, columns, 64);
+        }
+
+        let width = unsafe { splt_get_layout_width(plot) } as usize;
+        let height = unsafe { splt_get_layout_height(plot) } as usize;
+
+        let mut pixels = vec![0; width * height];
+
+        // Run Spectroplot
+        unsafe {
+            splt_draw(plot, pixels.as_mut_ptr(), width as u32, height as u32);
+            splt_destroy(plot);
+        }
+
+        (0..width)
+            .map(|x| {
+                let max = (0..height).map(|y| pixels[y * width + x] & 0xff).max();
+                (max.unwrap_or_default() as f32 / 255.0 - 1.0) * db_span
+            })
+            .collect()
+    }
+
+    /// Renders the current view again, plot area only with the time along x, in a grayscale `cmap`
+    /// so the pixel brightness follows the power. The time axis keeps its length so zoom and origin
+    /// select the same samples, there is one row per FFT bin within reason.
+    fn render_levels(&self, cmap: u32, db_span: f32) -> LevelRender {
+        let plot = Self::create_plot(&self.path, self.format);
+        let across = if self.layout_direction() == 0 { self.width() } else { self.height() };
+        let rows = self.fft_size().clamp(64, 1024);
+
+        // Setup Spectroplot, plot area only
+        unsafe {
+            splt_set_cmap(plot, cmap);
+            splt_set_fft_size(plot, self.fft_size());
+            splt_set_fft_window(plot, splt_get_fft_window(self.plot));
+            splt_set_db_gain(plot, 0.0);
+            splt_set_db_range(plot, db_span);
+            splt_set_layout_direction(plot, 0);
+            splt_set_layout_histo_width(plot, 0);
+            splt_set_layout_deci_height(plot, 0);
+            splt_set_layout_ask_height(plot, 0);
+            splt_set_layout_size(plot, across.max(1), rows);
+            splt_set_zoom(plot, self.zoom());
+            splt_set_origin(plot, self.origin());
+        }
+
+        let width = unsafe { splt_get_layout_width(plot) } as usize;
+        let height = unsafe { splt_get_layout_height(plot) } as usize;
+
+        let mut pixels = vec![0; width * height];
+
+        // Run Spectroplot, read the axes of the rendering along
+        let samples = (0..width).map(|x| unsafe { splt_get_sample_at_pos(plot, x as u32, 0) }).collect();
+        let freqs = (0..height).map(|y| unsafe { splt_get_freq_at_pos(plot, 0, y as u32) }).collect();
+        unsafe {
+            splt_draw(plot, pixels.as_mut_ptr(), width as u32, height as u32);
+            splt_destroy(plot);
+        }
+
+        LevelRender {
+            width,
+            height,
+            pixels,
+            db_span,
+            samples,
+            freqs,
+        }
+    }
+
+    /// Distribution of the power levels in the current view, `bins` spanning -`db_span` dB to 0 dB.
+    pub fn power_histogram(&self, bins: usize, cmap: u32, db_span: f32) -> Vec<u32> {
+        let render = self.render_levels(cmap, db_span);
+
+        let mut histogram = vec![0; bins.max(1)];
+        for level in render.brightnesses() {
+            histogram[level as usize * histogram.len() / 256] += 1;
+        }
+        histogram
+    }
+
+    /// Occupied bandwidth in Hz of the current view, the band holding `fraction` of the power.
+    /// Sums the power per frequency row of the level rendering and cuts half of the remainder
+    /// from each edge. With several signals this spans all of them.
+    pub fn occupied_bandwidth(&self, fraction: f64, cmap: u32, db_span: f32) -> f64 {
+        let render = self.render_levels(cmap, db_span);
+
+        // linear power per frequency row
+        let power: Vec<f64> = (0..render.height)
+            .map(|y| {
+                (0..render.width)
+                    .map(|x| 10f64.powf(render.level(x, y) as f64 / 10.0))
+                    .sum()
+            })
+            .collect();
+        let total: f64 = power.iter().sum();
+        if total <= 0.0 || power.is_empty() {
+            return 0.0;
+        }
+
+        // cut (1 - fraction) / 2 of the power from each edge
+        let cut = total * (1.0 - fraction) / 2.0;
+        let edge = |rows: &mut dyn Iterator<Item = (usize, &f64)>| {
+            let mut sum = 0.0;
+            rows.find(|(_, p)| {
+                sum += *p;
+                sum > cut
+            })
+            .map_or(0, |(index, _)| index)
+        };
+        let low = edge(&mut power.iter().enumerate());
+        let high = edge(&mut power.iter().enumerate().rev());
+        (high.saturating_sub(low) + 1) as f64 / power.len() as f64 * self.sample_rate()
+    }
+
+    /// Power levels of the region spanned by the samples and frequencies of two corners, in dB.
+    /// Uses the level rendering, so the levels are quantized to 256 steps over `db_span` below 0 dB,
+    /// at the time resolution of the view.
+    pub fn region_levels(&self, corner1: (u64, f64), corner2: (u64, f64), cmap: u32, db_span: f32) -> RegionLevels {
+        let render = self.render_levels(cmap, db_span);
+
+        // the axes of the rendering select the region
+        let (sample_lo, sample_hi) = (corner1.0.min(corner2.0), corner1.0.max(corner2.0));
+        let (freq_lo, freq_hi) = (corner1.1.min(corner2.1), corner1.1.max(corner2.1));
+        let columns: Vec<(usize, u64)> = render
+            .samples
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|&(_, sample)| (sample_lo..=sample_hi).contains(&sample))
+            .collect();
+        let rows: Vec<(usize, f64)> = render
+            .freqs
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|&(_, freq)| (freq_lo..=freq_hi).contains(&freq))
+            .collect();
+
+        let levels = rows
+            .iter()
+            .map(|&(y, _)| columns.iter().map(|&(x, _)| render.level(x, y)).collect())
+            .collect();
+        RegionLevels {
+            samples: columns.into_iter().map(|(_, sample)| sample).collect(),
+            freqs: rows.into_iter().map(|(_, freq)| freq).collect(),
+            levels,
+            step: render.step(),
+        }
     }
 
-    fn create_plot(path: impl AsRef<Path>) -> *mut splt_t {
+    /// The path libtriq reads: a link with the extension of `format`, if given.
+    fn format_path(path: &Path, format: Option<&str>) -> PathBuf {
+        let Some(format) = format else {
+            return path.to_path_buf();
+        };
+        match dirs::format_link(path, format) {
+            Ok(link) => link,
+            Err(err) => {
+                println!("Format link error {err:?}");
+                path.to_path_buf()
+            }
+        }
+    }
+
+    fn create_plot(path: impl AsRef<Path>, format: Option<&str>) -> *mut splt_t {
         // FIXME: Check if this is a file or a folder...
 
-        let path_str_c = CString::new(path.as_ref().as_os_str().as_encoded_bytes()).unwrap();
+        let path = Self::format_path(path.as_ref(), format);
+        let path_str_c = CString::new(path.as_os_str().as_encoded_bytes()).unwrap();
 
         let plot = unsafe { splt_create(path_str_c.as_ptr()) };
 
@@ -247,6 +562,12 @@ impl Plot {
     pub fn zoom(&self) -> u32 {
         unsafe { splt_get_zoom(self.plot) }
     }
+    pub fn visible_samples(&self) -> u64 {
+        unsafe { splt_get_visible_samples(self.plot) }
+    }
+    pub fn origin(&self) -> u32 {
+        unsafe { splt_get_origin(self.plot) }
+    }
     pub fn sample_format(&self) -> u8 {
         unsafe { splt_get_sample_format(self.plot) }
     }
@@ -274,10 +595,31 @@ impl Plot {
     pub fn height(&self) -> u32 {
         unsafe { splt_get_layout_height(self.plot) }
     }
+    pub fn layout_direction(&self) -> u8 {
+        unsafe { splt_get_layout_direction(self.plot) }
+    }
+    pub fn dc_notch(&self) -> bool {
+        self.dc_notch.get()
+    }
+    pub fn enhance(&self) -> u8 {
+        self.enhance.get()
+    }
+    pub fn layout_histo_width(&self) -> u32 {
+        unsafe { splt_get_layout_histo_width(self.plot) }
+    }
+    pub fn layout_deci_height(&self) -> u32 {
+        unsafe { splt_get_layout_deci_height(self.plot) }
+    }
+    pub fn layout_ask_height(&self) -> u32 {
+        unsafe { splt_get_layout_ask_height(self.plot) }
+    }
 
     pub fn set_zoom(&self, zoom: u32) {
         unsafe { splt_set_zoom(self.plot, zoom) }
     }
+    pub fn set_origin(&self, origin: u32) {
+        unsafe { splt_set_origin(self.plot, origin) }
+    }
     pub fn set_db_gain(&self, db_gain: f32) {
         unsafe { splt_set_db_gain(self.plot, db_gain) }
     }
@@ -293,6 +635,14 @@ impl Plot {
     pub fn set_fft_window(&self, fft_window_name: u8) {
         unsafe { splt_set_fft_window(self.plot, fft_window_name) }
     }
+    /// Suppress the DC spike in rendered bitmaps, see `notch_dc()`.
+    pub fn set_dc_notch(&self, enabled: bool) {
+        self.dc_notch.set(enabled)
+    }
+    /// Sets the contrast enhancement: 0 off, 1 per-row normalize, 2 unsharp mask.
+    pub fn set_enhance(&self, mode: u8) {
+        self.enhance.set(mode)
+    }
     pub fn set_layout_direction(&self, direction: u8) {
         unsafe { splt_set_layout_direction(self.plot, direction) }
     }
@@ -322,6 +672,225 @@ impl Plot {
         unsafe { splt_set_zoom_at(self.plot, x, y, zoom) }
     }
 
+    /// Plot area as `(x, y, width, height)` within the layout, without the histogram and the strips.
+    /// The histogram is right of the plot area, the deci and ASK strips are below it.
+    pub fn plot_rect(&self) -> (u32, u32, u32, u32) {
+        let width = self.width().saturating_sub(self.layout_histo_width());
+        let strips = self.layout_deci_height() + self.layout_ask_height();
+        (0, 0, width, self.height().saturating_sub(strips))
+    }
+
+    /// Tests if `x`/`y` is in the plot area, not on the axes, the histogram or the strips.
+    pub fn in_plot_rect(&self, x: u32, y: u32) -> bool {
+        let (left, top, width, height) = self.plot_rect();
+        (left..left + width).contains(&x) && (top..top + height).contains(&y)
+    }
+
+    /// Tests if a `sample` and `freq` is within the current view of the plot area.
+    pub fn is_in_view(&self, sample: u64, freq: f64) -> bool {
+        let (left, top, width, height) = self.plot_rect();
+        if width == 0 || height == 0 {
+            return false;
+        }
+        // opposite corners span both axes in either layout direction
+        let (right, bottom) = (left + width - 1, top + height - 1);
+        let (sample1, sample2) = (self.sample_at_pos(left, top), self.sample_at_pos(right, bottom));
+        let (freq1, freq2) = (self.freq_at_pos(left, top), self.freq_at_pos(right, bottom));
+        (sample1.min(sample2)..=sample1.max(sample2)).contains(&sample)
+            && (freq1.min(freq2)..=freq1.max(freq2)).contains(&freq)
+    }
+
+    /// Extents of the plot area along the time axis and along the frequency axis.
+    fn axis_extents(&self) -> (u32, u32) {
+        let (_, _, width, height) = self.plot_rect();
+        if self.layout_direction() == 0 { (width, height) } else { (height, width) }
+    }
+
+    /// Fits the plot width: the whole time extent with time along x, the frequency extent otherwise.
+    pub fn zoom_to_fit_width(&self) {
+        if self.layout_direction() == 0 {
+            self.zoom_to_span(0, self.sample_count());
+        } else {
+            self.fit_fft_size();
+        }
+    }
+
+    /// Fits the plot height: the frequency extent with time along x, the whole time extent otherwise.
+    pub fn zoom_to_fit_height(&self) {
+        if self.layout_direction() == 0 {
+            self.fit_fft_size();
+        } else {
+            self.zoom_to_span(0, self.sample_count());
+        }
+    }
+
+    /// Sets the FFT size to one bin per pixel of the frequency axis, the full band at the plot resolution.
+    fn fit_fft_size(&self) {
+        let (_, extent) = self.axis_extents();
+        self.set_fft_size((1 << extent.max(1).ilog2()).clamp(16, 1024));
+    }
+
+    /// Zoom so that the samples from `start` to `end` fill the time axis of the current layout direction.
+    pub fn zoom_to_span(&self, start: u64, end: u64) {
+        let (extent, _) = self.axis_extents();
+        if self.layout_direction() == 0 {
+            self.zoom_to_fit(start, end, extent, 0, self.height() / 2);
+        } else {
+            self.zoom_to_fit(start, end, extent, self.width() / 2, 0);
+        }
+    }
+
+    /// Set samples per pixel so `(end - start) / zoom <= extent`, then pan to the `start` sample.
+    fn zoom_to_fit(&self, start: u64, end: u64, extent: u32, x: u32, y: u32) {
+        let zoom = end.saturating_sub(start).div_ceil(extent.max(1) as u64);
+        self.set_zoom(zoom.clamp(1, u32::MAX as u64) as u32);
+        self.pan_to_pos(start, x, y);
+    }
+
+    /// Round sample indices in the visible range, as position along the time axis and sample.
+    /// Time is along x with direction 0, along y otherwise.
+    pub fn sample_ticks(&self, max_ticks: u64) -> Vec<(u32, u64)> {
+        let time_along_x = self.layout_direction() == 0;
+        let (extent, _) = self.axis_extents();
+        let mid = if time_along_x { self.height() / 2 } else { self.width() / 2 };
+        if extent < 2 {
+            return vec![];
+        }
+        let sample_at = |pos| {
+            if time_along_x {
+                self.sample_at_pos(pos, mid)
+            } else {
+                self.sample_at_pos(mid, pos)
+            }
+        };
+        let first = sample_at(0);
+        let last = sample_at(extent - 1);
+        let (lo, hi) = (first.min(last), first.max(last));
+        if hi == lo {
+            return vec![];
+        }
+
+        // a 1-2-5 step to get at most `max_ticks`
+        let raw = ((hi - lo) / max_ticks.max(1)).max(1);
+        let decade = 10u64.pow(raw.ilog10());
+        let step = [1, 2, 5, 10].map(|m| m * decade).into_iter().find(|&step| step >= raw).unwrap_or(raw);
+
+        let scale = (extent - 1) as f64 / (last as f64 - first as f64);
+        (lo.div_ceil(step)..=hi / step)
+            .map(|n| n * step)
+            .map(|sample| (((sample as f64 - first as f64) * scale).round() as u32, sample))
+            .collect()
+    }
+
+    /// Position of the power maximum within `radius` px of `x`/`y`, in the current view.
+    /// Draws the view once more with a grayscale `cmap` so the brightness follows the power.
+    /// `None` if no pixel is at least `min_rise` levels (of 255) above the one at `x`/`y`.
+    /// The search stays within the plot area.
+    pub fn peak_near(&self, x: u32, y: u32, radius: u32, cmap: u32, min_rise: u32) -> Option<(u32, u32)> {
+        let width = self.width() as usize;
+        let height = self.height() as usize;
+        if !self.in_plot_rect(x, y) {
+            return None;
+        }
+        let (left, top, plot_width, plot_height) = self.plot_rect();
+
+        let mut pixels = vec![0; width * height];
+
+        // Run Spectroplot, then restore the colormap
+        unsafe {
+            let view_cmap = splt_get_cmap(self.plot);
+            splt_set_cmap(self.plot, cmap);
+            splt_draw(self.plot, pixels.as_mut_ptr(), width as u32, height as u32);
+            splt_set_cmap(self.plot, view_cmap);
+        }
+
+        let level = |x: u32, y: u32| pixels[y as usize * width + x as usize] & 0xff;
+        let xs = x.saturating_sub(radius).max(left)..=(x + radius).min(left + plot_width - 1);
+        let ys = y.saturating_sub(radius).max(top)..=(y + radius).min(top + plot_height - 1);
+        let (peak_x, peak_y) = ys
+            .flat_map(|py| xs.clone().map(move |px| (px, py)))
+            .max_by_key(|&(px, py)| level(px, py))?;
+
+        (level(peak_x, peak_y) >= level(x, y) + min_rise).then_some((peak_x, peak_y))
+    }
+
+    /// A grayscale rendering of the view as laid out, at 0 dB gain over a `db_span` range, to read
+    /// the power level at a view position with `LevelRender::level()`. The axes are not read along.
+    pub fn render_view_levels(&self, cmap: u32, db_span: f32) -> LevelRender {
+        let width = self.width() as usize;
+        let height = self.height() as usize;
+
+        let mut pixels = vec![0; width * height];
+
+        // Run Spectroplot, then restore the colormap and levels
+        unsafe {
+            let (view_cmap, view_gain, view_range) = (splt_get_cmap(self.plot), self.db_gain(), self.db_range());
+            splt_set_cmap(self.plot, cmap);
+            splt_set_db_gain(self.plot, 0.0);
+            splt_set_db_range(self.plot, db_span);
+            splt_draw(self.plot, pixels.as_mut_ptr(), width as u32, height as u32);
+            splt_set_cmap(self.plot, view_cmap);
+            splt_set_db_gain(self.plot, view_gain);
+            splt_set_db_range(self.plot, view_range);
+        }
+
+        LevelRender {
+            width,
+            height,
+            pixels,
+            db_span,
+            samples: Vec::new(),
+            freqs: Vec::new(),
+        }
+    }
+
+    /// Pixel position of a `sample` and `freq`, the inverse of `sample_at_pos()` and `freq_at_pos()`.
+    /// Searches along the axes of the current layout direction, clamps to the layout.
+    pub fn pos_of(&self, sample: u64, freq: f64) -> (u32, u32) {
+        // binary search on a monotonic axis, ascending or descending
+        fn search(len: u32, at: impl Fn(u32) -> f64, value: f64) -> u32 {
+            let last = len.saturating_sub(1);
+            let ascending = at(last) >= at(0);
+            let (mut low, mut high) = (0, last);
+            while low < high {
+                let mid = (low + high) / 2;
+                if (at(mid) < value) == ascending {
+                    low = mid + 1;
+                } else {
+                    high = mid;
+                }
+            }
+            low
+        }
+
+        let (width, height) = (self.width(), self.height());
+        let sample = sample as f64;
+        if self.layout_direction() == 0 {
+            let x = search(width, |x| self.sample_at_pos(x, height / 2) as f64, sample);
+            let y = search(height, |y| self.freq_at_pos(x, y), freq);
+            (x, y)
+        } else {
+            let y = search(height, |y| self.sample_at_pos(width / 2, y) as f64, sample);
+            let x = search(width, |x| self.freq_at_pos(x, y), freq);
+            (x, y)
+        }
+    }
+
+    /// Sample span `(start, end)` of each pixel along the time axis of the plot area.
+    pub fn time_spans(&self) -> Vec<(u64, u64)> {
+        let (left, top, width, height) = self.plot_rect();
+        let (x, y) = (left + width / 2, top + height / 2);
+        if self.layout_direction() == 0 {
+            (left..left + width)
+                .map(|x| (self.sample_at_pos(x, y), self.sample_at_pos(x + 1, y)))
+                .collect()
+        } else {
+            (top..top + height)
+                .map(|y| (self.sample_at_pos(x, y), self.sample_at_pos(x, y + 1)))
+                .collect()
+        }
+    }
+
     pub fn is_nearby(&self, sample: u64, freq: f64, x: u32, y: u32) -> bool {
         let margin = 10; // in px
         // get cursor x/y sample/freq
@@ -336,7 +905,9 @@ impl Plot {
         sample.abs_diff(c_sample) <= d_sample || (freq - c_freq).abs() <= d_freq
     }
 
-    pub fn infos(&self) -> Vec<String> {
+    /// Info texts, the level scale in dBm if a calibration `dbm_offset` is given.
+    /// The sample count is formatted with `format_count`.
+    pub fn infos(&self, dbm_offset: Option<f32>, format_count: impl Fn(u64) -> String) -> Vec<String> {
         // 'File name', value: strip(file.name) })
         // 'File type', value: file.type || 'n/a' })
         // 'File size', value: `${file.size} bytes` })
@@ -352,16 +923,31 @@ impl Plot {
         vec![
             format!("{}", SAMPLE_FORMAT[self.sample_format() as usize]),
             format!("{:.6} MHz", self.center_freq() / 1000000.0),
-            format!("{:.3} kHz", self.sample_rate() / 1000.0),
+            match self.sample_rate() {
+                rate if rate > 0.0 => format!("{:.3} kHz", rate / 1000.0),
+                _ => "— kHz".into(),
+            },
             format!("1px = {} smps", self.zoom()),
-            format!("{} S", self.sample_count()),
-            format!("{:.3} s", self.sample_count() as f64 / self.sample_rate()),
-            format!("-{}+{} dBFS", self.db_range(), self.db_gain()),
+            format!("{} S", format_count(self.sample_count())),
+            match duration(self.sample_count(), self.sample_rate()) {
+                Some(secs) => format!("{secs:.3} s"),
+                None => "— s".into(),
+            },
+            match dbm_offset {
+                Some(offset) => {
+                    let top = offset - self.db_gain();
+                    format!("{:.1} to {:.1} dBm", top - self.db_range(), top)
+                }
+                None => format!("-{}+{} dBFS", self.db_range(), self.db_gain()),
+            },
             format!("FFT {}", self.fft_size()),
         ]
     }
 
-    pub fn to_guides_bitmap(&self, marker: PlotMarker, x: usize, y: usize) -> RawBitmap {
+    /// Draws the guides of the cursor at `x`/`y`, measured from the last of the `markers`.
+    /// The other markers are drawn as guides of their own, with the cursor on the marker,
+    /// markers outside of the view are skipped.
+    pub fn to_guides_bitmap(&self, markers: &[PlotMarker], x: usize, y: usize) -> RawBitmap {
         let width = unsafe { splt_get_layout_width(self.plot) } as usize;
         let height = unsafe { splt_get_layout_height(self.plot) } as usize;
 
@@ -370,8 +956,28 @@ impl Plot {
         // Run Spectroplot
         // abort if x/y is default
         if x != 0 || y != 0 {
+            let (last, others) = match markers.split_last() {
+                Some((last, others)) => (*last, others),
+                None => (PlotMarker::default(), &[][..]),
+            };
             unsafe {
-                splt_draw_guides(self.plot, pixels.as_mut_ptr(), width as u32, height as u32, marker.sample, marker.freq, x as u32, y as u32);
+                splt_draw_guides(self.plot, pixels.as_mut_ptr(), width as u32, height as u32, last.sample, last.freq, x as u32, y as u32);
+            }
+            // one scratch buffer for the other markers, merged into the guides after each
+            let mut guides = Vec::new();
+            for marker in others.iter().filter(|marker| self.is_in_view(marker.sample, marker.freq)) {
+                let (mx, my) = self.pos_of(marker.sample, marker.freq);
+                guides.clear();
+                guides.resize(width * height, 0);
+                unsafe {
+                    splt_draw_guides(self.plot, guides.as_mut_ptr(), width as u32, height as u32, marker.sample, marker.freq, mx, my);
+                }
+                // keep what is drawn already, guides are opaque where set
+                for (pixel, &guide) in pixels.iter_mut().zip(&guides) {
+                    if *pixel == 0 {
+                        *pixel = guide;
+                    }
+                }
             }
         }
 
@@ -394,8 +1000,198 @@ impl Plot {
             splt_draw(self.plot, pixels.as_mut_ptr(), width as u32, height as u32);
         }
 
+        match self.enhance.get() {
+            1 => self.normalize_lines(&mut pixels, width, height, true),
+            3 => self.normalize_lines(&mut pixels, width, height, false),
+            _ if self.dc_notch.get() => self.notch_dc(&mut pixels, width, height),
+            _ => {}
+        }
+        if self.enhance.get() == 2 {
+            unsharp_mask(&mut pixels, width, height, self.plot_rect());
+        }
+
         RawBitmap::from_rgba(pixels, width, height)
     }
+
+    /// Shifts the level of each frequency line (`rows`) or time column so their medians match,
+    /// evens out a slanted noise floor or gain steps over time (AGC). The levels are read from a
+    /// grayscale rendering of the view and colormapped again, within the plot area only.
+    /// The offsets are in colormap steps, not in dB.
+    fn normalize_lines(&self, pixels: &mut [u32], width: usize, height: usize, rows: bool) {
+        let mut levels = self.draw_levels(width, height);
+        let (left, top, plot_width, plot_height) = self.plot_rect();
+        let (left, top) = (left as usize, top as usize);
+        let (plot_width, plot_height) = ((plot_width as usize).min(width), (plot_height as usize).min(height));
+        if plot_width == 0 || plot_height == 0 {
+            return;
+        }
+        let in_rect = (top..top + plot_height).flat_map(|y| (left..left + plot_width).map(move |x| y * width + x));
+        let lut = colormap_lut(&levels, pixels, in_rect.clone());
+
+        if self.dc_notch.get() {
+            self.notch_dc(&mut levels, width, height);
+        }
+        // frequency is along y with time along x (direction 0), along x otherwise
+        let along_y = (self.layout_direction() == 0) == rows;
+        let (extent, across) = if along_y { (plot_height, plot_width) } else { (plot_width, plot_height) };
+        let index = |pos: usize, i: usize| {
+            if along_y { (top + pos) * width + left + i } else { (top + i) * width + left + pos }
+        };
+        equalize_medians(&mut levels, extent, across, index);
+
+        for i in in_rect {
+            pixels[i] = lut[(levels[i] & 0xff) as usize];
+        }
+    }
+
+    /// Renders the view in grayscale, the brightness of a pixel is its level on the colormap.
+    fn draw_levels(&self, width: usize, height: usize) -> Vec<u32> {
+        let mut levels = vec![0; width * height];
+        unsafe {
+            let view_cmap = splt_get_cmap(self.plot);
+            splt_set_cmap(self.plot, Colormap::Grayscale.to_value() as u32);
+            splt_draw(self.plot, levels.as_mut_ptr(), width as u32, height as u32);
+            splt_set_cmap(self.plot, view_cmap);
+        }
+        levels
+    }
+
+    /// Replaces the lines around the center frequency with the average of their neighbors.
+    /// A post-process on the rendered pixels, libtriq has no DC notch of its own.
+    fn notch_dc(&self, pixels: &mut [u32], width: usize, height: usize) {
+        // frequency is along y with time along x (direction 0), along x otherwise
+        let freq_along_y = self.layout_direction() == 0;
+        let (extent, across) = if freq_along_y {
+            (height, width.saturating_sub(self.layout_histo_width() as usize))
+        } else {
+            (width, height)
+        };
+        if extent < 8 || across == 0 {
+            return;
+        }
+        let freq_at = |pos: usize| {
+            let mid = across as u32 / 2;
+            if freq_along_y {
+                self.freq_at_pos(mid, pos as u32)
+            } else {
+                self.freq_at_pos(pos as u32, mid)
+            }
+        };
+
+        // frequencies might be relative to the center or absolute
+        let (first, last) = (freq_at(0), freq_at(extent - 1));
+        let center = if first.min(last) <= 0.0 && first.max(last) >= 0.0 {
+            0.0
+        } else {
+            self.center_freq()
+        };
+        let Some(dc) = (1..extent).find(|&pos| (freq_at(pos - 1) < center) != (freq_at(pos) < center)) else {
+            return;
+        };
+        if dc < 3 || dc + 3 >= extent {
+            return;
+        }
+
+        let index = |pos: usize, i: usize| if freq_along_y { pos * width + i } else { i * width + pos };
+        for i in 0..across {
+            let a = pixels[index(dc - 3, i)].to_le_bytes();
+            let b = pixels[index(dc + 2, i)].to_le_bytes();
+            let avg = u32::from_le_bytes([0, 1, 2, 3].map(|c| ((a[c] as u16 + b[c] as u16) / 2) as u8));
+            for pos in dc - 2..=dc + 1 {
+                pixels[index(pos, i)] = avg;
+            }
+        }
+    }
+}
+
+/// Brightness of a pixel, the mean of the color channels.
+fn brightness(pixel: u32) -> i32 {
+    let [r, g, b, _] = pixel.to_le_bytes();
+    (r as i32 + g as i32 + b as i32) / 3
+}
+
+/// Adds `offset` to the color channels of a pixel, saturating.
+fn offset_pixel(pixel: u32, offset: i32) -> u32 {
+    let [r, g, b, a] = pixel.to_le_bytes();
+    let add = |c: u8| (c as i32 + offset).clamp(0, 255) as u8;
+    u32::from_le_bytes([add(r), add(g), add(b), a])
+}
+
+/// The color of each of the 256 levels, paired up from a grayscale rendering (`levels`) and a
+/// colormapped rendering (`pixels`) of the same view at the `indices`. Levels that don't show
+/// are interpolated from their neighbors.
+fn colormap_lut(levels: &[u32], pixels: &[u32], indices: impl Iterator<Item = usize>) -> [u32; 256] {
+    let mut known = [None; 256];
+    for i in indices {
+        known[(levels[i] & 0xff) as usize] = Some(pixels[i]);
+    }
+    let mut lut = [0; 256];
+    for (level, color) in lut.iter_mut().enumerate() {
+        let below = (0..=level).rev().find_map(|l| known[l].map(|c| (l, c)));
+        let above = (level..256).find_map(|l| known[l].map(|c| (l, c)));
+        *color = match (below, above) {
+            (Some((l0, c0)), Some((l1, c1))) if l1 > l0 => {
+                let (c0, c1) = (c0.to_le_bytes(), c1.to_le_bytes());
+                let t = (level - l0) as f32 / (l1 - l0) as f32;
+                let mix = |c: usize| (c0[c] as f32 + (c1[c] as f32 - c0[c] as f32) * t).round() as u8;
+                u32::from_le_bytes([mix(0), mix(1), mix(2), mix(3)])
+            }
+            (Some((_, c)), _) | (None, Some((_, c))) => c,
+            (None, None) => 0,
+        };
+    }
+    lut
+}
+
+/// Shifts the brightness of each of the `extent` lines of `across` pixels so their medians match the
+/// median of all lines. `index` maps a line and a position on it to the pixel.
+fn equalize_medians(pixels: &mut [u32], extent: usize, across: usize, index: impl Fn(usize, usize) -> usize) {
+    let medians: Vec<i32> = (0..extent)
+        .map(|pos| {
+            let mut line: Vec<i32> = (0..across).map(|i| brightness(pixels[index(pos, i)])).collect();
+            line.sort_unstable();
+            line[line.len() / 2]
+        })
+        .collect();
+    let mut sorted = medians.clone();
+    sorted.sort_unstable();
+    let target = sorted[sorted.len() / 2];
+
+    for (pos, median) in medians.into_iter().enumerate() {
+        let offset = target - median;
+        for i in 0..across {
+            pixels[index(pos, i)] = offset_pixel(pixels[index(pos, i)], offset);
+        }
+    }
+}
+
+/// Sharpens by adding the difference to a 3x3 box blur, a post-process on the rendered pixels.
+/// Only the plot area `rect` is sharpened and the blur doesn't reach past its edges, so axes and
+/// labels stay as drawn.
+fn unsharp_mask(pixels: &mut [u32], width: usize, height: usize, rect: (u32, u32, u32, u32)) {
+    let (left, top) = (rect.0 as usize, rect.1 as usize);
+    let right = (left + rect.2 as usize).min(width);
+    let bottom = (top + rect.3 as usize).min(height);
+    if right < left + 3 || bottom < top + 3 {
+        return;
+    }
+    let source = pixels.to_vec();
+    for y in top..bottom {
+        for x in left..right {
+            let mut sum = [0i32; 3];
+            for ny in [y.saturating_sub(1).max(top), y, (y + 1).min(bottom - 1)] {
+                for nx in [x.saturating_sub(1).max(left), x, (x + 1).min(right - 1)] {
+                    let c = source[ny * width + nx].to_le_bytes();
+                    for (sum, &c) in sum.iter_mut().zip(&c) {
+                        *sum += c as i32;
+                    }
+                }
+            }
+            let c = source[y * width + x].to_le_bytes();
+            let sharp = |i: usize| (2 * c[i] as i32 - sum[i] / 9).clamp(0, 255) as u8;
+            pixels[y * width + x] = u32::from_le_bytes([sharp(0), sharp(1), sharp(2), c[3]]);
+        }
+    }
 }
 
 pub struct RawBitmap {
@@ -422,4 +1218,65 @@ impl RawBitmap {
             height,
         }
     }
+
+    /// Sets the color of all non-transparent pixels, keeps the alpha.
+    pub fn recolor(&mut self, rgb: [u8; 3]) {
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            if pixel[3] != 0 {
+                pixel[..3].copy_from_slice(&rgb);
+            }
+        }
+    }
+
+    /// Sets non-transparent pixels to black or white, contrasting the pixel of `under`.
+    pub fn recolor_contrast(&mut self, under: &RawBitmap) {
+        if under.pixels.len() != self.pixels.len() {
+            return;
+        }
+        for (pixel, below) in self.pixels.chunks_exact_mut(4).zip(under.pixels.chunks_exact(4)) {
+            if pixel[3] != 0 {
+                // Rec. 601 luma
+                let luma = 299 * below[0] as u32 + 587 * below[1] as u32 + 114 * below[2] as u32;
+                let v = if luma > 128 * 1000 { 0 } else { 255 };
+                pixel[..3].copy_from_slice(&[v, v, v]);
+            }
+        }
+    }
+
+    /// Box filter by `factor`, averaging in linear light (gamma 2.2) to avoid darkening.
+    pub fn downsample(self, factor: usize) -> Self {
+        if factor <= 1 {
+            return self;
+        }
+        let to_linear: Vec<f32> = (0..256).map(|v| (v as f32 / 255.0).powf(2.2)).collect();
+        let width = self.width / factor;
+        let height = self.height / factor;
+        let area = (factor * factor) as f32;
+
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0.0f32; 4];
+                for dy in 0..factor {
+                    let row = (y * factor + dy) * self.width;
+                    for dx in 0..factor {
+                        let offset = (row + x * factor + dx) * 4;
+                        let pixel = &self.pixels[offset..offset + 4];
+                        for (acc, &v) in sum.iter_mut().zip(&pixel[..3]) {
+                            *acc += to_linear[v as usize];
+                        }
+                        sum[3] += pixel[3] as f32;
+                    }
+                }
+                pixels.extend(sum[..3].iter().map(|&v| ((v / area).powf(1.0 / 2.2) * 255.0).round() as u8));
+                pixels.push((sum[3] / area).round() as u8);
+            }
+        }
+
+        Self {
+            pixels,
+            width,
+            height,
+        }
+    }
 }
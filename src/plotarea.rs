@@ -5,15 +5,31 @@
 
 use iced::advanced::layout::{self, Layout};
 use iced::advanced::widget::{self, Widget};
-use iced::advanced::{image, renderer};
-use iced::{Element, Length, Point, Rectangle, Rotation, Size, mouse};
+use iced::advanced::{image, renderer, text};
+use iced::{Color, Element, Font, Length, Pixels, Point, Rectangle, Rotation, Size, alignment, mouse};
 
+use crate::mask::Detection;
 use crate::plot_ffi::*;
 
 pub struct Plotarea<'a> {
     plot: &'a Plot,
     cursor: Point,
-    marker: PlotMarker,
+    markers: &'a [PlotMarker],
+    guides_filter: image::FilterMethod,
+    sample_grid: bool,
+    guides_color: GuidesColor,
+    mask: Option<image::Handle>,
+    mask_opacity: f32,
+}
+
+/// Color of the guides and marker overlay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuidesColor {
+    /// As drawn by libtriq.
+    Native,
+    Fixed([u8; 3]),
+    /// Black or white, whichever contrasts with the plot below.
+    Contrast,
 }
 
 /// Plotarea renders raster graphics in the appropriate size.
@@ -23,13 +39,18 @@ impl<'a> Plotarea<'a> {
         Self {
             plot,
             cursor: Point::default(),
-            marker: PlotMarker::default(),
+            markers: &[],
+            guides_filter: image::FilterMethod::Nearest,
+            sample_grid: false,
+            guides_color: GuidesColor::Native,
+            mask: None,
+            mask_opacity: 0.0,
         }
     }
 
-    /// Sets the marker in the [`Plotarea`].
-    pub fn marker(mut self, marker: PlotMarker) -> Self {
-        self.marker = marker;
+    /// Sets the markers in the [`Plotarea`], the last one is measured to the cursor.
+    pub fn markers(mut self, markers: &'a [PlotMarker]) -> Self {
+        self.markers = markers;
         self
     }
 
@@ -38,6 +59,41 @@ impl<'a> Plotarea<'a> {
         self.cursor = point;
         self
     }
+
+    /// Sets the color of the guides overlay in the [`Plotarea`].
+    pub fn guides_color(mut self, color: GuidesColor) -> Self {
+        self.guides_color = color;
+        self
+    }
+
+    /// Sets if gridlines at round sample indices are shown in the [`Plotarea`].
+    pub fn sample_grid(mut self, enabled: bool) -> Self {
+        self.sample_grid = enabled;
+        self
+    }
+
+    /// Sets the detection mask drawn over the plot in the [`Plotarea`], see [`mask_handle()`].
+    /// A zero `opacity` hides the mask.
+    pub fn mask(mut self, mask: Option<image::Handle>, opacity: f32) -> Self {
+        self.mask = mask;
+        self.mask_opacity = opacity;
+        self
+    }
+
+    /// Sets the scaling filter of the guides overlay in the [`Plotarea`].
+    pub fn guides_filter(mut self, filter_method: image::FilterMethod) -> Self {
+        self.guides_filter = filter_method;
+        self
+    }
+}
+
+/// Maps a position in the [`Plotarea`] to plot pixel coordinates.
+///
+/// Positions are logical (scaled) coordinates and the plot is rendered at the logical
+/// size of the widget, thus every position to data mapping has to go through here.
+pub fn plot_pos(position: Point) -> (u32, u32) {
+    // float to int casts saturate, negative positions map to 0
+    (position.x.floor() as u32, position.y.floor() as u32)
 }
 
 /// Creates a new [`Plotarea`] with the given image `Plot`.
@@ -47,7 +103,7 @@ pub fn plotarea(plot: &Plot) -> Plotarea {
 
 impl<'a, Message, Theme, Renderer> Widget<Message, Theme, Renderer> for Plotarea<'a>
 where
-    Renderer: image::Renderer<Handle = image::Handle>,
+    Renderer: image::Renderer<Handle = image::Handle> + text::Renderer<Font = Font>,
 {
     fn size(&self) -> Size<Length> {
         Size {
@@ -82,10 +138,18 @@ where
     ) {
         let drawing_bounds = layout.bounds();
 
-        let bitmap = self.plot.to_bitmap(
-            drawing_bounds.width as usize,
-            drawing_bounds.height as usize,
-        );
+        // render at the logical size, matching the positions from `plot_pos()`
+        let (width, height) = plot_pos(Point::new(drawing_bounds.width, drawing_bounds.height));
+        let bitmap = self.plot.to_bitmap(width as usize, height as usize);
+
+        let (x, y) = plot_pos(self.cursor);
+        let mut guides = self.plot.to_guides_bitmap(self.markers, x as usize, y as usize);
+        match self.guides_color {
+            GuidesColor::Native => {}
+            GuidesColor::Fixed(rgb) => guides.recolor(rgb),
+            GuidesColor::Contrast => guides.recolor_contrast(&bitmap),
+        }
+
         let handle =
             image::Handle::from_rgba(bitmap.width as u32, bitmap.height as u32, bitmap.pixels);
         renderer.draw_image(
@@ -99,29 +163,263 @@ where
             drawing_bounds,
         );
 
-        let bitmap =
-            self.plot
-                .to_guides_bitmap(self.marker, self.cursor.x as usize, self.cursor.y as usize);
+        if let Some(mask) = self.mask.as_ref().filter(|_| self.mask_opacity > 0.0) {
+            renderer.draw_image(
+                image::Image {
+                    handle: mask.clone(),
+                    filter_method: image::FilterMethod::Nearest,
+                    rotation: Rotation::default().radians(),
+                    opacity: self.mask_opacity,
+                    snap: true,
+                },
+                drawing_bounds,
+            );
+        }
+
         let handle =
-            image::Handle::from_rgba(bitmap.width as u32, bitmap.height as u32, bitmap.pixels);
+            image::Handle::from_rgba(guides.width as u32, guides.height as u32, guides.pixels);
         renderer.draw_image(
             image::Image {
                 handle: handle,
-                filter_method: image::FilterMethod::Nearest,
+                filter_method: self.guides_filter,
                 rotation: Rotation::default().radians(),
                 opacity: 1.0,
                 snap: true,
             },
             drawing_bounds,
         );
+
+        if self.sample_grid {
+            self.draw_sample_grid(renderer, drawing_bounds);
+        }
+    }
+}
+
+impl<'a> Plotarea<'a> {
+    /// Draws gridlines with labels at round sample indices across the time axis.
+    fn draw_sample_grid<Renderer>(&self, renderer: &mut Renderer, bounds: Rectangle)
+    where
+        Renderer: text::Renderer<Font = Font>,
+    {
+        let time_along_x = self.plot.layout_direction() == 0;
+        let color = Color::from_rgba8(255, 255, 255, 0.35);
+
+        for (pos, sample) in self.plot.sample_ticks(8) {
+            let (line, label_at) = if time_along_x {
+                let x = bounds.x + pos as f32;
+                (Rectangle::new(Point::new(x, bounds.y), Size::new(1.0, bounds.height)), Point::new(x + 3.0, bounds.y + 2.0))
+            } else {
+                let y = bounds.y + pos as f32;
+                (Rectangle::new(Point::new(bounds.x, y), Size::new(bounds.width, 1.0)), Point::new(bounds.x + 2.0, y + 2.0))
+            };
+            renderer.fill_quad(
+                renderer::Quad {
+                    bounds: line,
+                    ..renderer::Quad::default()
+                },
+                color,
+            );
+            renderer.fill_text(
+                text::Text {
+                    content: format_sample_index(sample),
+                    bounds: Size::new(80.0, 16.0),
+                    size: Pixels(12.0),
+                    line_height: text::LineHeight::default(),
+                    font: Font::MONOSPACE,
+                    align_x: text::Alignment::Left,
+                    align_y: alignment::Vertical::Top,
+                    shaping: text::Shaping::Basic,
+                    wrapping: text::Wrapping::None,
+                },
+                label_at,
+                Color::WHITE,
+                bounds,
+            );
+        }
+    }
+}
+
+/// Paints the `detections` as opaque rectangles aligned to the current plot view, the rest transparent.
+/// Higher scores are painted last, on top of overlapping lower ones. Only the plot area is painted,
+/// a missing frequency edge spans to the edge of the plot area.
+pub fn mask_handle(plot: &Plot, detections: &[Detection], ramp: [[u8; 3]; 2]) -> image::Handle {
+    let (width, height) = (plot.width() as usize, plot.height() as usize);
+    let (rect_left, rect_top, rect_width, rect_height) = plot.plot_rect();
+    let (rect_right, rect_bottom) = (rect_left + rect_width, rect_top + rect_height);
+    if width == 0 || height == 0 {
+        return image::Handle::from_rgba(1, 1, vec![0, 0, 0, 0]);
+    }
+    let mut pixels = vec![0u8; width * height * 4];
+    let time_along_x = plot.layout_direction() == 0;
+    // the extent of the frequency axis in the plot area, for missing frequency edges
+    let freq_edges = if time_along_x { (rect_top, rect_bottom) } else { (rect_left, rect_right) };
+
+    let mut detections: Vec<&Detection> = detections.iter().collect();
+    detections.sort_by(|a, b| a.score.total_cmp(&b.score));
+    for detection in detections {
+        let end = match detection.sample_count {
+            Some(count) => detection.sample_start.saturating_add(count),
+            None => plot.sample_count(),
+        };
+        let (x1, y1) = plot.pos_of(detection.sample_start, detection.freq_lower.unwrap_or_default());
+        let (x2, y2) = plot.pos_of(end, detection.freq_upper.unwrap_or_default());
+        let (time1, time2, mut freq1, mut freq2) = if time_along_x { (x1, x2, y1, y2) } else { (y1, y2, x1, x2) };
+        if detection.freq_lower.is_none() || detection.freq_upper.is_none() {
+            (freq1, freq2) = freq_edges;
+        }
+        let ((left, right), (top, bottom)) = if time_along_x {
+            ((time1.min(time2), time1.max(time2)), (freq1.min(freq2), freq1.max(freq2)))
+        } else {
+            ((freq1.min(freq2), freq1.max(freq2)), (time1.min(time2), time1.max(time2)))
+        };
+        let (left, right) = (left.max(rect_left), right.min(rect_right));
+        let (top, bottom) = (top.max(rect_top), bottom.min(rect_bottom));
+        // detections outside of the view are clamped to an empty span
+        if left >= right || top >= bottom {
+            continue;
+        }
+
+        let [low, high] = ramp;
+        let color: Vec<u8> = low
+            .iter()
+            .zip(high)
+            .map(|(&l, h)| (l as f32 + (h as f32 - l as f32) * detection.score).round() as u8)
+            .chain([255])
+            .collect();
+        for y in top as usize..(bottom as usize).min(height) {
+            for x in left as usize..(right as usize).min(width) {
+                let offset = (y * width + x) * 4;
+                pixels[offset..offset + 4].copy_from_slice(&color);
+            }
+        }
+    }
+
+    image::Handle::from_rgba(width as u32, height as u32, pixels)
+}
+
+/// Formats a sample index with a k/M/G suffix, e.g. `2.5M`.
+fn format_sample_index(sample: u64) -> String {
+    match sample {
+        0..1_000 => format!("{sample}"),
+        1_000..1_000_000 => format!("{}k", sample as f64 / 1e3),
+        1_000_000..1_000_000_000 => format!("{}M", sample as f64 / 1e6),
+        _ => format!("{}G", sample as f64 / 1e9),
     }
 }
 
 impl<'a, Message, Theme, Renderer> From<Plotarea<'a>> for Element<'a, Message, Theme, Renderer>
 where
-    Renderer: image::Renderer<Handle = image::Handle>,
+    Renderer: image::Renderer<Handle = image::Handle> + text::Renderer<Font = Font>,
 {
     fn from(plotarea: Plotarea<'a>) -> Self {
         Self::new(plotarea)
     }
 }
+
+/// Creates the signal presence timeline, `levels` above the `threshold` are highlighted.
+pub fn timeline_handle(levels: &[f32], threshold: f32) -> image::Handle {
+    if levels.is_empty() {
+        return image::Handle::from_rgba(1, 1, vec![48, 48, 48, 255]);
+    }
+
+    let pixels = levels
+        .iter()
+        .flat_map(|&level| {
+            if level >= threshold {
+                let v = 128 + (127.0 * level) as u8;
+                [v, v / 2 + 32, 0, 255]
+            } else {
+                [48, 48, 48, 255]
+            }
+        })
+        .collect::<Vec<u8>>();
+
+    image::Handle::from_rgba(levels.len() as u32, 1, pixels)
+}
+
+/// Creates the power histogram bar chart, bins within `window` (as fractions) are highlighted.
+pub fn power_histogram_handle(histogram: &[u32], window: (f32, f32)) -> image::Handle {
+    const HEIGHT: usize = 32;
+
+    let max = histogram.iter().copied().max().unwrap_or_default();
+    if max == 0 {
+        return image::Handle::from_rgba(1, 1, vec![48, 48, 48, 255]);
+    }
+
+    // log scale, the counts span many decades
+    let scale = (max as f32).ln_1p();
+    let width = histogram.len();
+    let mut pixels = vec![0; width * HEIGHT * 4];
+    for (x, &count) in histogram.iter().enumerate() {
+        let fraction = x as f32 / width as f32;
+        let inside = fraction >= window.0 && fraction <= window.1;
+        let bar = ((count as f32).ln_1p() / scale * HEIGHT as f32).round() as usize;
+        for y in 0..HEIGHT {
+            let rgba = match (HEIGHT - y <= bar, inside) {
+                (true, true) => [255, 160, 32, 255],
+                (true, false) => [128, 128, 128, 255],
+                (false, true) => [72, 56, 40, 255],
+                (false, false) => [48, 48, 48, 255],
+            };
+            let offset = (y * width + x) * 4;
+            pixels[offset..offset + 4].copy_from_slice(&rgba);
+        }
+    }
+
+    image::Handle::from_rgba(width as u32, HEIGHT as u32, pixels)
+}
+
+/// Creates the time scrollbar, the thumb spans `thumb` (as fractions of the sample range).
+pub fn scrollbar_handle(thumb: (f32, f32)) -> image::Handle {
+    const WIDTH: usize = 1024;
+
+    let pixels = (0..WIDTH)
+        .flat_map(|x| {
+            let fraction = x as f32 / WIDTH as f32;
+            if fraction >= thumb.0 && fraction <= thumb.1 {
+                [160, 160, 160, 255]
+            } else {
+                [48, 48, 48, 255]
+            }
+        })
+        .collect::<Vec<u8>>();
+
+    image::Handle::from_rgba(WIDTH as u32, 1, pixels)
+}
+
+/// Creates the component amplitude strip over the plot area `rect` as `(x, y, width, height)`
+/// of a layout of `size`, one `ranges` entry per pixel along the time axis. Time runs down if `vertical`.
+/// The axes, the histogram and the strips outside the plot area stay transparent.
+pub fn component_handle(ranges: &[(f32, f32)], rect: (u32, u32, u32, u32), size: (u32, u32), vertical: bool) -> image::Handle {
+    let (left, top, rect_width, rect_height) = (rect.0 as usize, rect.1 as usize, rect.2 as usize, rect.3 as usize);
+    let (width, height) = (size.0 as usize, size.1 as usize);
+    let across = if vertical { rect_width } else { rect_height };
+    if ranges.is_empty() || across == 0 || width == 0 || height == 0 {
+        return image::Handle::from_rgba(1, 1, vec![0, 0, 0, 0]);
+    }
+
+    let mut pixels = vec![0; width * height * 4];
+    let zero = across / 2;
+    for (t, &(min, max)) in ranges.iter().enumerate() {
+        // +1 at the top (or left), -1 at the bottom (or right)
+        let to_pixel = |value: f32| ((1.0 - value) / 2.0 * (across - 1) as f32).round() as usize;
+        let (low, high) = (to_pixel(max), to_pixel(min));
+        for a in 0..across {
+            let v = if (low..=high).contains(&a) {
+                220
+            } else if a == zero {
+                64
+            } else {
+                0
+            };
+            let (x, y) = if vertical { (left + a, top + t) } else { (left + t, top + a) };
+            if x >= width || y >= height {
+                continue;
+            }
+            let offset = (y * width + x) * 4;
+            pixels[offset..offset + 4].copy_from_slice(&[v, v, v, 255]);
+        }
+    }
+
+    image::Handle::from_rgba(width as u32, height as u32, pixels)
+}
@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (2025) Christian W. Zuckschwerdt
+
+//! I/Q Viewer -- Headless rendering of a view to PNG.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::archive::is_archive;
+use crate::dirs;
+use crate::options::ViewOptions;
+use crate::plot_ffi::Plot;
+
+/// Keys of `ViewOptions::parse_token()`, a token it rejects with one of these has a bad value.
+const VIEW_OPTION_KEYS: &[&str] = &[
+    "fft", "fftn", "window", "windowf", "gain", "range", "cmap", "colormap", "orientation",
+];
+
+/// View state reconstructed from a parameter string.
+#[derive(Debug, Clone, Default)]
+pub struct RenderParams {
+    pub path: PathBuf,
+    pub options: ViewOptions,
+    pub zoom: Option<u32>,
+    pub origin: Option<u32>,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl RenderParams {
+    /// Parses a parameter string `path?key=value&key=value`.
+    /// Keys are the I/Q list options and `zoom`, `origin`, `width`, `height`.
+    /// The path is a single I/Q file, lists and archives have no one view to render.
+    pub fn parse(params: &str) -> Result<Self, String> {
+        let (path, query) = params.rsplit_once('?').unwrap_or((params, ""));
+        let path = dirs::iq_path_from_str(path)
+            .filter(|path| path.is_file())
+            .ok_or_else(|| format!("Not an I/Q file \"{path}\""))?;
+        if dirs::is_iq_list(&path) || is_archive(&path) {
+            return Err(format!("Not a single I/Q file, can't render a list or archive \"{}\"", path.display()));
+        }
+
+        let mut render = Self {
+            path,
+            width: 1024,
+            height: 768,
+            ..Self::default()
+        };
+        for token in query.split('&').filter(|token| !token.is_empty()) {
+            if render.options.parse_token(token) {
+                continue;
+            }
+            let (key, value) = token
+                .split_once('=')
+                .ok_or_else(|| format!("Malformed parameter \"{token}\""))?;
+            let key = key.to_ascii_lowercase();
+            if VIEW_OPTION_KEYS.contains(&key.as_str()) {
+                return Err(format!("Invalid value in parameter \"{token}\""));
+            }
+            let value: u32 = value
+                .parse()
+                .map_err(|_| format!("Invalid number in parameter \"{token}\""))?;
+            match key.as_str() {
+                "zoom" => render.zoom = Some(value),
+                "origin" => render.origin = Some(value),
+                "width" if (16..=16384).contains(&value) => render.width = value,
+                "height" if (16..=16384).contains(&value) => render.height = value,
+                "width" | "height" => return Err(format!("Size out of range \"{token}\"")),
+                _ => return Err(format!("Unknown parameter \"{token}\"")),
+            }
+        }
+        Ok(render)
+    }
+
+    /// Renders the view and writes it as PNG file.
+    pub fn render_png(&self, out: &Path) -> Result<(), String> {
+        let plot = Plot::with_path(&self.path, None);
+        let options = &self.options;
+        plot.set_fft_size(options.fftn.unwrap_or_default().to_value() as u32);
+        plot.set_fft_window(options.windowf.unwrap_or_default().to_value() as u8);
+        plot.set_db_gain(options.gain.unwrap_or_default().to_value());
+        plot.set_db_range(options.range.unwrap_or_default().to_value());
+        plot.set_cmap(options.colormap.unwrap_or_default().to_value() as u32);
+        plot.set_layout_direction(options.orientation.unwrap_or_default().to_value() as u8);
+        if let Some(zoom) = self.zoom {
+            plot.set_zoom(zoom);
+        }
+        if let Some(origin) = self.origin {
+            plot.set_origin(origin);
+        }
+
+        let bitmap = plot.to_bitmap(self.width as usize, self.height as usize);
+        write_png(out, &bitmap.pixels, bitmap.width as u32, bitmap.height as u32)
+            .map_err(|err| format!("Can't write \"{}\": {err}", out.display()))
+    }
+}
+
+fn write_png(out: &Path, rgba: &[u8], width: u32, height: u32) -> io::Result<()> {
+    let file = io::BufWriter::new(fs::File::create(out)?);
+    let mut encoder = png::Encoder::new(file, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(rgba)?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Handles `--render '<params>' out.png`, returns the error message on failure.
+pub fn run_cli(args: &[String]) -> Result<(), String> {
+    let [params, out] = args else {
+        return Err("Usage: iqviewer --render '<path?key=value&...>' out.png".into());
+    };
+    RenderParams::parse(params)?.render_png(Path::new(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty I/Q file named `name` in a fresh temp folder.
+    fn temp_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("iqviewer-test-render-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, b"").unwrap();
+        path
+    }
+
+    #[test]
+    fn parse_params() {
+        let path = temp_file("valid.cu8");
+        let render = RenderParams::parse(&format!("{}?zoom=4&Width=640&height=480", path.display())).unwrap();
+        assert_eq!(render.path, path);
+        assert_eq!((render.zoom, render.origin), (Some(4), None));
+        assert_eq!((render.width, render.height), (640, 480));
+
+        let render = RenderParams::parse(&path.display().to_string()).unwrap();
+        assert_eq!((render.width, render.height), (1024, 768));
+    }
+
+    #[test]
+    fn parse_params_errors() {
+        let path = temp_file("errors.cu8");
+        let error = |query: &str| RenderParams::parse(&format!("{}?{query}", path.display())).unwrap_err();
+        assert!(error("zoom").starts_with("Malformed parameter"));
+        assert!(error("speed=4").starts_with("Unknown parameter"));
+        assert!(error("zoom=four").starts_with("Invalid number"));
+        assert!(error("origin=-1").starts_with("Invalid number"));
+        assert!(error("width=8").starts_with("Size out of range"));
+        assert!(error("height=20000").starts_with("Size out of range"));
+        assert!(error("gain=999").starts_with("Invalid value"));
+        assert!(error("cmap=nope").starts_with("Invalid value"));
+    }
+
+    #[test]
+    fn parse_params_not_a_file() {
+        assert!(RenderParams::parse("/nonexistent/capture.cu8").unwrap_err().starts_with("Not an I/Q file"));
+        let list = temp_file("files.iqlist");
+        let archive = temp_file("captures.zip");
+        for path in [list, archive] {
+            let error = RenderParams::parse(&path.display().to_string()).unwrap_err();
+            assert!(error.starts_with("Not a single I/Q file"), "{error}");
+        }
+    }
+}
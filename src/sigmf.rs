@@ -0,0 +1,362 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (2025) Christian W. Zuckschwerdt
+
+//! I/Q Viewer -- Provenance fields and collections from SigMF metadata.
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Global fields shown, with their labels. Missing fields are omitted.
+const FIELDS: &[(&str, &str)] = &[
+    ("core:hw", "Hardware"),
+    ("core:author", "Author"),
+    ("antenna:model", "Antenna"),
+    ("antenna:type", "Antenna type"),
+    ("antenna:gain", "Antenna gain"),
+];
+
+/// Collection fields shown with the members of a collection, with their labels.
+const COLLECTION_FIELDS: &[(&str, &str)] = &[
+    ("core:description", "Collection"),
+    ("core:author", "Collection author"),
+    ("core:license", "Collection license"),
+    ("core:collection_doi", "Collection DOI"),
+];
+
+/// Reads the provenance fields of a SigMF archive (`.sigmf`) or a recording
+/// with a `.sigmf-meta` file next to it, empty if there is no metadata.
+pub fn provenance(path: &Path) -> Vec<(&'static str, String)> {
+    let Ok(meta) = read_meta(path) else {
+        return Vec::new();
+    };
+    let globals = match serde_json::from_str::<serde_json::Value>(&meta) {
+        Ok(json) => json.get("global").cloned().unwrap_or_default(),
+        Err(err) => {
+            println!("SigMF metadata error {err:?}");
+            return Vec::new();
+        }
+    };
+
+    FIELDS
+        .iter()
+        .filter_map(|&(key, label)| {
+            let value = match globals.get(key)? {
+                serde_json::Value::String(text) => text.clone(),
+                serde_json::Value::Number(number) if key == "antenna:gain" => format!("{number} dBi"),
+                value => value.to_string(),
+            };
+            Some((label, value))
+        })
+        .collect()
+}
+
+/// Reads the annotations with a frequency range as `(sample_start, sample_count, freq_lower, freq_upper)`,
+/// empty if there is no metadata. Annotations without a frequency range span the full bandwidth,
+/// a missing sample count is `None` and spans to the end of the recording.
+pub fn annotations(path: &Path) -> Vec<(u64, Option<u64>, Option<f64>, Option<f64>)> {
+    let Ok(meta) = read_meta(path) else {
+        return Vec::new();
+    };
+    let annotations = match serde_json::from_str::<serde_json::Value>(&meta) {
+        Ok(json) => json.get("annotations").cloned().unwrap_or_default(),
+        Err(err) => {
+            println!("SigMF metadata error {err:?}");
+            return Vec::new();
+        }
+    };
+    let Some(annotations) = annotations.as_array() else {
+        return Vec::new();
+    };
+
+    annotations
+        .iter()
+        .filter_map(|annotation| {
+            let sample_start = annotation.get("core:sample_start")?.as_u64()?;
+            let sample_count = annotation.get("core:sample_count").and_then(|v| v.as_u64());
+            let freq_lower = annotation.get("core:freq_lower_edge").and_then(|v| v.as_f64());
+            let freq_upper = annotation.get("core:freq_upper_edge").and_then(|v| v.as_f64());
+            Some((sample_start, sample_count, freq_lower, freq_upper))
+        })
+        .collect()
+}
+
+/// Recording location from the `core:geolocation` GeoJSON point, in degrees and meters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geolocation {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: Option<f64>,
+}
+
+impl Geolocation {
+    /// The location on OpenStreetMap, with a marker.
+    pub fn map_url(&self) -> String {
+        let (lat, lon) = (self.latitude, self.longitude);
+        format!("https://www.openstreetmap.org/?mlat={lat:.6}&mlon={lon:.6}#map=15/{lat:.6}/{lon:.6}")
+    }
+}
+
+impl std::fmt::Display for Geolocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ns = if self.latitude < 0.0 { 'S' } else { 'N' };
+        let ew = if self.longitude < 0.0 { 'W' } else { 'E' };
+        write!(f, "{:.6}° {ns} {:.6}° {ew}", self.latitude.abs(), self.longitude.abs())?;
+        if let Some(altitude) = self.altitude {
+            write!(f, ", {altitude:.0} m")?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the recording location of a SigMF archive or a recording with a `.sigmf-meta` file,
+/// `None` if there is no location. GeoJSON points are longitude, latitude and optional altitude.
+pub fn geolocation(path: &Path) -> Option<Geolocation> {
+    let meta = read_meta(path).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&meta).ok()?;
+    let point = json.pointer("/global/core:geolocation")?;
+    if point.get("type")?.as_str()? != "Point" {
+        return None;
+    }
+    let coordinates = point.get("coordinates")?.as_array()?;
+    let coordinate = |index: usize| coordinates.get(index).and_then(|v| v.as_f64());
+    let (longitude, latitude) = (coordinate(0)?, coordinate(1)?);
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        println!("SigMF geolocation out of range {coordinates:?}");
+        return None;
+    }
+    Some(Geolocation {
+        latitude,
+        longitude,
+        altitude: coordinate(2),
+    })
+}
+
+/// Tests for a SigMF collection file.
+pub fn is_collection(path: impl AsRef<Path>) -> bool {
+    path.as_ref().extension().is_some_and(|ext| ext == "sigmf-collection")
+}
+
+/// Reads the member recordings of a SigMF collection in the declared order.
+/// Streams name the recordings relative to the collection, as a `.sigmf` archive
+/// or a `.sigmf-data` file, see [`data_format()`] for the format of the latter.
+/// Members not found are returned as the bare name, for the caller to report.
+pub fn read_collection(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let base = path.parent().unwrap_or(Path::new(""));
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    let streams = json
+        .pointer("/collection/core:streams")
+        .and_then(|streams| streams.as_array())
+        .ok_or(io::ErrorKind::InvalidData)?;
+
+    let members = streams
+        .iter()
+        .filter_map(|stream| stream.get("name")?.as_str())
+        .map(|name| {
+            let member = base.join(name);
+            let archive = with_suffix(&member, ".sigmf");
+            let data = with_suffix(&member, ".sigmf-data");
+            if archive.is_file() {
+                archive
+            } else if data.is_file() {
+                data
+            } else {
+                member
+            }
+        })
+        .collect();
+    Ok(members)
+}
+
+/// Reads the collection fields of a SigMF collection, empty if there are none.
+pub fn collection_fields(path: &Path) -> Vec<(&'static str, String)> {
+    let Ok(meta) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let collection = match serde_json::from_str::<serde_json::Value>(&meta) {
+        Ok(json) => json.get("collection").cloned().unwrap_or_default(),
+        Err(err) => {
+            println!("SigMF collection error {err:?}");
+            return Vec::new();
+        }
+    };
+
+    COLLECTION_FIELDS
+        .iter()
+        .filter_map(|&(key, label)| {
+            let value = match collection.get(key)? {
+                serde_json::Value::String(text) => text.clone(),
+                value => value.to_string(),
+            };
+            Some((label, value))
+        })
+        .collect()
+}
+
+/// The sample format of a `.sigmf-data` file from the datatype of its `.sigmf-meta`,
+/// as libtriq detects the format from the extension only. `None` for other files.
+pub fn data_format(data: &Path) -> Option<&'static str> {
+    if data.extension().is_none_or(|ext| ext != "sigmf-data") {
+        return None;
+    }
+    let meta = std::fs::read_to_string(meta_path(data)).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&meta).ok()?;
+    let datatype = json.pointer("/global/core:datatype")?.as_str()?;
+    let format = sample_format(datatype);
+    if format.is_none() {
+        println!("SigMF datatype {datatype} not supported");
+    }
+    format
+}
+
+/// The sample format of a complex SigMF datatype, e.g. `ci16_le` is CS16.
+fn sample_format(datatype: &str) -> Option<&'static str> {
+    match datatype {
+        "cu8" => Some("CU8"),
+        "ci8" => Some("CS8"),
+        "cu16_le" => Some("CU16"),
+        "ci16_le" => Some("CS16"),
+        "cu32_le" => Some("CU32"),
+        "ci32_le" => Some("CS32"),
+        "cf32_le" => Some("CF32"),
+        "cf64_le" => Some("CF64"),
+        _ => None,
+    }
+}
+
+/// Appends a `suffix` to the file name, stream names may contain dots.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Reads the metadata of a SigMF archive (`.sigmf`) or the `.sigmf-meta` file next to a recording.
+fn read_meta(path: &Path) -> io::Result<String> {
+    if path.extension().is_some_and(|ext| ext == "sigmf") {
+        read_archive_meta(path)
+    } else {
+        std::fs::read_to_string(meta_path(path))
+    }
+}
+
+/// The `.sigmf-meta` file of a recording, e.g. `capture.sigmf-data`.
+fn meta_path(path: &Path) -> PathBuf {
+    path.with_extension("sigmf-meta")
+}
+
+/// Finds the `.sigmf-meta` member of a SigMF archive (a tar file) and reads it.
+fn read_archive_meta(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let (offset, size) = find_member(&mut file, ".sigmf-meta")?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut meta = String::new();
+    file.take(size).read_to_string(&mut meta)?;
+    Ok(meta)
+}
+
+/// Byte offset and length of the `.sigmf-data` member of a SigMF archive (a tar file).
+pub fn archive_data_range(path: &Path) -> io::Result<(u64, u64)> {
+    find_member(&mut File::open(path)?, ".sigmf-data")
+}
+
+/// Byte offset and length of the first tar member with a name ending in `suffix`.
+fn find_member(file: &mut (impl Read + Seek), suffix: &str) -> io::Result<(u64, u64)> {
+    let mut header = [0u8; 512];
+    let mut offset = 0;
+    loop {
+        file.read_exact(&mut header)?;
+        offset += 512;
+        // an empty block marks the end of the archive
+        if header.iter().all(|&b| b == 0) {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        let name = tar_str(&header[0..100]);
+        let size = u64::from_str_radix(tar_str(&header[124..136]).trim(), 8)
+            .map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+        if name.ends_with(suffix) {
+            return Ok((offset, size));
+        }
+        // members are padded to full blocks
+        let padded = size.next_multiple_of(512);
+        file.seek(SeekFrom::Current(padded as i64))?;
+        offset += padded;
+    }
+}
+
+/// A NUL terminated tar header field.
+fn tar_str(field: &[u8]) -> &str {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    std::str::from_utf8(&field[..end]).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// An in-memory tar archive of the `members`, as names and contents.
+    fn tar_archive(members: &[(&str, &[u8])]) -> Cursor<Vec<u8>> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for &(name, data) in members {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, data).unwrap();
+        }
+        Cursor::new(builder.into_inner().unwrap())
+    }
+
+    #[test]
+    fn find_member_in_archive() {
+        let meta = br#"{"global":{"core:datatype":"ci16_le"}}"#;
+        let data = [1u8; 700];
+        let mut archive = tar_archive(&[("rec/rec.sigmf-meta", meta), ("rec/rec.sigmf-data", &data)]);
+
+        let (offset, size) = find_member(&mut archive, ".sigmf-data").unwrap();
+        assert_eq!(size, data.len() as u64);
+        // after the meta header, the meta padded to a block, and the data header
+        assert_eq!(offset, 512 + 512 + 512);
+        assert_eq!(&archive.get_ref()[offset as usize..(offset + size) as usize], data.as_slice());
+
+        archive.set_position(0);
+        let (offset, size) = find_member(&mut archive, ".sigmf-meta").unwrap();
+        assert_eq!((offset, size), (512, meta.len() as u64));
+    }
+
+    #[test]
+    fn find_member_missing() {
+        let mut archive = tar_archive(&[("rec/rec.sigmf-meta", b"{}")]);
+        let err = find_member(&mut archive, ".sigmf-data").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        // a truncated archive ends without the empty block
+        let mut truncated = Cursor::new(vec![]);
+        assert!(find_member(&mut truncated, ".sigmf-data").is_err());
+    }
+
+    #[test]
+    fn sample_format_datatypes() {
+        let known = [
+            ("cu8", "CU8"),
+            ("ci8", "CS8"),
+            ("cu16_le", "CU16"),
+            ("ci16_le", "CS16"),
+            ("cu32_le", "CU32"),
+            ("ci32_le", "CS32"),
+            ("cf32_le", "CF32"),
+            ("cf64_le", "CF64"),
+        ];
+        for (datatype, format) in known {
+            assert_eq!(sample_format(datatype), Some(format), "{datatype}");
+        }
+    }
+
+    #[test]
+    fn sample_format_unsupported() {
+        // big-endian and real datatypes are not read, nor an endianness on single bytes
+        for datatype in ["ci16_be", "cf32_be", "cu16", "ri16_le", "rf32_le", "cu8_le", "", "CI16_LE"] {
+            assert_eq!(sample_format(datatype), None, "{datatype}");
+        }
+    }
+}
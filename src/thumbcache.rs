@@ -0,0 +1,247 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (2025) Christian W. Zuckschwerdt
+
+//! I/Q Viewer -- Thumbnail disk cache.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::dirs::cache_dir;
+use crate::plot_ffi::{FileInfo, RawBitmap, SAMPLE_FORMAT};
+
+/// Render settings of the thumbnails, part of the key.
+const THUMBNAIL_SETTINGS: &str = "256x256";
+/// Cache size limit, least recently used entries are evicted.
+const CACHE_SIZE_LIMIT: u64 = 256 * 1024 * 1024;
+
+const MAGIC: &[u8; 4] = b"IQT2";
+/// Magic, width, height, sample format, sample count, center freq, sample rate, clipped fraction.
+const HEADER_LEN: usize = 4 + 4 + 4 + 1 + 8 + 8 + 8 + 4;
+
+/// Cache key from the canonical path, file size, modification time and thumbnail settings.
+/// A changed file gets a new key, the old entry is evicted eventually.
+/// The `appearance` tag distinguishes renders with different options.
+pub fn key(path: &Path, size: u64, mtime: SystemTime, appearance: &str) -> String {
+    let mtime = mtime
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let key = format!("{}|{size}|{mtime}|{THUMBNAIL_SETTINGS}@{appearance}", path.display());
+    format!("{:016x}", fnv1a(key.as_bytes()))
+}
+
+/// Loads a cached thumbnail, file info and clipped fraction, marks the entry as recently used.
+pub fn load(key: &str) -> Option<(RawBitmap, FileInfo, Option<f32>)> {
+    let path = entry_path(key)?;
+    let entry = decode(&fs::read(&path).ok()?);
+    if entry.is_some() {
+        let _ = fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+    }
+    entry
+}
+
+/// Stores a thumbnail, file info and clipped fraction.
+pub fn store(key: &str, bitmap: &RawBitmap, file_info: &FileInfo, clipped: Option<f32>) {
+    let Some(path) = entry_path(key) else {
+        return;
+    };
+    let result = match path.parent() {
+        Some(dir) => fs::create_dir_all(dir),
+        None => Ok(()),
+    };
+    if let Err(err) = result.and_then(|_| fs::write(&path, encode(bitmap, file_info, clipped))) {
+        println!("Thumbnail cache error {err:?}");
+    }
+}
+
+/// Removes least recently used entries until the cache fits the size limit.
+pub fn evict() {
+    if let Some(dir) = thumbnails_dir() {
+        evict_dir(&dir, CACHE_SIZE_LIMIT);
+    }
+}
+
+/// Removes the least recently modified files of the `dir` until their total size is within `limit`.
+fn evict_dir(dir: &Path, limit: u64) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let metadata = entry.metadata().ok()?;
+            Some((metadata.modified().ok()?, metadata.len(), entry.path()))
+        })
+        .collect::<Vec<_>>();
+
+    // oldest first
+    entries.sort();
+
+    let mut total: u64 = entries.iter().map(|(_, len, _)| len).sum();
+    for (_, len, path) in entries {
+        if total <= limit {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            total -= len;
+        }
+    }
+}
+
+/// Removes all cached thumbnails.
+pub fn clear() {
+    if let Some(dir) = thumbnails_dir() {
+        match fs::remove_dir_all(&dir) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                println!("Thumbnail cache error {err:?}");
+            }
+            _ => {}
+        }
+    }
+}
+
+fn thumbnails_dir() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join("thumbnails"))
+}
+
+fn entry_path(key: &str) -> Option<PathBuf> {
+    thumbnails_dir().map(|dir| dir.join(key))
+}
+
+/// FNV-1a, a stable hash across builds.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn encode(bitmap: &RawBitmap, file_info: &FileInfo, clipped: Option<f32>) -> Vec<u8> {
+    let sample_format = SAMPLE_FORMAT
+        .iter()
+        .position(|&f| f == file_info.sample_format)
+        .unwrap_or_default();
+
+    let mut data = Vec::with_capacity(HEADER_LEN + bitmap.pixels.len());
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&(bitmap.width as u32).to_le_bytes());
+    data.extend_from_slice(&(bitmap.height as u32).to_le_bytes());
+    data.push(sample_format as u8);
+    data.extend_from_slice(&file_info.sample_count.to_le_bytes());
+    data.extend_from_slice(&file_info.center_freq.to_le_bytes());
+    data.extend_from_slice(&file_info.sample_rate.to_le_bytes());
+    // NaN if the format can't be checked
+    data.extend_from_slice(&clipped.unwrap_or(f32::NAN).to_le_bytes());
+    data.extend_from_slice(&bitmap.pixels);
+    data
+}
+
+fn decode(data: &[u8]) -> Option<(RawBitmap, FileInfo, Option<f32>)> {
+    let (header, pixels) = data.split_at_checked(HEADER_LEN)?;
+    if &header[0..4] != MAGIC {
+        return None;
+    }
+    let u32_at = |i: usize| u32::from_le_bytes(header[i..i + 4].try_into().unwrap());
+    let u64_at = |i: usize| u64::from_le_bytes(header[i..i + 8].try_into().unwrap());
+
+    let width = u32_at(4) as usize;
+    let height = u32_at(8) as usize;
+    if pixels.len() != width * height * 4 {
+        return None;
+    }
+
+    let file_info = FileInfo {
+        sample_format: SAMPLE_FORMAT.get(header[12] as usize).copied()?,
+        sample_count: u64_at(13),
+        center_freq: f64::from_bits(u64_at(21)),
+        sample_rate: f64::from_bits(u64_at(29)),
+    };
+    let clipped = Some(f32::from_bits(u32_at(37))).filter(|clipped| !clipped.is_nan());
+    let bitmap = RawBitmap {
+        pixels: pixels.to_vec(),
+        width,
+        height,
+    };
+
+    Some((bitmap, file_info, clipped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let bitmap = RawBitmap {
+            pixels: (0..4 * 3 * 2).map(|i| i as u8).collect(),
+            width: 3,
+            height: 2,
+        };
+        let file_info = FileInfo {
+            sample_format: "CS16",
+            sample_count: 1 << 40,
+            center_freq: 433.92e6,
+            sample_rate: 250e3,
+        };
+        for clipped in [Some(0.25), Some(0.0), None] {
+            let (decoded, info, decoded_clipped) = decode(&encode(&bitmap, &file_info, clipped)).unwrap();
+            assert_eq!((decoded.width, decoded.height), (3, 2));
+            assert_eq!(decoded.pixels, bitmap.pixels);
+            assert_eq!(info.sample_format, "CS16");
+            assert_eq!(info.sample_count, 1 << 40);
+            assert_eq!(info.center_freq, 433.92e6);
+            assert_eq!(info.sample_rate, 250e3);
+            assert_eq!(decoded_clipped, clipped);
+        }
+    }
+
+    #[test]
+    fn decode_rejects_invalid() {
+        let bitmap = RawBitmap {
+            pixels: vec![0; 4 * 2 * 2],
+            width: 2,
+            height: 2,
+        };
+        let file_info = FileInfo {
+            sample_format: "CU8",
+            sample_count: 0,
+            center_freq: 0.0,
+            sample_rate: 0.0,
+        };
+        let data = encode(&bitmap, &file_info, None);
+        assert!(decode(&data[..HEADER_LEN - 1]).is_none());
+        assert!(decode(&data[..data.len() - 1]).is_none());
+        let mut old = data;
+        old[..4].copy_from_slice(b"IQT1");
+        assert!(decode(&old).is_none());
+    }
+
+    #[test]
+    fn evict_keeps_recent_within_limit() {
+        let dir = std::env::temp_dir().join(format!("iqviewer-test-thumbcache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        // ten entries of 1000 bytes, each used a minute after the previous one
+        let start = SystemTime::now() - Duration::from_secs(3600);
+        for index in 0..10u64 {
+            let path = dir.join(format!("entry{index}"));
+            fs::write(&path, vec![0u8; 1000]).unwrap();
+            let used = start + Duration::from_secs(60 * index);
+            fs::File::options().write(true).open(&path).unwrap().set_modified(used).unwrap();
+        }
+
+        evict_dir(&dir, 4500);
+        let mut left: Vec<String> =
+            fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().file_name().to_string_lossy().into()).collect();
+        left.sort();
+        assert_eq!(left, ["entry6", "entry7", "entry8", "entry9"]);
+        let total: u64 = fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().metadata().unwrap().len()).sum();
+        assert!(total <= 4500);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
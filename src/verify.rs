@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (2025) Christian W. Zuckschwerdt
+
+//! I/Q Viewer -- Verify that files decode.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::dirs::read_dir_iq;
+use crate::plot_ffi::{Plot, sample_pair_size};
+
+/// Result of verifying a single file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Pass,
+    Warn(String),
+    Fail(String),
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pass => f.write_str("PASS"),
+            Self::Warn(reason) => write!(f, "WARN {reason}"),
+            Self::Fail(reason) => write!(f, "FAIL {reason}"),
+        }
+    }
+}
+
+/// Tries to decode the file, as `format` if given, and checks for empty or truncated data.
+pub fn verify_file(path: &Path, format: Option<&'static str>) -> Verdict {
+    let size = match fs::metadata(path) {
+        Ok(metadata) => metadata.len(),
+        Err(err) => return Verdict::Fail(err.to_string()),
+    };
+    if size == 0 {
+        return Verdict::Fail("empty file".into());
+    }
+    let Some(file_info) = Plot::probe(path, format) else {
+        return Verdict::Fail("can't decode".into());
+    };
+    if file_info.sample_count == 0 {
+        return Verdict::Fail("zero samples".into());
+    }
+
+    // SigMF data is wrapped, only raw files map bytes to samples directly
+    let is_sigmf = path.extension().is_some_and(|ext| ext.to_string_lossy().starts_with("sigmf"));
+    if let Some(pair_size) = sample_pair_size(file_info.sample_format).filter(|_| !is_sigmf) {
+        let trailing = size % pair_size as u64;
+        if trailing != 0 {
+            return Verdict::Warn(format!("truncated, {trailing} trailing bytes"));
+        }
+        let expected = file_info.sample_count * pair_size as u64;
+        if expected != size {
+            return Verdict::Warn(format!("size mismatch, {size} bytes for {expected}"));
+        }
+    }
+    if file_info.sample_rate.is_nan() || file_info.sample_rate <= 0.0 {
+        return Verdict::Warn("no sample rate".into());
+    }
+    Verdict::Pass
+}
+
+/// Verifies all files with their format overrides, failures and warnings first.
+pub fn verify_all<'a>(files: impl IntoIterator<Item = (&'a Path, Option<&'static str>)>) -> Vec<(PathBuf, Verdict)> {
+    let mut report: Vec<_> = files
+        .into_iter()
+        .map(|(path, format)| (path.to_path_buf(), verify_file(path, format)))
+        .collect();
+    report.sort_by_key(|(_, verdict)| match verdict {
+        Verdict::Fail(_) => 0,
+        Verdict::Warn(_) => 1,
+        Verdict::Pass => 2,
+    });
+    report
+}
+
+/// Handles `--verify <dir>...`, prints the report and returns false if any file failed.
+pub fn run_cli(args: &[String]) -> Result<bool, String> {
+    if args.is_empty() {
+        return Err("Usage: iqviewer --verify <dir>...".into());
+    }
+    let mut paths = vec![];
+    for arg in args {
+        let path = PathBuf::from(arg);
+        if path.is_dir() {
+            paths.extend(read_dir_iq(&path).map_err(|err| format!("{arg}: {err}"))?);
+        } else {
+            paths.push(path);
+        }
+    }
+
+    let report = verify_all(paths.iter().map(|path| (path.as_path(), None)));
+    for (path, verdict) in &report {
+        println!("{verdict}  {}", path.display());
+    }
+    let failed = report.iter().filter(|(_, v)| matches!(v, Verdict::Fail(_))).count();
+    let warned = report.iter().filter(|(_, v)| matches!(v, Verdict::Warn(_))).count();
+    println!("{} files, {failed} failed, {warned} warnings", report.len());
+    Ok(failed == 0)
+}
@@ -24,7 +24,8 @@ pub enum WatcherEvent {
 
 #[derive(Debug, Clone)]
 enum Cmd {
-    Watch(PathBuf),
+    /// Watches a folder, with its subfolders if recursive.
+    Watch(PathBuf, bool),
     Unwatch(PathBuf),
 }
 
@@ -51,8 +52,9 @@ pub fn watcher_subscription() -> impl Stream<Item = WatcherEvent> {
             iced::futures::select! {
                 res = receiver.select_next_some() => {
                     match res {
-                        Cmd::Watch(path) => {
-                            if watcher.watch(&path, notify::RecursiveMode::NonRecursive).is_ok() {
+                        Cmd::Watch(path, recursive) => {
+                            let mode = if recursive { notify::RecursiveMode::Recursive } else { notify::RecursiveMode::NonRecursive };
+                            if watcher.watch(&path, mode).is_ok() {
                                 output.send(WatcherEvent::Added(path)).await.expect("Send Added event");
                             }
                         }
@@ -113,16 +115,23 @@ impl FolderWatcher {
     }
 
     pub fn added(&mut self, path: PathBuf) {
-        self.paths.push(path);
+        if !self.paths.contains(&path) {
+            self.paths.push(path);
+        }
     }
 
     pub fn removed(&mut self, path: PathBuf) {
         self.paths.retain(|item| item != &path)
     }
 
-    pub fn watch(&mut self, path: PathBuf) {
+    /// Watches a folder, with all of its subfolders if `recursive`.
+    pub fn watch(&mut self, path: PathBuf, recursive: bool) {
+        // a folder given twice is watched once
+        if self.paths.contains(&path) {
+            return;
+        }
         self.sender
-            .try_send(Cmd::Watch(path))
+            .try_send(Cmd::Watch(path, recursive))
             .expect("Send Watch command")
     }
 
@@ -141,3 +150,138 @@ impl FolderWatcher {
         }
     }
 }
+
+/// File events collected over a short time, to refresh once per burst instead of per file.
+#[derive(Debug, Default)]
+pub struct EventBatch {
+    created: Vec<PathBuf>,
+    modified: Vec<PathBuf>,
+    removed: Vec<PathBuf>,
+}
+
+impl EventBatch {
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+
+    /// Collects a file event, other events are returned to be handled right away.
+    pub fn push(&mut self, event: WatcherEvent) -> Option<WatcherEvent> {
+        match event {
+            WatcherEvent::Create(paths) => {
+                for path in paths {
+                    // a file replaced by remove and create is already listed, it needs a refresh
+                    let replaced = self.removed.contains(&path);
+                    self.removed.retain(|p| *p != path);
+                    if replaced {
+                        if !self.modified.contains(&path) {
+                            self.modified.push(path);
+                        }
+                    } else {
+                        self.modified.retain(|p| *p != path);
+                        if !self.created.contains(&path) {
+                            self.created.push(path);
+                        }
+                    }
+                }
+            }
+            WatcherEvent::Modify(paths) => {
+                // a file created in this batch is read in full anyway
+                for path in paths {
+                    if !self.created.contains(&path) && !self.modified.contains(&path) {
+                        self.modified.push(path);
+                    }
+                }
+            }
+            WatcherEvent::Remove(paths) => {
+                for path in paths {
+                    self.created.retain(|p| *p != path);
+                    self.modified.retain(|p| *p != path);
+                    if !self.removed.contains(&path) {
+                        self.removed.push(path);
+                    }
+                }
+            }
+            event => return Some(event),
+        }
+        None
+    }
+
+    /// The collected events, removals first, and empties the batch.
+    pub fn drain(&mut self) -> Vec<WatcherEvent> {
+        let mut events = vec![];
+        if !self.removed.is_empty() {
+            events.push(WatcherEvent::Remove(std::mem::take(&mut self.removed)));
+        }
+        if !self.created.is_empty() {
+            events.push(WatcherEvent::Create(std::mem::take(&mut self.created)));
+        }
+        if !self.modified.is_empty() {
+            events.push(WatcherEvent::Modify(std::mem::take(&mut self.modified)));
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(PathBuf::from).collect()
+    }
+
+    /// The drained events as (kind, paths) for comparison.
+    fn drained(batch: &mut EventBatch) -> Vec<(&'static str, Vec<PathBuf>)> {
+        batch
+            .drain()
+            .into_iter()
+            .map(|event| match event {
+                WatcherEvent::Create(paths) => ("create", paths),
+                WatcherEvent::Modify(paths) => ("modify", paths),
+                WatcherEvent::Remove(paths) => ("remove", paths),
+                event => panic!("unexpected event {event:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn event_batch_merges_duplicates() {
+        let mut batch = EventBatch::default();
+        assert!(batch.is_empty());
+        batch.push(WatcherEvent::Create(paths(&["a.cu8", "b.cu8"])));
+        batch.push(WatcherEvent::Create(paths(&["a.cu8"])));
+        batch.push(WatcherEvent::Modify(paths(&["a.cu8", "c.cu8"])));
+        batch.push(WatcherEvent::Modify(paths(&["c.cu8"])));
+        assert!(!batch.is_empty());
+        assert_eq!(
+            drained(&mut batch),
+            vec![("create", paths(&["a.cu8", "b.cu8"])), ("modify", paths(&["c.cu8"]))]
+        );
+        assert!(batch.is_empty());
+    }
+
+    #[test]
+    fn event_batch_remove_cancels_create() {
+        let mut batch = EventBatch::default();
+        batch.push(WatcherEvent::Create(paths(&["a.cu8"])));
+        batch.push(WatcherEvent::Modify(paths(&["b.cu8"])));
+        batch.push(WatcherEvent::Remove(paths(&["a.cu8", "b.cu8"])));
+        assert_eq!(drained(&mut batch), vec![("remove", paths(&["a.cu8", "b.cu8"]))]);
+    }
+
+    #[test]
+    fn event_batch_replaced_file_is_modified() {
+        let mut batch = EventBatch::default();
+        batch.push(WatcherEvent::Remove(paths(&["a.cu8"])));
+        batch.push(WatcherEvent::Create(paths(&["a.cu8"])));
+        assert_eq!(drained(&mut batch), vec![("modify", paths(&["a.cu8"]))]);
+    }
+
+    #[test]
+    fn event_batch_passes_other_events() {
+        let mut batch = EventBatch::default();
+        let event = batch.push(WatcherEvent::Added(PathBuf::from("dir")));
+        assert!(matches!(event, Some(WatcherEvent::Added(path)) if path == PathBuf::from("dir")));
+        assert!(batch.is_empty());
+    }
+}
@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (2025) Christian W. Zuckschwerdt
+
+//! I/Q Viewer -- Spectral leakage of the FFT window functions.
+
+use std::f64::consts::PI;
+
+use crate::options::WindowFunctions;
+
+/// Window length and zero-padding factor for the response, fine enough for a tenth of a bin.
+const WINDOW_LEN: usize = 256;
+const OVERSAMPLE: usize = 16;
+
+/// Main-lobe width and highest sidelobe of a window's frequency response.
+#[derive(Debug, Clone, Copy)]
+pub struct Leakage {
+    /// Width of the main lobe at -3 dB, in FFT bins.
+    pub mainlobe_bins: f64,
+    /// Level of the highest sidelobe relative to the main lobe, in dB.
+    pub sidelobe_db: f64,
+}
+
+impl std::fmt::Display for Leakage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "3 dB width {:.2} bins, sidelobes {:.1} dB", self.mainlobe_bins, self.sidelobe_db)
+    }
+}
+
+/// Sum of cosines window `a0 - a1 cos(x) + a2 cos(2x) - ...` at `x` from 0 to 2π.
+fn cosine_sum(coeffs: &[f64], x: f64) -> f64 {
+    coeffs
+        .iter()
+        .enumerate()
+        .map(|(k, a)| {
+            let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+            sign * a * (k as f64 * x).cos()
+        })
+        .sum()
+}
+
+/// Window coefficients, the Gaussian with sigma 0.4 as an approximation.
+pub fn coefficients(window: WindowFunctions, len: usize) -> Vec<f64> {
+    let last = (len.max(2) - 1) as f64;
+    (0..len)
+        .map(|n| {
+            let t = n as f64 / last; // 0..1
+            let x = 2.0 * PI * t;
+            match window {
+                WindowFunctions::Rectangular => 1.0,
+                WindowFunctions::Bartlett => 1.0 - (2.0 * t - 1.0).abs(),
+                WindowFunctions::Hann => cosine_sum(&[0.5, 0.5], x),
+                WindowFunctions::Hamming => cosine_sum(&[0.54, 0.46], x),
+                WindowFunctions::Blackman => cosine_sum(&[0.42, 0.5, 0.08], x),
+                WindowFunctions::BlackmanHarris => cosine_sum(&[0.35875, 0.48829, 0.14128, 0.01168], x),
+                WindowFunctions::BlackmanNuttall => {
+                    cosine_sum(&[0.3635819, 0.4891775, 0.1365995, 0.0106411], x)
+                }
+                WindowFunctions::FlatTop => {
+                    cosine_sum(&[0.21557895, 0.41663158, 0.277263158, 0.083578947, 0.006947368], x)
+                }
+                WindowFunctions::BartlettHann => 0.62 - 0.48 * (t - 0.5).abs() - 0.38 * x.cos(),
+                WindowFunctions::Cosine => (PI * t).sin(),
+                WindowFunctions::Lanczos => {
+                    let y = PI * (2.0 * t - 1.0);
+                    if y == 0.0 { 1.0 } else { y.sin() / y }
+                }
+                WindowFunctions::Gaussian => (-0.5 * ((2.0 * t - 1.0) / 0.4).powi(2)).exp(),
+            }
+        })
+        .collect()
+}
+
+/// Main-lobe width and sidelobe level of the `window`, from its zero-padded DFT over half a spectrum.
+pub fn leakage(window: WindowFunctions) -> Leakage {
+    let coeffs = coefficients(window, WINDOW_LEN);
+    let points = WINDOW_LEN * OVERSAMPLE;
+    // response in dB relative to DC, up to half the spectrum
+    let response: Vec<f64> = (0..points / 2)
+        .map(|k| {
+            let w = 2.0 * PI * k as f64 / points as f64;
+            let (re, im) = coeffs.iter().enumerate().fold((0.0, 0.0), |(re, im), (n, c)| {
+                (re + c * (w * n as f64).cos(), im - c * (w * n as f64).sin())
+            });
+            (re * re + im * im).sqrt()
+        })
+        .collect();
+    let peak = response[0].max(f64::MIN_POSITIVE);
+    let db: Vec<f64> = response.iter().map(|m| 20.0 * (m / peak).max(1e-12).log10()).collect();
+
+    let half_power = db.iter().position(|&level| level < -3.01).unwrap_or(db.len());
+    // the main lobe ends at the first local minimum
+    let lobe_end = (1..db.len() - 1)
+        .find(|&k| db[k] <= db[k - 1] && db[k] < db[k + 1])
+        .unwrap_or(db.len() - 1);
+    let sidelobe_db = db[lobe_end..].iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    Leakage {
+        mainlobe_bins: 2.0 * half_power as f64 / OVERSAMPLE as f64,
+        sidelobe_db,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Published 3 dB widths and highest sidelobes, e.g. from Harris 1978.
+    const KNOWN: &[(WindowFunctions, f64, f64)] = &[
+        (WindowFunctions::Rectangular, 0.89, -13.3),
+        (WindowFunctions::Hann, 1.44, -31.5),
+        (WindowFunctions::Hamming, 1.30, -42.7),
+        (WindowFunctions::Blackman, 1.68, -58.1),
+    ];
+
+    #[test]
+    fn leakage_known_figures() {
+        for &(window, mainlobe_bins, sidelobe_db) in KNOWN {
+            let leakage = leakage(window);
+            // the width is resolved to an eighth of a bin
+            assert!((leakage.mainlobe_bins - mainlobe_bins).abs() < 0.15, "{window:?} {leakage}");
+            assert!((leakage.sidelobe_db - sidelobe_db).abs() < 0.5, "{window:?} {leakage}");
+        }
+    }
+
+    #[test]
+    fn leakage_orders_windows() {
+        // lower sidelobes are traded for a wider main lobe
+        let rectangular = leakage(WindowFunctions::Rectangular);
+        let blackman_harris = leakage(WindowFunctions::BlackmanHarris);
+        assert!(blackman_harris.sidelobe_db < -90.0, "{blackman_harris}");
+        assert!(blackman_harris.mainlobe_bins > rectangular.mainlobe_bins);
+    }
+}